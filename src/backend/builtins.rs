@@ -0,0 +1,46 @@
+// The builtin functions for expr-lang, as declared in the std env.
+
+use super::runtime::{ExprValue, List};
+use crate::diagnostics::codes::ErrorCode;
+use crate::error::{ExprError, ExprResult};
+
+/// Call the builtin function `name` with the (already type-checked) args.
+pub fn call(name: &str, args: Vec<ExprValue>) -> ExprResult<ExprValue> {
+    match (name, args.as_slice()) {
+        ("len", [ExprValue::Str(sval)]) => len(sval),
+        ("len", [ExprValue::List(list)]) => len_list(list),
+        ("push", [ExprValue::List(list), value]) => {
+            list.push(value.clone());
+            Ok(ExprValue::Unit)
+        }
+        ("pop", [ExprValue::List(list)]) => list.pop(),
+        ("some", [value]) => Ok(ExprValue::Option(Some(Box::new(value.clone())))),
+        ("is_some", [ExprValue::Option(opt)]) => Ok(ExprValue::Bool(opt.is_some())),
+        _ => Err(ExprError::new(
+            ErrorCode::InvalidOperandValues,
+            format!("cannot call {} with {} argument(s)", name, args.len()),
+        )),
+    }
+}
+
+/// The number of characters (not bytes) in a string.
+fn len(sval: &str) -> ExprResult<ExprValue> {
+    i32::try_from(sval.chars().count())
+        .map(ExprValue::Int)
+        .map_err(|_| {
+            ExprError::new(
+                ErrorCode::ArithmeticOverflow,
+                "the length of the string does not fit in an int".to_owned(),
+            )
+        })
+}
+
+/// The number of elements in a list.
+fn len_list(list: &List) -> ExprResult<ExprValue> {
+    i32::try_from(list.len()).map(ExprValue::Int).map_err(|_| {
+        ExprError::new(
+            ErrorCode::ArithmeticOverflow,
+            "the length of the list does not fit in an int".to_owned(),
+        )
+    })
+}
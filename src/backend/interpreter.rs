@@ -1,219 +1,898 @@
-use super::runtime::{ExprValue, Runtime};
+use super::builtins;
+use super::runtime::{ExprValue, Function, List, Record, Runtime, Variant};
+use crate::diagnostics::codes::ErrorCode;
+use crate::error::{ExprError, ExprResult};
 use crate::front::ast::*;
-use crate::middle::visitor::VisitorMut;
+use crate::middle::visitor::Visitor;
+use std::sync::Arc;
+use std::thread;
 
+/// Why the evaluation of an expression stopped before it produced a value:
+/// either an error, or a break or a continue, which unwind to the innermost
+/// enclosing loop.
+#[derive(Debug)]
+pub enum Unwind {
+    Error(ExprError),
+    Break,
+    Continue,
+}
+
+impl From<ExprError> for Unwind {
+    fn from(err: ExprError) -> Self {
+        Unwind::Error(err)
+    }
+}
+
+/// Where an assignment puts its value: a variable, an element of a list or a
+/// field of a record.
+enum Place {
+    Var(String),
+    Elem(Arc<List>, i32),
+    Field(Arc<Record>, String),
+}
+
+#[derive(Clone)]
 pub struct Interpreter {
     runtime: Runtime,
+    max_call_depth: usize,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
 }
 
 impl Interpreter {
+    /// The number of calls that can be nested by default.
+    pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+    /// The largest limit on nested calls, whose stack (of a few gigabytes)
+    /// can still be reserved.
+    pub const MAX_CALL_DEPTH: usize = 100_000;
+
+    /// The stack of the interpreter grows with the number of nested calls, so
+    /// it runs on a thread with a stack that is large enough for
+    /// `max_call_depth` of them (plus some for the expressions in between).
+    const BASE_STACK_SIZE: usize = 16 * 1024 * 1024;
+    const STACK_SIZE_PER_CALL: usize = 32 * 1024;
+
     pub fn new() -> Self {
         Interpreter {
             runtime: Runtime::new(),
+            max_call_depth: Interpreter::DEFAULT_MAX_CALL_DEPTH,
         }
     }
 
-    pub fn interpret(&mut self, ast: &mut Ast) {
-        match self.visit_ast(ast) {
-            ExprValue::Int(ival) => println!("{}", ival),
-            ExprValue::Float(fval) => println!("{}", fval),
-            ExprValue::Bool(bval) => println!("{}", bval),
-            ExprValue::None => {}
+    /// Limit the number of nested calls to `max_call_depth`, or to
+    /// `MAX_CALL_DEPTH` if that is smaller.
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth.min(Interpreter::MAX_CALL_DEPTH);
+    }
+
+    pub fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+
+    /// Evaluate the ast, returning the value of its last expression.
+    pub fn evaluate(&mut self, ast: &Ast) -> ExprResult<ExprValue> {
+        let stack_size = Interpreter::STACK_SIZE_PER_CALL
+            .saturating_mul(self.max_call_depth)
+            .saturating_add(Interpreter::BASE_STACK_SIZE);
+
+        let max_call_depth = self.max_call_depth;
+        let result = thread::scope(|scope| {
+            match thread::Builder::new()
+                .stack_size(stack_size)
+                .spawn_scoped(scope, || self.visit_ast(ast))
+            {
+                Ok(handle) => handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic)),
+                Err(err) => Err(Unwind::Error(ExprError::new(
+                    ErrorCode::CallDepthExceeded,
+                    format!(
+                        "could not reserve the stack for {} nested calls: {}",
+                        max_call_depth, err
+                    ),
+                ))),
+            }
+        });
+
+        match result {
+            Ok(val) => Ok(val),
+            Err(Unwind::Error(err)) => Err(err),
+            // the checker only allows break and continue inside loops.
+            Err(Unwind::Break) | Err(Unwind::Continue) => Err(ExprError::new(
+                ErrorCode::BreakOutsideLoop,
+                "break or continue outside of a loop".to_owned(),
+            )),
         }
     }
-}
 
-impl VisitorMut for Interpreter {
-    type Result = ExprValue;
+    /// Call a user-defined function in a frame of its own, in which only the
+    /// function itself, its captures and its parameters are bound.
+    fn call_function(
+        &mut self,
+        func: Arc<Function>,
+        args: Vec<ExprValue>,
+    ) -> ExprResult<ExprValue> {
+        if self.runtime.depth() >= self.max_call_depth {
+            return Err(ExprError::new(
+                ErrorCode::CallDepthExceeded,
+                format!(
+                    "calls are nested more than {} deep while calling {}",
+                    self.max_call_depth,
+                    func.name.as_deref().unwrap_or("a closure")
+                ),
+            ));
+        }
 
-    fn visit_assign_expr(&mut self, ass_expr: &mut AssignExpr) -> Self::Result {
-        let var_name_val = self.visit_expr(&mut ass_expr.vname);
-        let var_val = self.visit_expr(&mut ass_expr.expr);
+        self.runtime.open_frame();
+        for (name, val) in &func.captures {
+            self.runtime.save_binding(name, val.clone());
+        }
+        if let Some(ref name) = func.name {
+            self.runtime
+                .save_binding(name, ExprValue::Function(func.clone()));
+        }
+        for (param, arg) in func.params.iter().zip(args) {
+            self.runtime.save_binding(param, arg);
+        }
 
-        if let Expr::VnameExpr(ref vname_expr) = *ass_expr.vname {
-            match ass_expr.op {
-                BinaryOperator::Assign => {
-                    self.runtime
-                        .save_binding(&vname_expr.id.spelling, var_val.clone());
-                    var_val
-                }
+        let val = self.visit_expr(&func.body);
+        self.runtime.close_frame();
 
-                BinaryOperator::AddAssign => {
-                    let sum_val = var_name_val + var_val;
-                    self.runtime
-                        .save_binding(&vname_expr.id.spelling, sum_val.clone());
-                    sum_val
-                }
+        match val {
+            Ok(val) => Ok(val),
+            Err(Unwind::Error(err)) => Err(err),
+            // the checker only allows break and continue inside loops, and
+            // a loop in the caller does not enclose the body.
+            Err(Unwind::Break) | Err(Unwind::Continue) => Err(ExprError::new(
+                ErrorCode::BreakOutsideLoop,
+                "break or continue outside of a loop".to_owned(),
+            )),
+        }
+    }
 
-                BinaryOperator::SubAssign => {
-                    let sub_val = var_name_val.clone() - var_val.clone();
-                    self.runtime
-                        .save_binding(&vname_expr.id.spelling, sub_val.clone());
-                    sub_val
-                }
+    /// Apply ++ or -- to the variable `unary_expr.elem`, whose value is
+    /// `old_val`. The prefix forms have the new value, and the postfix forms
+    /// the old one.
+    fn increment(&mut self, unary_expr: &UnaryExpr, old_val: ExprValue) -> ExprResult<ExprValue> {
+        let vname = match *unary_expr.elem {
+            Expr::VnameExpr(ref vname) => vname,
+            _ => {
+                return Err(ExprError::new(
+                    ErrorCode::InvalidAssignmentTargetValue,
+                    format!("the operand of {} must be a variable", unary_expr.op),
+                ))
+            }
+        };
 
-                BinaryOperator::MulAssign => {
-                    let mul_val = var_name_val.clone() * var_val.clone();
-                    self.runtime
-                        .save_binding(&vname_expr.id.spelling, mul_val.clone());
-                    mul_val
-                }
+        let new_val = match unary_expr.op {
+            UnaryOperator::PostfixIncrement | UnaryOperator::PrefixIncrement => {
+                old_val.clone() + ExprValue::Int(1)
+            }
+            _ => old_val.clone() - ExprValue::Int(1),
+        }?;
+        self.runtime
+            .update_binding(&vname.id.spelling, new_val.clone());
 
-                BinaryOperator::DivAssign => {
-                    let div_val = var_name_val.clone() / var_val.clone();
-                    self.runtime
-                        .save_binding(&vname_expr.id.spelling, div_val.clone());
-                    div_val
-                }
+        match unary_expr.op {
+            UnaryOperator::PostfixDecrement | UnaryOperator::PostfixIncrement => Ok(old_val),
+            _ => Ok(new_val),
+        }
+    }
 
-                BinaryOperator::ModAssign => {
-                    let mod_val = var_name_val.clone() % var_val.clone();
-                    self.runtime
-                        .save_binding(&vname_expr.id.spelling, mod_val.clone());
-                    mod_val
-                }
+    /// Evaluate the list and the index of an index expr.
+    fn index_operands(&mut self, index_expr: &IndexExpr) -> Result<(Arc<List>, i32), Unwind> {
+        let list_val = self.visit_expr(&index_expr.list)?;
+        let index_val = self.visit_expr(&index_expr.index)?;
 
-                BinaryOperator::BitwiseAndAssign => {
-                    let bitand_val = var_name_val.clone() & var_val.clone();
-                    self.runtime
-                        .save_binding(&vname_expr.id.spelling, bitand_val.clone());
-                    bitand_val
-                }
+        match (list_val, index_val) {
+            (ExprValue::List(list), ExprValue::Int(index)) => Ok((list, index)),
+            (list_val, index_val) => Err(ExprError::with_span(
+                ErrorCode::InvalidOperandValues,
+                format!("cannot index into {} with {}", list_val, index_val),
+                index_expr.span.clone(),
+            )
+            .into()),
+        }
+    }
 
-                BinaryOperator::BitwiseOrAssign => {
-                    let bitor_val = var_name_val.clone() | var_val.clone();
-                    self.runtime
-                        .save_binding(&vname_expr.id.spelling, bitor_val.clone());
-                    bitor_val
-                }
-                BinaryOperator::BitwiseXorAssign => {
-                    let bitxor_val = var_name_val.clone() ^ var_val.clone();
-                    self.runtime
-                        .save_binding(&vname_expr.id.spelling, bitxor_val.clone());
-                    bitxor_val
+    /// Assign `val` to `target`, which is a tuple of targets or one of its
+    /// elements.
+    fn destructure(&mut self, target: &Expr, val: ExprValue) -> Result<(), Unwind> {
+        match (target, val) {
+            (Expr::TupleExpr(ref tuple_expr), ExprValue::Tuple(vals))
+                if vals.len() == tuple_expr.elems.len() =>
+            {
+                for (elem, val) in tuple_expr.elems.iter().zip(vals) {
+                    self.destructure(elem, val)?;
                 }
+            }
+            (Expr::TupleExpr(ref tuple_expr), val) => {
+                return Err(ExprError::with_span(
+                    ErrorCode::InvalidAssignmentTargetValue,
+                    format!("cannot assign {} to this", val),
+                    tuple_expr.span.clone(),
+                )
+                .into())
+            }
+            (target, val) => {
+                let place = self.place(target)?;
+                self.store(place, val)
+                    .map_err(|err| err.or_span(target.span()))?;
+            }
+        }
 
-                BinaryOperator::LeftShiftAssign => {
-                    let shl_val = var_name_val.clone() + var_val.clone();
-                    self.runtime
-                        .save_binding(&vname_expr.id.spelling, shl_val.clone());
-                    shl_val
-                }
+        Ok(())
+    }
 
-                BinaryOperator::RightShiftAssign => {
-                    let shr_val = var_name_val.clone() >> var_val.clone();
-                    self.runtime
-                        .save_binding(&vname_expr.id.spelling, shr_val.clone());
-                    shr_val
-                }
+    /// Match `val` against `pattern`, collecting the names that it binds
+    /// along with their values.
+    fn match_pattern(
+        pattern: &Pattern,
+        val: &ExprValue,
+        bindings: &mut Vec<(String, ExprValue)>,
+    ) -> bool {
+        match (pattern, val) {
+            (Pattern::Wildcard(_), _) => true,
+            (Pattern::Binding(ref vname), val) => {
+                bindings.push((vname.id.spelling.clone(), val.clone()));
+                true
+            }
+            (Pattern::Bool(ref bool_expr), ExprValue::Bool(bval)) => bool_expr.value == *bval,
+            (Pattern::Int(ref int_expr), ExprValue::Int(ival)) => int_expr.value == *ival,
+            (Pattern::Str(ref str_expr), ExprValue::Str(sval)) => str_expr.value == *sval,
+            (Pattern::Variant(ref variant_pattern), ExprValue::Variant(variant)) => {
+                variant_pattern.name.spelling == variant.name
+                    && variant_pattern.args.len() == variant.payload.len()
+                    && variant_pattern
+                        .args
+                        .iter()
+                        .zip(&variant.payload)
+                        .all(|(arg, val)| Interpreter::match_pattern(arg, val, bindings))
+            }
+            _ => false,
+        }
+    }
+
+    /// Evaluate the parts of an assignment target that say where the value
+    /// goes, i.e., the list and the index of an element or the record of a
+    /// field.
+    fn place(&mut self, target: &Expr) -> Result<Place, Unwind> {
+        match *target {
+            Expr::VnameExpr(ref vname_expr) => Ok(Place::Var(vname_expr.id.spelling.clone())),
+            Expr::IndexExpr(ref index_expr) => {
+                let (list, index) = self.index_operands(index_expr)?;
+                Ok(Place::Elem(list, index))
+            }
+            Expr::FieldExpr(ref field_expr) => match self.visit_expr(&field_expr.expr)? {
+                ExprValue::Record(record) => Ok(Place::Field(record, field_expr.field.clone())),
+                val => Err(ExprError::with_span(
+                    ErrorCode::InvalidAssignmentTargetValue,
+                    format!("cannot assign to field {} of {}", field_expr.field, val),
+                    field_expr.span.clone(),
+                )
+                .into()),
+            },
+            ref target => Err(ExprError::with_span(
+                ErrorCode::InvalidAssignmentTargetValue,
+                "the lhs of an assignment expression must be a vname, an index expr or a field expr"
+                    .to_owned(),
+                target.span().clone(),
+            )
+            .into()),
+        }
+    }
+
+    fn load(&self, place: &Place) -> ExprResult<ExprValue> {
+        match *place {
+            Place::Var(ref name) => self.lookup(name),
+            Place::Elem(ref list, index) => list.get(index),
+            Place::Field(ref record, ref field) => record.get(field),
+        }
+    }
 
-                _ => unreachable!(),
+    fn store(&mut self, place: Place, val: ExprValue) -> ExprResult<()> {
+        match place {
+            Place::Var(name) => {
+                self.runtime.update_binding(&name, val);
+                Ok(())
             }
-        } else {
-            ExprValue::None
+            Place::Elem(list, index) => list.set(index, val),
+            Place::Field(record, field) => record.set(&field, val),
         }
     }
 
-    fn visit_ast(&mut self, ast: &mut Ast) -> Self::Result {
-        let mut resp = ExprValue::None;
+    /// The value bound to the variable `name`, which the checker has
+    /// validated is declared.
+    fn lookup(&self, name: &str) -> ExprResult<ExprValue> {
+        self.runtime.get_binding(name).ok_or_else(|| {
+            ExprError::new(
+                ErrorCode::UnboundVariable,
+                format!("no value is bound to {}", name),
+            )
+        })
+    }
+
+    /// The current values of the names captured by a function or a closure.
+    fn captures(&self, names: &[String]) -> Vec<(String, ExprValue)> {
+        // builtins are not bound in the runtime, so they are not captured.
+        names
+            .iter()
+            .filter_map(|name| Some((name.clone(), self.runtime.get_binding(name)?)))
+            .collect()
+    }
+
+    /// Run the body of a loop once, returning whether the loop should go on.
+    fn run_loop_body(&mut self, body: &Expr) -> ExprResult<bool> {
+        match self.visit_expr(body) {
+            Ok(_) | Err(Unwind::Continue) => Ok(true),
+            Err(Unwind::Break) => Ok(false),
+            Err(Unwind::Error(err)) => Err(err),
+        }
+    }
 
-        for expr in &mut ast.exprs {
-            resp = self.visit_expr(expr);
+    pub fn interpret(&mut self, ast: &Ast) -> ExprResult<()> {
+        match self.evaluate(ast)? {
+            ExprValue::Unit => {}
+            val => println!("{}", val),
         }
 
-        resp
+        Ok(())
     }
+}
+
+impl Visitor for Interpreter {
+    type Result = Result<ExprValue, Unwind>;
 
-    fn visit_binary_expr(&mut self, bin_expr: &mut BinaryExpr) -> Self::Result {
-        let lhs_val = self.visit_expr(&mut bin_expr.lhs);
-        let rhs_val = self.visit_expr(&mut bin_expr.rhs);
+    fn visit_assign_expr(&mut self, ass_expr: &AssignExpr) -> Self::Result {
+        // the whole rhs is evaluated before any target of a tuple is assigned
+        // to, so that (a, b) = (b, a) swaps a and b.
+        if let Expr::TupleExpr(_) = *ass_expr.target {
+            let val = self.visit_expr(&ass_expr.expr)?;
+            self.destructure(&ass_expr.target, val.clone())?;
+            return Ok(val);
+        }
+
+        // the list and the index of an indexed target, or the record of a
+        // field, are only evaluated once, before the rhs.
+        let place = self.place(&ass_expr.target)?;
+        let var_name_val = self
+            .load(&place)
+            .map_err(|err| err.or_span(ass_expr.target.span()))?;
+        let var_val = self.visit_expr(&ass_expr.expr)?;
 
-        match bin_expr.op {
+        let new_val = match ass_expr.op {
+            BinaryOperator::Assign => Ok(var_val),
+            BinaryOperator::AddAssign => var_name_val + var_val,
+            BinaryOperator::SubAssign => var_name_val - var_val,
+            BinaryOperator::MulAssign => var_name_val * var_val,
+            BinaryOperator::DivAssign => var_name_val / var_val,
+            BinaryOperator::ModAssign => var_name_val % var_val,
+            BinaryOperator::BitwiseAndAssign => var_name_val & var_val,
+            BinaryOperator::BitwiseOrAssign => var_name_val | var_val,
+            BinaryOperator::BitwiseXorAssign => var_name_val ^ var_val,
+            BinaryOperator::LeftShiftAssign => var_name_val << var_val,
+            BinaryOperator::RightShiftAssign => var_name_val >> var_val,
+            op => Err(ExprError::new(
+                ErrorCode::UnsupportedOperator,
+                format!("unsupported assignment operator {:?}", op),
+            )),
+        }
+        .map_err(|err| err.or_span(&ass_expr.span))?;
+
+        self.store(place, new_val.clone())
+            .map_err(|err| err.or_span(ass_expr.target.span()))?;
+
+        Ok(new_val)
+    }
+
+    fn visit_ast(&mut self, ast: &Ast) -> Self::Result {
+        let mut resp = ExprValue::Unit;
+
+        for expr in &ast.exprs {
+            resp = self.visit_expr(expr)?;
+        }
+
+        Ok(resp)
+    }
+
+    fn visit_binary_expr(&mut self, bin_expr: &BinaryExpr) -> Self::Result {
+        let lhs_val = self.visit_expr(&bin_expr.lhs)?;
+
+        // the default of ?? is only evaluated if the option is none.
+        if bin_expr.op == BinaryOperator::Coalesce {
+            return match lhs_val {
+                ExprValue::Option(Some(val)) => Ok(*val),
+                ExprValue::Option(None) => self.visit_expr(&bin_expr.rhs),
+                lhs_val => Err(ExprError::with_span(
+                    ErrorCode::InvalidOperandValues,
+                    format!("cannot apply operator ?? to {}", lhs_val),
+                    bin_expr.span.clone(),
+                )
+                .into()),
+            };
+        }
+
+        // the rhs of && is only evaluated if the lhs is true, and that of ||
+        // only if the lhs is false.
+        if matches!(
+            bin_expr.op,
+            BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr
+        ) {
+            let short_circuit = bin_expr.op == BinaryOperator::LogicalOr;
+            let val = match lhs_val {
+                ExprValue::Bool(lhs_val) if lhs_val == short_circuit => ExprValue::Bool(lhs_val),
+                ExprValue::Bool(_) => self.visit_expr(&bin_expr.rhs)?,
+                val => val,
+            };
+            return match val {
+                ExprValue::Bool(_) => Ok(val),
+                val => Err(ExprError::with_span(
+                    ErrorCode::InvalidOperandValues,
+                    format!("cannot apply operator {} to {}", bin_expr.op, val),
+                    bin_expr.span.clone(),
+                )
+                .into()),
+            };
+        }
+
+        let rhs_val = self.visit_expr(&bin_expr.rhs)?;
+
+        let val = match bin_expr.op {
             BinaryOperator::Add => lhs_val + rhs_val,
             BinaryOperator::BitwiseAnd => lhs_val & rhs_val,
             BinaryOperator::BitwiseOr => lhs_val | rhs_val,
             BinaryOperator::BitwiseXor => lhs_val ^ rhs_val,
             BinaryOperator::Div => lhs_val / rhs_val,
-            BinaryOperator::Equal => ExprValue::Bool(lhs_val == rhs_val),
-            BinaryOperator::GreaterThan => ExprValue::Bool(lhs_val > rhs_val),
-            BinaryOperator::GreaterThanOrEqual => ExprValue::Bool(lhs_val >= rhs_val),
+            BinaryOperator::Equal => Ok(ExprValue::Bool(lhs_val == rhs_val)),
+            BinaryOperator::GreaterThan => Ok(ExprValue::Bool(lhs_val > rhs_val)),
+            BinaryOperator::GreaterThanOrEqual => Ok(ExprValue::Bool(lhs_val >= rhs_val)),
             BinaryOperator::LeftShift => lhs_val << rhs_val,
-            BinaryOperator::LessThan => ExprValue::Bool(lhs_val < rhs_val),
-            BinaryOperator::LessThanOrEqual => ExprValue::Bool(lhs_val <= rhs_val),
-            BinaryOperator::LogicalAnd => {
-                if let ExprValue::Bool(lhs_val) = lhs_val {
-                    if let ExprValue::Bool(rhs_val) = rhs_val {
-                        ExprValue::Bool(lhs_val && rhs_val)
-                    } else {
-                        ExprValue::None
-                    }
-                } else {
-                    ExprValue::None
-                }
-            }
-            BinaryOperator::LogicalOr => {
-                if let ExprValue::Bool(lhs_val) = lhs_val {
-                    if let ExprValue::Bool(rhs_val) = rhs_val {
-                        ExprValue::Bool(lhs_val || rhs_val)
-                    } else {
-                        ExprValue::None
-                    }
-                } else {
-                    ExprValue::None
-                }
-            }
+            BinaryOperator::LessThan => Ok(ExprValue::Bool(lhs_val < rhs_val)),
+            BinaryOperator::LessThanOrEqual => Ok(ExprValue::Bool(lhs_val <= rhs_val)),
             BinaryOperator::Mod => lhs_val % rhs_val,
             BinaryOperator::Mul => lhs_val * rhs_val,
-            BinaryOperator::NotEqual => ExprValue::Bool(lhs_val == rhs_val),
+            BinaryOperator::NotEqual => Ok(ExprValue::Bool(lhs_val != rhs_val)),
             BinaryOperator::RightShift => lhs_val >> rhs_val,
             BinaryOperator::Sub => lhs_val - rhs_val,
 
-            _ => unreachable!(),
+            op => Err(ExprError::new(
+                ErrorCode::UnsupportedOperator,
+                format!("unsupported binary operator {:?}", op),
+            )),
+        };
+
+        Ok(val.map_err(|err| err.or_span(&bin_expr.span))?)
+    }
+
+    fn visit_block_expr(&mut self, block_expr: &BlockExpr) -> Self::Result {
+        self.runtime.open_level();
+
+        let mut val = Ok(ExprValue::Unit);
+        for expr in &block_expr.exprs {
+            val = self.visit_expr(expr);
+            if val.is_err() {
+                break;
+            }
+        }
+
+        self.runtime.close_level();
+        val
+    }
+
+    fn visit_bool_expr(&mut self, bool_expr: &BoolExpr) -> Self::Result {
+        Ok(ExprValue::Bool(bool_expr.value))
+    }
+
+    fn visit_break_expr(&mut self, _break_expr: &BreakExpr) -> Self::Result {
+        Err(Unwind::Break)
+    }
+
+    fn visit_call_expr(&mut self, call_expr: &CallExpr) -> Self::Result {
+        // a name that is not bound in the runtime is that of a builtin.
+        let callee = match *call_expr.callee {
+            Expr::VnameExpr(ref vname) => self
+                .runtime
+                .get_binding(&vname.id.spelling)
+                .ok_or_else(|| vname.id.spelling.clone()),
+            ref callee => Ok(self.visit_expr(callee)?),
+        };
+
+        let mut args = Vec::new();
+        for arg in &call_expr.args {
+            args.push(self.visit_expr(arg)?);
+        }
+
+        let val = match callee {
+            Ok(ExprValue::Function(func)) => self.call_function(func, args),
+            // a variant with a payload is called to give it its payload.
+            Ok(ExprValue::Variant(variant)) => Ok(ExprValue::Variant(Arc::new(Variant::new(
+                variant.enum_name.clone(),
+                variant.name.clone(),
+                args,
+            )))),
+            Ok(val) => Err(ExprError::with_span(
+                ErrorCode::InvalidOperandValues,
+                format!("only functions can be called, not {}", val),
+                call_expr.callee.span().clone(),
+            )),
+            Err(name) => builtins::call(&name, args),
+        };
+
+        Ok(val.map_err(|err| err.or_span(&call_expr.span))?)
+    }
+
+    fn visit_cast_expr(&mut self, cast_expr: &CastExpr) -> Self::Result {
+        let val = self.visit_expr(&cast_expr.expr)?;
+
+        match (val, &cast_expr.target) {
+            (ExprValue::Int(ival), Type::FloatType) => Ok(ExprValue::Float(ival as f64)),
+            // rounds towards zero, which is exact for any float in range.
+            (ExprValue::Float(fval), Type::IntType) => {
+                if fval.is_finite() && fval > i32::MIN as f64 - 1.0 && fval < i32::MAX as f64 + 1.0
+                {
+                    Ok(ExprValue::Int(fval as i32))
+                } else {
+                    Err(ExprError::with_span(
+                        ErrorCode::FloatToIntOverflow,
+                        format!("{:?} cannot be converted to int", fval),
+                        cast_expr.span.clone(),
+                    )
+                    .into())
+                }
+            }
+            (val @ ExprValue::Int(_), Type::IntType)
+            | (val @ ExprValue::Float(_), Type::FloatType) => Ok(val),
+            (val, target) => Err(ExprError::with_span(
+                ErrorCode::InvalidOperandValues,
+                format!("cannot convert {} to {}", val, target),
+                cast_expr.span.clone(),
+            )
+            .into()),
         }
     }
 
-    fn visit_bool_expr(&mut self, bool_val: &mut bool) -> Self::Result {
-        ExprValue::Bool(*bool_val)
+    fn visit_continue_expr(&mut self, _continue_expr: &ContinueExpr) -> Self::Result {
+        Err(Unwind::Continue)
     }
 
-    fn visit_expr(&mut self, expr: &mut Expr) -> Self::Result {
+    fn visit_enum_expr(&mut self, enum_expr: &EnumExpr) -> Self::Result {
+        for variant in &enum_expr.variants {
+            let val = Variant::new(
+                enum_expr.name.spelling.clone(),
+                variant.name.spelling.clone(),
+                Vec::new(),
+            );
+            self.runtime
+                .save_binding(&variant.name.spelling, ExprValue::Variant(Arc::new(val)));
+        }
+
+        Ok(ExprValue::Unit)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) -> Self::Result {
         match expr {
-            Expr::AssignExpr(ref mut ass_expr) => self.visit_assign_expr(ass_expr),
-            Expr::BinaryExpr(ref mut bin_expr) => self.visit_binary_expr(bin_expr),
-            Expr::BoolExpr(ref mut bool_expr) => self.visit_bool_expr(bool_expr),
-            Expr::IntegerExpr(ref mut int_expr) => self.visit_integer_expr(int_expr),
-            Expr::PrintExpr(ref mut print_expr) => self.visit_print_expr(print_expr),
-            Expr::UnaryExpr(ref mut unary_expr) => self.visit_unary_expr(unary_expr),
-            Expr::VnameExpr(ref mut vname_expr) => self.visit_vname_expr(vname_expr),
+            Expr::AssignExpr(ref ass_expr) => self.visit_assign_expr(ass_expr),
+            Expr::BinaryExpr(ref bin_expr) => self.visit_binary_expr(bin_expr),
+            Expr::BlockExpr(ref block_expr) => self.visit_block_expr(block_expr),
+            Expr::BoolExpr(ref bool_expr) => self.visit_bool_expr(bool_expr),
+            Expr::BreakExpr(ref break_expr) => self.visit_break_expr(break_expr),
+            Expr::CallExpr(ref call_expr) => self.visit_call_expr(call_expr),
+            Expr::CastExpr(ref cast_expr) => self.visit_cast_expr(cast_expr),
+            Expr::ContinueExpr(ref continue_expr) => self.visit_continue_expr(continue_expr),
+            Expr::FieldExpr(ref field_expr) => self.visit_field_expr(field_expr),
+            Expr::FloatExpr(ref float_expr) => self.visit_float_expr(float_expr),
+            Expr::FnExpr(ref fn_expr) => self.visit_fn_expr(fn_expr),
+            Expr::LambdaExpr(ref lambda_expr) => self.visit_lambda_expr(lambda_expr),
+            Expr::ForExpr(ref for_expr) => self.visit_for_expr(for_expr),
+            Expr::IfExpr(ref if_expr) => self.visit_if_expr(if_expr),
+            Expr::IndexExpr(ref index_expr) => self.visit_index_expr(index_expr),
+            Expr::IntegerExpr(ref int_expr) => self.visit_integer_expr(int_expr),
+            Expr::LetExpr(ref let_expr) => self.visit_let_expr(let_expr),
+            Expr::ListExpr(ref list_expr) => self.visit_list_expr(list_expr),
+            Expr::PrintExpr(ref print_expr) => self.visit_print_expr(print_expr),
+            Expr::RecordExpr(ref record_expr) => self.visit_record_expr(record_expr),
+            Expr::EnumExpr(ref enum_expr) => self.visit_enum_expr(enum_expr),
+            Expr::MatchExpr(ref match_expr) => self.visit_match_expr(match_expr),
+            Expr::NoneExpr(ref none_expr) => self.visit_none_expr(none_expr),
+            Expr::StringExpr(ref str_expr) => self.visit_string_expr(str_expr),
+            Expr::StructExpr(ref struct_expr) => self.visit_struct_expr(struct_expr),
+            Expr::TernaryExpr(ref ternary_expr) => self.visit_ternary_expr(ternary_expr),
+            Expr::TupleExpr(ref tuple_expr) => self.visit_tuple_expr(tuple_expr),
+            Expr::UnaryExpr(ref unary_expr) => self.visit_unary_expr(unary_expr),
+            Expr::VnameExpr(ref vname_expr) => self.visit_vname_expr(vname_expr),
+            Expr::WhileExpr(ref while_expr) => self.visit_while_expr(while_expr),
+        }
+    }
+
+    fn visit_fn_expr(&mut self, fn_expr: &FnExpr) -> Self::Result {
+        let func = Function {
+            name: Some(fn_expr.vname.id.spelling.clone()),
+            params: fn_expr
+                .params
+                .iter()
+                .map(|param| param.vname.id.spelling.clone())
+                .collect(),
+            body: (*fn_expr.body).clone(),
+            captures: self.captures(&fn_expr.captures),
+        };
+        self.runtime.save_binding(
+            &fn_expr.vname.id.spelling,
+            ExprValue::Function(Arc::new(func)),
+        );
+
+        Ok(ExprValue::Unit)
+    }
+
+    fn visit_for_expr(&mut self, for_expr: &ForExpr) -> Self::Result {
+        let (start, end) = match (
+            self.visit_expr(&for_expr.start)?,
+            self.visit_expr(&for_expr.end)?,
+        ) {
+            (ExprValue::Int(start), ExprValue::Int(end)) => (start, end),
+            (start, end) => {
+                return Err(ExprError::with_span(
+                    ErrorCode::InvalidOperandValues,
+                    format!("cannot loop over the range {}..{}", start, end),
+                    for_expr.span.clone(),
+                )
+                .into())
+            }
+        };
+
+        for ival in start..end {
+            // the loop variable is bound afresh for each iteration, so that
+            // assigning to it in the body does not change the iterations.
+            self.runtime.open_level();
+            self.runtime
+                .save_binding(&for_expr.vname.id.spelling, ExprValue::Int(ival));
+            let go_on = self.run_loop_body(&for_expr.body);
+            self.runtime.close_level();
+
+            if !go_on? {
+                break;
+            }
+        }
+
+        Ok(ExprValue::Unit)
+    }
+
+    fn visit_identifier(&mut self, id: &Identifier) -> Self::Result {
+        Ok(self
+            .lookup(&id.spelling)
+            .map_err(|err| err.or_span(&id.span))?)
+    }
+
+    fn visit_field_expr(&mut self, field_expr: &FieldExpr) -> Self::Result {
+        match self.visit_expr(&field_expr.expr)? {
+            ExprValue::Tuple(mut elems) => match field_expr.field.parse::<usize>() {
+                Ok(idx) if idx < elems.len() => Ok(elems.swap_remove(idx)),
+                _ => Err(ExprError::with_span(
+                    ErrorCode::InvalidOperandValues,
+                    format!(
+                        "no field {} on a tuple of {} elements",
+                        field_expr.field,
+                        elems.len()
+                    ),
+                    field_expr.span.clone(),
+                )
+                .into()),
+            },
+            ExprValue::Record(record) => Ok(record
+                .get(&field_expr.field)
+                .map_err(|err| err.or_span(&field_expr.span))?),
+            val => Err(ExprError::with_span(
+                ErrorCode::InvalidOperandValues,
+                format!("no field {} on {}", field_expr.field, val),
+                field_expr.span.clone(),
+            )
+            .into()),
+        }
+    }
+
+    fn visit_float_expr(&mut self, float_expr: &FloatExpr) -> Self::Result {
+        Ok(ExprValue::Float(float_expr.value))
+    }
+
+    fn visit_if_expr(&mut self, if_expr: &IfExpr) -> Self::Result {
+        match self.visit_expr(&if_expr.cond)? {
+            ExprValue::Bool(true) => {
+                let val = self.visit_expr(&if_expr.then_branch)?;
+                // without an else, the if has no value even if its branch ran.
+                match if_expr.else_branch {
+                    Some(_) => Ok(val),
+                    None => Ok(ExprValue::Unit),
+                }
+            }
+            ExprValue::Bool(false) => match if_expr.else_branch {
+                Some(ref else_branch) => self.visit_expr(else_branch),
+                None => Ok(ExprValue::Unit),
+            },
+            val => Err(ExprError::with_span(
+                ErrorCode::InvalidOperandValues,
+                format!("the condition of an if must be a bool, not {}", val),
+                if_expr.cond.span().clone(),
+            )
+            .into()),
+        }
+    }
+
+    fn visit_index_expr(&mut self, index_expr: &IndexExpr) -> Self::Result {
+        let (list, index) = self.index_operands(index_expr)?;
+        Ok(list
+            .get(index)
+            .map_err(|err| err.or_span(&index_expr.span))?)
+    }
+
+    fn visit_integer_expr(&mut self, int_expr: &IntegerExpr) -> Self::Result {
+        Ok(ExprValue::Int(int_expr.value))
+    }
+
+    fn visit_lambda_expr(&mut self, lambda_expr: &LambdaExpr) -> Self::Result {
+        let func = Function {
+            name: None,
+            params: lambda_expr
+                .params
+                .iter()
+                .map(|param| param.vname.id.spelling.clone())
+                .collect(),
+            body: (*lambda_expr.body).clone(),
+            captures: self.captures(&lambda_expr.captures),
+        };
+
+        Ok(ExprValue::Function(Arc::new(func)))
+    }
+
+    fn visit_let_expr(&mut self, let_expr: &LetExpr) -> Self::Result {
+        let val = self.visit_expr(&let_expr.expr)?;
+        self.runtime
+            .save_binding(&let_expr.vname.id.spelling, val.clone());
+        Ok(val)
+    }
+
+    fn visit_list_expr(&mut self, list_expr: &ListExpr) -> Self::Result {
+        let mut elems = Vec::new();
+        for elem in &list_expr.elems {
+            elems.push(self.visit_expr(elem)?);
         }
+
+        Ok(ExprValue::List(Arc::new(List::new(elems))))
     }
 
-    fn visit_identifier(&mut self, id: &mut Identifier) -> Self::Result {
-        self.runtime.get_binding(&id.spelling)
+    fn visit_match_expr(&mut self, match_expr: &MatchExpr) -> Self::Result {
+        let val = self.visit_expr(&match_expr.expr)?;
+
+        for arm in &match_expr.arms {
+            let mut bindings = Vec::new();
+            if !Interpreter::match_pattern(&arm.pattern, &val, &mut bindings) {
+                continue;
+            }
+
+            self.runtime.open_level();
+            for (name, val) in bindings {
+                self.runtime.save_binding(&name, val);
+            }
+            let body_val = self.visit_expr(&arm.body);
+            self.runtime.close_level();
+            return body_val;
+        }
+
+        Err(ExprError::with_span(
+            ErrorCode::InvalidOperandValues,
+            format!("no arm of the match matches {}", val),
+            match_expr.expr.span().clone(),
+        )
+        .into())
     }
 
-    fn visit_integer_expr(&mut self, int_val: &mut i32) -> Self::Result {
-        ExprValue::Int(*int_val)
+    fn visit_none_expr(&mut self, _none_expr: &NoneExpr) -> Self::Result {
+        Ok(ExprValue::Option(None))
     }
 
-    fn visit_print_expr(&mut self, print_expr: &mut Expr) -> Self::Result {
-        let expr_val = self.visit_expr(print_expr);
+    fn visit_print_expr(&mut self, print_expr: &PrintExpr) -> Self::Result {
+        let expr_val = self.visit_expr(&print_expr.expr)?;
         println!("{}", expr_val);
-        ExprValue::None
+        Ok(ExprValue::Unit)
     }
 
-    fn visit_unary_expr(&mut self, unary_expr: &mut UnaryExpr) -> Self::Result {
-        let expr_val = self.visit_expr(&mut unary_expr.elem);
+    fn visit_record_expr(&mut self, record_expr: &RecordExpr) -> Self::Result {
+        // the fields are evaluated in the order they are written, and are
+        // stored in the order of the struct declaration.
+        let mut vals = Vec::new();
+        for (field_id, field_expr) in &record_expr.fields {
+            vals.push((field_id.spelling.clone(), self.visit_expr(field_expr)?));
+        }
+
+        let fields = record_expr
+            .decl_fields
+            .iter()
+            .filter_map(|name| {
+                let pos = vals.iter().position(|(field, _)| field == name)?;
+                Some(vals.swap_remove(pos))
+            })
+            .collect();
 
-        match unary_expr.op {
-            UnaryOperator::UnaryPlus => expr_val,
+        Ok(ExprValue::Record(Arc::new(Record::new(
+            record_expr.name.spelling.clone(),
+            fields,
+        ))))
+    }
+
+    fn visit_string_expr(&mut self, str_expr: &StringExpr) -> Self::Result {
+        Ok(ExprValue::Str(str_expr.value.clone()))
+    }
+
+    fn visit_struct_expr(&mut self, _struct_expr: &StructExpr) -> Self::Result {
+        Ok(ExprValue::Unit)
+    }
+
+    fn visit_ternary_expr(&mut self, ternary_expr: &TernaryExpr) -> Self::Result {
+        match self.visit_expr(&ternary_expr.cond)? {
+            ExprValue::Bool(true) => self.visit_expr(&ternary_expr.then_expr),
+            ExprValue::Bool(false) => self.visit_expr(&ternary_expr.else_expr),
+            val => Err(ExprError::with_span(
+                ErrorCode::InvalidOperandValues,
+                format!("the condition of a ?: must be a bool, not {}", val),
+                ternary_expr.cond.span().clone(),
+            )
+            .into()),
+        }
+    }
+
+    fn visit_tuple_expr(&mut self, tuple_expr: &TupleExpr) -> Self::Result {
+        let mut elems = Vec::new();
+        for elem in &tuple_expr.elems {
+            elems.push(self.visit_expr(elem)?);
+        }
+
+        Ok(ExprValue::Tuple(elems))
+    }
+
+    fn visit_unary_expr(&mut self, unary_expr: &UnaryExpr) -> Self::Result {
+        let expr_val = self.visit_expr(&unary_expr.elem)?;
+
+        let val = match unary_expr.op {
+            UnaryOperator::UnaryPlus => Ok(expr_val),
             UnaryOperator::BitwiseNot => !expr_val,
             UnaryOperator::LogicalNot => !expr_val,
             UnaryOperator::UnaryMinus => -expr_val,
-        }
+            UnaryOperator::PostfixDecrement
+            | UnaryOperator::PostfixIncrement
+            | UnaryOperator::PrefixDecrement
+            | UnaryOperator::PrefixIncrement => self.increment(unary_expr, expr_val),
+        };
+
+        Ok(val.map_err(|err| err.or_span(&unary_expr.span))?)
     }
 
-    fn visit_vname_expr(&mut self, vname_expr: &mut VnameExpr) -> Self::Result {
-        self.visit_identifier(&mut vname_expr.id)
+    fn visit_vname_expr(&mut self, vname_expr: &VnameExpr) -> Self::Result {
+        self.visit_identifier(&vname_expr.id)
+    }
+
+    fn visit_while_expr(&mut self, while_expr: &WhileExpr) -> Self::Result {
+        loop {
+            match self.visit_expr(&while_expr.cond)? {
+                ExprValue::Bool(true) => {}
+                ExprValue::Bool(false) => break,
+                val => {
+                    return Err(ExprError::with_span(
+                        ErrorCode::InvalidOperandValues,
+                        format!("the condition of a while must be a bool, not {}", val),
+                        while_expr.cond.span().clone(),
+                    )
+                    .into())
+                }
+            }
+
+            if !self.run_loop_body(&while_expr.body)? {
+                break;
+            }
+        }
+
+        Ok(ExprValue::Unit)
     }
 }
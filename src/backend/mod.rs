@@ -1,2 +1,3 @@
+pub mod builtins;
 pub mod interpreter;
 pub mod runtime;
@@ -1,203 +1,819 @@
-use std::collections::HashMap;
+use crate::diagnostics::codes::ErrorCode;
+use crate::error::{ExprError, ExprResult};
+use crate::front::ast::Expr;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Not, Rem, Shl, Shr, Sub};
+use std::sync::{Arc, Mutex};
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialOrd)]
 pub enum ExprValue {
-    None,
+    /// the value of exprs of type (), such as loops and declarations.
+    Unit,
     Bool(bool),
     Int(i32),
     Float(f64),
+    Str(String),
+    Function(Arc<Function>),
+    List(Arc<List>),
+    Tuple(Vec<ExprValue>),
+    Record(Arc<Record>),
+    Variant(Arc<Variant>),
+    /// `some(value)` or `none`.
+    Option(Option<Box<ExprValue>>),
 }
 
-impl fmt::Display for ExprValue {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match *self {
-                ExprValue::None => format!("()"),
-                ExprValue::Int(ref ival) => format!("{}", ival),
-                ExprValue::Float(ref fval) => format!("{}", fval),
-                ExprValue::Bool(bval) => format!("{}", bval),
+/// Values are equal when they have equal contents, see `all_equal`.
+impl PartialEq for ExprValue {
+    fn eq(&self, other: &Self) -> bool {
+        all_equal(vec![(self.clone(), other.clone())])
+    }
+}
+
+/// Whether the values of each pair are equal. Lists and records can contain
+/// themselves, so a pair of them that recurs while it is being compared is
+/// taken to be equal (any difference is found elsewhere).
+fn all_equal(mut pending: Vec<(ExprValue, ExprValue)>) -> bool {
+    let mut seen = HashSet::new();
+
+    while let Some(pair) = pending.pop() {
+        match pair {
+            (ExprValue::Unit, ExprValue::Unit) => {}
+            (ExprValue::Bool(lhs), ExprValue::Bool(rhs)) if lhs == rhs => {}
+            (ExprValue::Int(lhs), ExprValue::Int(rhs)) if lhs == rhs => {}
+            (ExprValue::Float(lhs), ExprValue::Float(rhs)) if lhs == rhs => {}
+            (ExprValue::Str(lhs), ExprValue::Str(rhs)) if lhs == rhs => {}
+            (ExprValue::Function(lhs), ExprValue::Function(rhs)) if lhs == rhs => {}
+            (ExprValue::List(lhs), ExprValue::List(rhs)) => {
+                if Arc::ptr_eq(&lhs, &rhs)
+                    || !seen.insert((Arc::as_ptr(&lhs) as usize, Arc::as_ptr(&rhs) as usize))
+                {
+                    continue;
+                }
+                let (lhs, rhs) = (lhs.elems(), rhs.elems());
+                if lhs.len() != rhs.len() {
+                    return false;
+                }
+                pending.extend(lhs.into_iter().zip(rhs));
+            }
+            (ExprValue::Record(lhs), ExprValue::Record(rhs)) => {
+                if Arc::ptr_eq(&lhs, &rhs)
+                    || !seen.insert((Arc::as_ptr(&lhs) as usize, Arc::as_ptr(&rhs) as usize))
+                {
+                    continue;
+                }
+                if lhs.name != rhs.name {
+                    return false;
+                }
+                let (lhs, rhs) = (lhs.fields(), rhs.fields());
+                if lhs.len() != rhs.len() {
+                    return false;
+                }
+                for ((lhs_name, lhs), (rhs_name, rhs)) in lhs.into_iter().zip(rhs) {
+                    if lhs_name != rhs_name {
+                        return false;
+                    }
+                    pending.push((lhs, rhs));
+                }
+            }
+            (ExprValue::Tuple(lhs), ExprValue::Tuple(rhs)) if lhs.len() == rhs.len() => {
+                pending.extend(lhs.into_iter().zip(rhs));
+            }
+            (ExprValue::Variant(lhs), ExprValue::Variant(rhs)) => {
+                if Arc::ptr_eq(&lhs, &rhs) {
+                    continue;
+                }
+                if lhs.enum_name != rhs.enum_name
+                    || lhs.name != rhs.name
+                    || lhs.payload.len() != rhs.payload.len()
+                {
+                    return false;
+                }
+                pending.extend(lhs.payload.iter().cloned().zip(rhs.payload.iter().cloned()));
+            }
+            (ExprValue::Option(Some(lhs)), ExprValue::Option(Some(rhs))) => {
+                pending.push((*lhs, *rhs));
+            }
+            (ExprValue::Option(None), ExprValue::Option(None)) => {}
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+/// A user-defined function or a closure (which has no name), along with the
+/// values of the variables and functions that its body refers to (see
+/// `FnExpr::captures` and `LambdaExpr::captures`).
+#[derive(Debug)]
+pub struct Function {
+    pub name: Option<String>,
+    pub params: Vec<String>,
+    pub body: Expr,
+    pub captures: Vec<(String, ExprValue)>,
+}
+
+/// A value can hold a long chain of records or variants, e.g., a linked list,
+/// so its parts are dropped one after the other rather than recursively.
+impl Drop for Function {
+    fn drop(&mut self) {
+        drop_all(self.captures.drain(..).map(|(_, val)| val).collect());
+    }
+}
+
+/// Drop `vals`, taking the values out of the lists, the records and the
+/// variants that are not shared, so that no drop nests in another.
+fn drop_all(mut pending: Vec<ExprValue>) {
+    while let Some(val) = pending.pop() {
+        match val {
+            ExprValue::Function(func) => {
+                if let Ok(mut func) = Arc::try_unwrap(func) {
+                    pending.extend(func.captures.drain(..).map(|(_, val)| val));
+                }
             }
+            ExprValue::List(list) => {
+                if let Ok(list) = Arc::try_unwrap(list) {
+                    pending.append(&mut list.take());
+                }
+            }
+            ExprValue::Record(record) => {
+                if let Ok(record) = Arc::try_unwrap(record) {
+                    pending.extend(record.take().into_iter().map(|(_, val)| val));
+                }
+            }
+            ExprValue::Variant(variant) => {
+                if let Ok(mut variant) = Arc::try_unwrap(variant) {
+                    pending.append(&mut variant.payload);
+                }
+            }
+            ExprValue::Tuple(elems) => pending.extend(elems),
+            ExprValue::Option(Some(val)) => pending.push(*val),
+            _ => {}
+        }
+    }
+}
+
+/// Functions are only equal to themselves, and are not ordered.
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl PartialOrd for Function {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            Some(Ordering::Equal)
+        } else {
+            None
+        }
+    }
+}
+
+/// A list, which is shared rather than copied by assignments and calls, so
+/// that the changes made through one variable are seen through the others.
+#[derive(Debug)]
+pub struct List {
+    elems: Mutex<Vec<ExprValue>>,
+}
+
+impl List {
+    pub fn new(elems: Vec<ExprValue>) -> Self {
+        List {
+            elems: Mutex::new(elems),
+        }
+    }
+
+    /// A copy of the elements of the list.
+    pub fn elems(&self) -> Vec<ExprValue> {
+        self.elems.lock().unwrap().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.elems.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: i32) -> ExprResult<ExprValue> {
+        let elems = self.elems.lock().unwrap();
+        List::position(index, elems.len()).map(|pos| elems[pos].clone())
+    }
+
+    pub fn set(&self, index: i32, value: ExprValue) -> ExprResult<()> {
+        let mut elems = self.elems.lock().unwrap();
+        let pos = List::position(index, elems.len())?;
+        elems[pos] = value;
+        Ok(())
+    }
+
+    pub fn push(&self, value: ExprValue) {
+        self.elems.lock().unwrap().push(value);
+    }
+
+    pub fn pop(&self) -> ExprResult<ExprValue> {
+        self.elems.lock().unwrap().pop().ok_or_else(|| {
+            ExprError::new(
+                ErrorCode::IndexOutOfBounds,
+                "cannot pop from an empty list".to_owned(),
+            )
+        })
+    }
+
+    /// Take the elements out of the list, leaving it empty.
+    fn take(&self) -> Vec<ExprValue> {
+        let mut elems = self.elems.lock().unwrap_or_else(|err| err.into_inner());
+        std::mem::take(&mut *elems)
+    }
+
+    /// The position of the element at `index`, if the list has one.
+    fn position(index: i32, len: usize) -> ExprResult<usize> {
+        usize::try_from(index)
+            .ok()
+            .filter(|&pos| pos < len)
+            .ok_or_else(|| {
+                ExprError::new(
+                    ErrorCode::IndexOutOfBounds,
+                    format!(
+                        "index {} is out of bounds for a list of length {}",
+                        index, len
+                    ),
+                )
+            })
+    }
+}
+
+impl Drop for List {
+    fn drop(&mut self) {
+        drop_all(self.take());
+    }
+}
+
+/// Lists are equal when they have equal elements, and are not ordered.
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        if std::ptr::eq(self, other) {
+            return true;
+        }
+        let (lhs, rhs) = (self.elems(), other.elems());
+        lhs.len() == rhs.len() && all_equal(lhs.into_iter().zip(rhs).collect())
+    }
+}
+
+impl PartialOrd for List {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            Some(Ordering::Equal)
+        } else {
+            None
+        }
+    }
+}
+
+/// A value of a struct type, which is shared like a list, with its fields in
+/// the order of the struct declaration.
+#[derive(Debug)]
+pub struct Record {
+    pub name: String,
+    fields: Mutex<Vec<(String, ExprValue)>>,
+}
+
+impl Record {
+    pub fn new(name: String, fields: Vec<(String, ExprValue)>) -> Self {
+        Record {
+            name,
+            fields: Mutex::new(fields),
+        }
+    }
+
+    /// A copy of the fields of the record.
+    pub fn fields(&self) -> Vec<(String, ExprValue)> {
+        self.fields.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, field: &str) -> ExprResult<ExprValue> {
+        let fields = self.fields.lock().unwrap();
+        fields
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, val)| val.clone())
+            .ok_or_else(|| self.field_error(field))
+    }
+
+    pub fn set(&self, field: &str, value: ExprValue) -> ExprResult<()> {
+        let mut fields = self.fields.lock().unwrap();
+        match fields.iter_mut().find(|(name, _)| name == field) {
+            Some((_, val)) => {
+                *val = value;
+                Ok(())
+            }
+            None => Err(self.field_error(field)),
+        }
+    }
+
+    /// Take the fields out of the record, leaving it without any.
+    fn take(&self) -> Fields {
+        let mut fields = self.fields.lock().unwrap_or_else(|err| err.into_inner());
+        std::mem::take(&mut *fields)
+    }
+
+    fn field_error(&self, field: &str) -> ExprError {
+        ExprError::new(
+            ErrorCode::InvalidOperandValues,
+            format!("no field {} on a record of struct {}", field, self.name),
         )
     }
 }
 
+impl Drop for Record {
+    fn drop(&mut self) {
+        drop_all(self.take().into_iter().map(|(_, val)| val).collect());
+    }
+}
+
+/// Records are equal when they are of the same struct and have equal fields,
+/// and are not ordered.
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        if std::ptr::eq(self, other) {
+            return true;
+        }
+        let (lhs, rhs) = (self.fields(), other.fields());
+        self.name == other.name
+            && lhs.len() == rhs.len()
+            && lhs
+                .iter()
+                .zip(&rhs)
+                .all(|((lhs_name, _), (rhs_name, _))| lhs_name == rhs_name)
+            && all_equal(
+                lhs.into_iter()
+                    .zip(rhs)
+                    .map(|((_, lhs), (_, rhs))| (lhs, rhs))
+                    .collect(),
+            )
+    }
+}
+
+impl PartialOrd for Record {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            Some(Ordering::Equal)
+        } else {
+            None
+        }
+    }
+}
+
+/// A value of an enum: one of its variants, with the values of its payload.
+/// A variant that has a payload is bound to its name without one, and is
+/// given its payload when it is called.
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub enum_name: String,
+    pub name: String,
+    pub payload: Vec<ExprValue>,
+}
+
+impl Variant {
+    pub fn new(enum_name: String, name: String, payload: Vec<ExprValue>) -> Self {
+        Variant {
+            enum_name,
+            name,
+            payload,
+        }
+    }
+}
+
+impl Drop for Variant {
+    fn drop(&mut self) {
+        drop_all(std::mem::take(&mut self.payload));
+    }
+}
+
+/// Variants are equal when they are the same variant of the same enum and
+/// have equal payloads, and are not ordered.
+impl PartialEq for Variant {
+    fn eq(&self, other: &Self) -> bool {
+        self.enum_name == other.enum_name
+            && self.name == other.name
+            && self.payload.len() == other.payload.len()
+            && all_equal(
+                self.payload
+                    .iter()
+                    .cloned()
+                    .zip(other.payload.iter().cloned())
+                    .collect(),
+            )
+    }
+}
+
+impl PartialOrd for Variant {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            Some(Ordering::Equal)
+        } else {
+            None
+        }
+    }
+}
+
+type Fields = Vec<(String, ExprValue)>;
+
+/// The contents of the lists and the records that can be reached from some
+/// values. Cloning a value shares its lists and records rather than copying
+/// them, so these are saved separately to undo the changes made to them.
+#[derive(Debug, Default)]
+pub struct Contents {
+    lists: Vec<(Arc<List>, Vec<ExprValue>)>,
+    records: Vec<(Arc<Record>, Fields)>,
+}
+
+impl Contents {
+    /// Save the contents of the lists and the records reachable from `vals`,
+    /// each once, since they can be shared and even contain themselves.
+    pub fn of<'a>(vals: impl IntoIterator<Item = &'a ExprValue>) -> Self {
+        let mut contents = Contents::default();
+        let mut seen = HashSet::new();
+        let mut pending: Vec<ExprValue> = vals.into_iter().cloned().collect();
+
+        while let Some(val) = pending.pop() {
+            match val {
+                ExprValue::List(list) if seen.insert(Arc::as_ptr(&list) as usize) => {
+                    let elems = list.elems();
+                    pending.extend(elems.iter().cloned());
+                    contents.lists.push((list, elems));
+                }
+                ExprValue::Record(record) if seen.insert(Arc::as_ptr(&record) as usize) => {
+                    let fields = record.fields();
+                    pending.extend(fields.iter().map(|(_, val)| val.clone()));
+                    contents.records.push((record, fields));
+                }
+                ExprValue::Function(func) if seen.insert(Arc::as_ptr(&func) as usize) => {
+                    pending.extend(func.captures.iter().map(|(_, val)| val.clone()));
+                }
+                ExprValue::Variant(variant) if seen.insert(Arc::as_ptr(&variant) as usize) => {
+                    pending.extend(variant.payload.iter().cloned());
+                }
+                ExprValue::Tuple(elems) => pending.extend(elems),
+                ExprValue::Option(Some(val)) => pending.push(*val),
+                _ => {}
+            }
+        }
+
+        contents
+    }
+
+    /// Put the saved contents back into the lists and the records.
+    pub fn restore(self) {
+        for (list, elems) in self.lists {
+            *list.elems.lock().unwrap() = elems;
+        }
+        for (record, fields) in self.records {
+            *record.fields.lock().unwrap() = fields;
+        }
+    }
+}
+
+/// The parts of a value that are still to be shown, see `format_value`.
+enum Piece {
+    /// a value, which is quoted if it is a string nested in another value.
+    Value(ExprValue, bool),
+    Text(String),
+    /// the end of the list or the record at the given address.
+    Leave(usize),
+}
+
+impl Piece {
+    /// The pieces that show `elems` nested in another value, separated by
+    /// commas.
+    fn elems(elems: impl IntoIterator<Item = ExprValue>) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        for elem in elems {
+            if !pieces.is_empty() {
+                pieces.push(Piece::Text(", ".to_owned()));
+            }
+            pieces.push(Piece::Value(elem, true));
+        }
+        pieces
+    }
+}
+
+/// Show a value. A list or a record can contain itself, so it is shown as
+/// ... where it recurs inside itself. The parts of the value are shown one
+/// after the other rather than recursively, since a value can hold a long
+/// chain of records or variants.
+fn format_value(val: &ExprValue) -> String {
+    let mut shown = String::new();
+    // the lists and the records that the current part is nested in.
+    let mut outer = HashSet::new();
+    let mut pending = vec![Piece::Value(val.clone(), false)];
+
+    while let Some(piece) = pending.pop() {
+        let (val, nested) = match piece {
+            Piece::Value(val, nested) => (val, nested),
+            Piece::Text(text) => {
+                shown.push_str(&text);
+                continue;
+            }
+            Piece::Leave(ptr) => {
+                outer.remove(&ptr);
+                continue;
+            }
+        };
+
+        // the pieces that are left to show after the start of the value.
+        let mut rest = Vec::new();
+        match val {
+            ExprValue::Unit => shown.push_str("()"),
+            ExprValue::Int(ival) => shown.push_str(&ival.to_string()),
+            // always show a fraction or an exponent, so that floats can be
+            // told apart from ints, e.g., 1.0 rather than 1.
+            ExprValue::Float(fval) => shown.push_str(&format!("{:?}", fval)),
+            ExprValue::Bool(bval) => shown.push_str(&bval.to_string()),
+            ExprValue::Str(ref sval) if nested => shown.push_str(&format!("{:?}", sval)),
+            ExprValue::Str(ref sval) => shown.push_str(sval),
+            ExprValue::Function(ref func) => match func.name {
+                Some(ref name) => shown.push_str(&format!("<fn {}>", name)),
+                None => shown.push_str("<closure>"),
+            },
+            ExprValue::List(ref list) if !outer.insert(Arc::as_ptr(list) as usize) => {
+                shown.push_str("...")
+            }
+            ExprValue::List(ref list) => {
+                shown.push('[');
+                rest = Piece::elems(list.elems());
+                rest.push(Piece::Text("]".to_owned()));
+                rest.push(Piece::Leave(Arc::as_ptr(list) as usize));
+            }
+            ExprValue::Tuple(elems) => {
+                shown.push('(');
+                rest = Piece::elems(elems);
+                rest.push(Piece::Text(")".to_owned()));
+            }
+            ExprValue::Record(ref record) if !outer.insert(Arc::as_ptr(record) as usize) => {
+                shown.push_str("...")
+            }
+            ExprValue::Record(ref record) => {
+                shown.push_str(&record.name);
+                shown.push_str(" { ");
+                for (name, val) in record.fields() {
+                    let sep = if rest.is_empty() { "" } else { ", " };
+                    rest.push(Piece::Text(format!("{}{}: ", sep, name)));
+                    rest.push(Piece::Value(val, true));
+                }
+                rest.push(Piece::Text(" }".to_owned()));
+                rest.push(Piece::Leave(Arc::as_ptr(record) as usize));
+            }
+            ExprValue::Variant(ref variant) if variant.payload.is_empty() => {
+                shown.push_str(&variant.name)
+            }
+            ExprValue::Variant(ref variant) => {
+                shown.push_str(&variant.name);
+                shown.push('(');
+                rest = Piece::elems(variant.payload.iter().cloned());
+                rest.push(Piece::Text(")".to_owned()));
+            }
+            ExprValue::Option(Some(val)) => {
+                shown.push_str("some(");
+                rest = vec![Piece::Value(*val, true), Piece::Text(")".to_owned())];
+            }
+            ExprValue::Option(None) => shown.push_str("none"),
+        }
+        pending.extend(rest.into_iter().rev());
+    }
+
+    shown
+}
+
+impl fmt::Display for ExprValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_value(self))
+    }
+}
+
+fn overflow_error(op: &str) -> ExprError {
+    ExprError::new(
+        ErrorCode::ArithmeticOverflow,
+        format!("arithmetic overflow while evaluating operator {}", op),
+    )
+}
+
+fn operand_error(op: &str, lhs: &ExprValue, rhs: &ExprValue) -> ExprError {
+    ExprError::new(
+        ErrorCode::InvalidOperandValues,
+        format!("cannot apply operator {} to {} and {}", op, lhs, rhs),
+    )
+}
+
 impl Add for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn add(self, rhs: Self) -> Self::Output {
-        match self {
-            ExprValue::Int(ref lhs) => match rhs {
-                ExprValue::Int(ref rhs) => ExprValue::Int(lhs + rhs),
-                _ => ExprValue::None,
-            },
-            _ => ExprValue::None,
+        match (self, rhs) {
+            (ExprValue::Int(lhs), ExprValue::Int(rhs)) => lhs
+                .checked_add(rhs)
+                .map(ExprValue::Int)
+                .ok_or_else(|| overflow_error("+")),
+            (ExprValue::Float(lhs), ExprValue::Float(rhs)) => Ok(ExprValue::Float(lhs + rhs)),
+            (ExprValue::Str(lhs), ExprValue::Str(rhs)) => Ok(ExprValue::Str(lhs + &rhs)),
+            (lhs, rhs) => Err(operand_error("+", &lhs, &rhs)),
         }
     }
 }
 
 impl Sub for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        match self {
-            ExprValue::Int(ref lhs) => match rhs {
-                ExprValue::Int(ref rhs) => ExprValue::Int(lhs - rhs),
-                _ => ExprValue::None,
-            },
-            _ => ExprValue::None,
+        match (self, rhs) {
+            (ExprValue::Int(lhs), ExprValue::Int(rhs)) => lhs
+                .checked_sub(rhs)
+                .map(ExprValue::Int)
+                .ok_or_else(|| overflow_error("-")),
+            (ExprValue::Float(lhs), ExprValue::Float(rhs)) => Ok(ExprValue::Float(lhs - rhs)),
+            (lhs, rhs) => Err(operand_error("-", &lhs, &rhs)),
         }
     }
 }
 
 impl Mul for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        match self {
-            ExprValue::Int(ref lhs) => match rhs {
-                ExprValue::Int(ref rhs) => ExprValue::Int(lhs * rhs),
-                _ => ExprValue::None,
-            },
-            _ => ExprValue::None,
+        match (self, rhs) {
+            (ExprValue::Int(lhs), ExprValue::Int(rhs)) => lhs
+                .checked_mul(rhs)
+                .map(ExprValue::Int)
+                .ok_or_else(|| overflow_error("*")),
+            (ExprValue::Float(lhs), ExprValue::Float(rhs)) => Ok(ExprValue::Float(lhs * rhs)),
+            (lhs, rhs) => Err(operand_error("*", &lhs, &rhs)),
         }
     }
 }
 
 impl Div for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn div(self, rhs: Self) -> Self::Output {
-        match self {
-            ExprValue::Int(ref lhs) => match rhs {
-                ExprValue::Int(ref rhs) => ExprValue::Int(lhs / rhs),
-                _ => ExprValue::None,
-            },
-            _ => ExprValue::None,
+        match (self, rhs) {
+            (ExprValue::Int(_), ExprValue::Int(0)) => Err(ExprError::new(
+                ErrorCode::DivisionByZero,
+                "division by zero".to_owned(),
+            )),
+            (ExprValue::Int(lhs), ExprValue::Int(rhs)) => lhs
+                .checked_div(rhs)
+                .map(ExprValue::Int)
+                .ok_or_else(|| overflow_error("/")),
+            // float division follows IEEE 754, i.e., dividing by zero gives an
+            // infinity or NaN rather than an error.
+            (ExprValue::Float(lhs), ExprValue::Float(rhs)) => Ok(ExprValue::Float(lhs / rhs)),
+            (lhs, rhs) => Err(operand_error("/", &lhs, &rhs)),
         }
     }
 }
 
 impl Rem for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        match self {
-            ExprValue::Int(ref lhs) => match rhs {
-                ExprValue::Int(ref rhs) => ExprValue::Int(lhs % rhs),
-                _ => ExprValue::None,
-            },
-            _ => ExprValue::None,
+        match (self, rhs) {
+            (ExprValue::Int(_), ExprValue::Int(0)) => Err(ExprError::new(
+                ErrorCode::DivisionByZero,
+                "remainder by zero".to_owned(),
+            )),
+            (ExprValue::Int(lhs), ExprValue::Int(rhs)) => lhs
+                .checked_rem(rhs)
+                .map(ExprValue::Int)
+                .ok_or_else(|| overflow_error("%")),
+            (ExprValue::Float(lhs), ExprValue::Float(rhs)) => Ok(ExprValue::Float(lhs % rhs)),
+            (lhs, rhs) => Err(operand_error("%", &lhs, &rhs)),
         }
     }
 }
 
 impl BitAnd for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn bitand(self, rhs: Self) -> Self::Output {
-        match self {
-            ExprValue::Int(ref lhs) => match rhs {
-                ExprValue::Int(ref rhs) => ExprValue::Int(lhs & rhs),
-                _ => ExprValue::None,
-            },
-            _ => ExprValue::None,
+        match (self, rhs) {
+            (ExprValue::Int(lhs), ExprValue::Int(rhs)) => Ok(ExprValue::Int(lhs & rhs)),
+            (lhs, rhs) => Err(operand_error("&", &lhs, &rhs)),
         }
     }
 }
 
 impl BitOr for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn bitor(self, rhs: Self) -> Self::Output {
-        match self {
-            ExprValue::Int(ref lhs) => match rhs {
-                ExprValue::Int(ref rhs) => ExprValue::Int(lhs | rhs),
-                _ => ExprValue::None,
-            },
-            _ => ExprValue::None,
+        match (self, rhs) {
+            (ExprValue::Int(lhs), ExprValue::Int(rhs)) => Ok(ExprValue::Int(lhs | rhs)),
+            (lhs, rhs) => Err(operand_error("|", &lhs, &rhs)),
         }
     }
 }
 
 impl BitXor for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn bitxor(self, rhs: Self) -> Self::Output {
-        match self {
-            ExprValue::Int(ref lhs) => match rhs {
-                ExprValue::Int(ref rhs) => ExprValue::Int(lhs ^ rhs),
-                _ => ExprValue::None,
-            },
-            _ => ExprValue::None,
+        match (self, rhs) {
+            (ExprValue::Int(lhs), ExprValue::Int(rhs)) => Ok(ExprValue::Int(lhs ^ rhs)),
+            (lhs, rhs) => Err(operand_error("^", &lhs, &rhs)),
         }
     }
 }
 
 impl Shl for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn shl(self, rhs: Self) -> Self::Output {
-        match self {
-            ExprValue::Int(ref lhs) => match rhs {
-                ExprValue::Int(ref rhs) => ExprValue::Int(lhs << rhs),
-                _ => ExprValue::None,
-            },
-            _ => ExprValue::None,
+        match (self, rhs) {
+            (ExprValue::Int(lhs), ExprValue::Int(rhs)) => u32::try_from(rhs)
+                .ok()
+                .and_then(|rhs| lhs.checked_shl(rhs))
+                .map(ExprValue::Int)
+                .ok_or_else(|| overflow_error("<<")),
+            (lhs, rhs) => Err(operand_error("<<", &lhs, &rhs)),
         }
     }
 }
 
 impl Shr for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn shr(self, rhs: Self) -> Self::Output {
-        match self {
-            ExprValue::Int(ref lhs) => match rhs {
-                ExprValue::Int(ref rhs) => ExprValue::Int(lhs >> rhs),
-                _ => ExprValue::None,
-            },
-            _ => ExprValue::None,
+        match (self, rhs) {
+            (ExprValue::Int(lhs), ExprValue::Int(rhs)) => u32::try_from(rhs)
+                .ok()
+                .and_then(|rhs| lhs.checked_shr(rhs))
+                .map(ExprValue::Int)
+                .ok_or_else(|| overflow_error(">>")),
+            (lhs, rhs) => Err(operand_error(">>", &lhs, &rhs)),
         }
     }
 }
 
 impl Neg for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn neg(self) -> Self::Output {
         match self {
-            ExprValue::Int(ref val) => ExprValue::Int(-val),
-            _ => ExprValue::None,
+            ExprValue::Int(val) => val
+                .checked_neg()
+                .map(ExprValue::Int)
+                .ok_or_else(|| overflow_error("-")),
+            ExprValue::Float(val) => Ok(ExprValue::Float(-val)),
+            val => Err(ExprError::new(
+                ErrorCode::InvalidOperandValues,
+                format!("cannot apply unary operator - to {}", val),
+            )),
         }
     }
 }
 
 impl Not for ExprValue {
-    type Output = Self;
+    type Output = ExprResult<Self>;
 
     fn not(self) -> Self::Output {
         match self {
-            ExprValue::Int(ival) => ExprValue::Int(!ival),
-            ExprValue::Bool(bval) => ExprValue::Bool(!bval),
-            _ => ExprValue::None,
+            ExprValue::Int(ival) => Ok(ExprValue::Int(!ival)),
+            ExprValue::Bool(bval) => Ok(ExprValue::Bool(!bval)),
+            val => Err(ExprError::new(
+                ErrorCode::InvalidOperandValues,
+                format!("cannot apply unary operator ! to {}", val),
+            )),
         }
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct Runtime {
     bindings: HashMap<isize, HashMap<String, ExprValue>>,
     level: isize,
+    /// the level that each call frame starts at, innermost last. The
+    /// bindings of the levels below a frame are hidden from it.
+    frames: Vec<isize>,
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Runtime::new()
+    }
 }
 
 impl Runtime {
     pub fn new() -> Self {
         let mut bindings = HashMap::new();
         bindings.insert(0isize, HashMap::new());
-        Runtime { bindings, level: 0 }
+        Runtime {
+            bindings,
+            level: 0,
+            frames: Vec::new(),
+        }
     }
 
     pub fn save_binding(&mut self, id: &str, value: ExprValue) {
@@ -207,19 +823,72 @@ impl Runtime {
             .insert(id.to_owned(), value);
     }
 
-    pub fn get_binding(&self, check_id: &str) -> ExprValue {
+    /// The lowest level that is visible from the current call frame.
+    fn frame_level(&self) -> isize {
+        self.frames.last().copied().unwrap_or(0)
+    }
+
+    /// Rebind an existing variable, at the level where it was bound.
+    pub fn update_binding(&mut self, id: &str, value: ExprValue) {
         let mut level = self.level;
 
-        while level >= 0 {
+        while level >= self.frame_level() {
+            let bindings = self.bindings.get_mut(&level).unwrap();
+            if let Some(val) = bindings.get_mut(id) {
+                *val = value;
+                return;
+            }
+
+            level -= 1;
+        }
+
+        self.save_binding(id, value);
+    }
+
+    /// The value bound to `check_id`, if it is bound in a level that is
+    /// visible from the current one.
+    pub fn get_binding(&self, check_id: &str) -> Option<ExprValue> {
+        let mut level = self.level;
+
+        while level >= self.frame_level() {
             for (id, val) in self.bindings.get(&level).unwrap().iter() {
                 if check_id == *id {
-                    return val.clone();
+                    return Some(val.clone());
+                }
+            }
+
+            level -= 1;
+        }
+        None
+    }
+
+    /// The contents of the lists and the records reachable from all the
+    /// bindings, see `Contents`.
+    pub fn contents(&self) -> Contents {
+        Contents::of(
+            self.bindings
+                .values()
+                .flat_map(|bindings| bindings.values()),
+        )
+    }
+
+    /// All the bindings visible from the current level, sorted by name.
+    pub fn bindings(&self) -> Vec<(&str, &ExprValue)> {
+        let mut bindings: Vec<(&str, &ExprValue)> = Vec::new();
+        let mut level = self.level;
+
+        while level >= self.frame_level() {
+            for (id, val) in self.bindings.get(&level).unwrap().iter() {
+                if !bindings.iter().any(|(seen, _)| seen == id) {
+                    bindings.push((id, val));
                 }
             }
 
             level -= 1;
         }
-        ExprValue::None
+
+        bindings.sort_by(|lhs, rhs| lhs.0.cmp(rhs.0));
+        bindings
     }
 
     pub fn open_level(&mut self) {
@@ -231,6 +900,22 @@ impl Runtime {
         self.bindings.remove(&self.level);
         self.level -= 1;
     }
+
+    /// Open a level for a call, which cannot see the bindings of its caller.
+    pub fn open_frame(&mut self) {
+        self.open_level();
+        self.frames.push(self.level);
+    }
+
+    pub fn close_frame(&mut self) {
+        self.frames.pop();
+        self.close_level();
+    }
+
+    /// The number of calls that are being run.
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
 }
 
 impl fmt::Display for Runtime {
@@ -238,12 +923,12 @@ impl fmt::Display for Runtime {
         let mut level = self.level;
 
         while level >= 0 {
-            println!("Bindings for level {}...", level);
+            writeln!(f, "Bindings for level {}...", level)?;
             for (id, val) in self.bindings.get(&level).unwrap().iter() {
-                let _ = write!(f, "{:?} => {:?}", id, val);
+                writeln!(f, "{:?} => {:?}", id, val)?;
             }
             level -= 1;
         }
-        write!(f, "\n")
+        writeln!(f)
     }
 }
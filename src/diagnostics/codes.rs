@@ -0,0 +1,1031 @@
+use crate::error::ExprErrorKind;
+use std::fmt;
+
+/// A stable code for every distinct error and warning, so that they can be
+/// searched for and explained (see `expr-lang explain`). Codes are never
+/// reused or renumbered - new diagnostics get new codes. Errors are numbered
+/// E0001... and warnings (which come from lints) W0001...
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorCode {
+    SourceFileUnreadable,
+    UnexpectedCharacter,
+    OutOfCharacters,
+    NotABinaryOperator,
+    MissingRightParen,
+    IntegerOutOfRange,
+    UnexpectedEndOfInput,
+    UnexpectedToken,
+    UnknownOperandType,
+    UnaryOperandMismatch,
+    UndeclaredOperator,
+    VariableTypeChanged,
+    InvalidAssignmentTarget,
+    OperandTypesDiffer,
+    OperandTypeMismatch,
+    ArithmeticOverflow,
+    DivisionByZero,
+    InvalidOperandValues,
+    UnsupportedOperator,
+    InvalidAssignmentTargetValue,
+    UnusedVariable,
+    DeadAssignment,
+    SelfAssignment,
+    ConstantCondition,
+    UnaryPlus,
+    FloatOutOfRange,
+    ExpectedType,
+    InvalidCast,
+    FloatToIntOverflow,
+    InvalidEscape,
+    UnterminatedString,
+    NotCallable,
+    ArgumentCountMismatch,
+    ArgumentTypeMismatch,
+    ExpectedToken,
+    UndeclaredVariable,
+    AnnotationMismatch,
+    NonBoolCondition,
+    BranchTypeMismatch,
+    BreakOutsideLoop,
+    NonIntRangeBound,
+    ReturnTypeMismatch,
+    CannotCaptureVariable,
+    CallDepthExceeded,
+    CannotInferParamType,
+    AssignToCapturedVariable,
+    FunctionUsedAsValue,
+    CannotInferListType,
+    ListElementTypeMismatch,
+    NotIndexable,
+    NonIntIndex,
+    IndexOutOfBounds,
+    UnknownField,
+    DestructuringMismatch,
+    UnknownType,
+    FieldTypeMismatch,
+    MissingField,
+    DuplicateField,
+    NonExhaustiveMatch,
+    UnreachablePattern,
+    PatternTypeMismatch,
+    DuplicateVariant,
+    CannotInferOptionType,
+    UnboundVariable,
+    DuplicateType,
+    NestingTooDeep,
+    DuplicateParameter,
+    DuplicateBinding,
+    DuplicateFunction,
+}
+
+/// A longer description of an error, along with a minimal program that is
+/// reported with it and the same program fixed. Errors that guard against
+/// internal inconsistencies cannot be caused by a program, and have no
+/// examples.
+pub struct Explanation {
+    pub title: &'static str,
+    pub description: &'static str,
+    pub failing: Option<&'static str>,
+    pub fixed: Option<&'static str>,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 69] = [
+        ErrorCode::SourceFileUnreadable,
+        ErrorCode::UnexpectedCharacter,
+        ErrorCode::OutOfCharacters,
+        ErrorCode::NotABinaryOperator,
+        ErrorCode::MissingRightParen,
+        ErrorCode::IntegerOutOfRange,
+        ErrorCode::UnexpectedEndOfInput,
+        ErrorCode::UnexpectedToken,
+        ErrorCode::UnknownOperandType,
+        ErrorCode::UnaryOperandMismatch,
+        ErrorCode::UndeclaredOperator,
+        ErrorCode::VariableTypeChanged,
+        ErrorCode::InvalidAssignmentTarget,
+        ErrorCode::OperandTypesDiffer,
+        ErrorCode::OperandTypeMismatch,
+        ErrorCode::ArithmeticOverflow,
+        ErrorCode::DivisionByZero,
+        ErrorCode::InvalidOperandValues,
+        ErrorCode::UnsupportedOperator,
+        ErrorCode::InvalidAssignmentTargetValue,
+        ErrorCode::UnusedVariable,
+        ErrorCode::DeadAssignment,
+        ErrorCode::SelfAssignment,
+        ErrorCode::ConstantCondition,
+        ErrorCode::UnaryPlus,
+        ErrorCode::FloatOutOfRange,
+        ErrorCode::ExpectedType,
+        ErrorCode::InvalidCast,
+        ErrorCode::FloatToIntOverflow,
+        ErrorCode::InvalidEscape,
+        ErrorCode::UnterminatedString,
+        ErrorCode::NotCallable,
+        ErrorCode::ArgumentCountMismatch,
+        ErrorCode::ArgumentTypeMismatch,
+        ErrorCode::ExpectedToken,
+        ErrorCode::UndeclaredVariable,
+        ErrorCode::AnnotationMismatch,
+        ErrorCode::NonBoolCondition,
+        ErrorCode::BranchTypeMismatch,
+        ErrorCode::BreakOutsideLoop,
+        ErrorCode::NonIntRangeBound,
+        ErrorCode::ReturnTypeMismatch,
+        ErrorCode::CannotCaptureVariable,
+        ErrorCode::CallDepthExceeded,
+        ErrorCode::CannotInferParamType,
+        ErrorCode::AssignToCapturedVariable,
+        ErrorCode::FunctionUsedAsValue,
+        ErrorCode::CannotInferListType,
+        ErrorCode::ListElementTypeMismatch,
+        ErrorCode::NotIndexable,
+        ErrorCode::NonIntIndex,
+        ErrorCode::IndexOutOfBounds,
+        ErrorCode::UnknownField,
+        ErrorCode::DestructuringMismatch,
+        ErrorCode::UnknownType,
+        ErrorCode::FieldTypeMismatch,
+        ErrorCode::MissingField,
+        ErrorCode::DuplicateField,
+        ErrorCode::NonExhaustiveMatch,
+        ErrorCode::UnreachablePattern,
+        ErrorCode::PatternTypeMismatch,
+        ErrorCode::DuplicateVariant,
+        ErrorCode::CannotInferOptionType,
+        ErrorCode::UnboundVariable,
+        ErrorCode::DuplicateType,
+        ErrorCode::NestingTooDeep,
+        ErrorCode::DuplicateParameter,
+        ErrorCode::DuplicateBinding,
+        ErrorCode::DuplicateFunction,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ErrorCode::SourceFileUnreadable => "E0001",
+            ErrorCode::UnexpectedCharacter => "E0002",
+            ErrorCode::OutOfCharacters => "E0003",
+            ErrorCode::NotABinaryOperator => "E0004",
+            ErrorCode::MissingRightParen => "E0005",
+            ErrorCode::IntegerOutOfRange => "E0006",
+            ErrorCode::UnexpectedEndOfInput => "E0007",
+            ErrorCode::UnexpectedToken => "E0008",
+            ErrorCode::UnknownOperandType => "E0009",
+            ErrorCode::UnaryOperandMismatch => "E0010",
+            ErrorCode::UndeclaredOperator => "E0011",
+            ErrorCode::VariableTypeChanged => "E0012",
+            ErrorCode::InvalidAssignmentTarget => "E0013",
+            ErrorCode::OperandTypesDiffer => "E0014",
+            ErrorCode::OperandTypeMismatch => "E0015",
+            ErrorCode::ArithmeticOverflow => "E0016",
+            ErrorCode::DivisionByZero => "E0017",
+            ErrorCode::InvalidOperandValues => "E0018",
+            ErrorCode::UnsupportedOperator => "E0019",
+            ErrorCode::InvalidAssignmentTargetValue => "E0020",
+            ErrorCode::UnusedVariable => "W0001",
+            ErrorCode::DeadAssignment => "W0002",
+            ErrorCode::SelfAssignment => "W0003",
+            ErrorCode::ConstantCondition => "W0004",
+            ErrorCode::UnaryPlus => "W0005",
+            ErrorCode::FloatOutOfRange => "E0021",
+            ErrorCode::ExpectedType => "E0022",
+            ErrorCode::InvalidCast => "E0023",
+            ErrorCode::FloatToIntOverflow => "E0024",
+            ErrorCode::InvalidEscape => "E0025",
+            ErrorCode::UnterminatedString => "E0026",
+            ErrorCode::NotCallable => "E0027",
+            ErrorCode::ArgumentCountMismatch => "E0028",
+            ErrorCode::ArgumentTypeMismatch => "E0029",
+            ErrorCode::ExpectedToken => "E0030",
+            ErrorCode::UndeclaredVariable => "E0031",
+            ErrorCode::AnnotationMismatch => "E0032",
+            ErrorCode::NonBoolCondition => "E0033",
+            ErrorCode::BranchTypeMismatch => "E0034",
+            ErrorCode::BreakOutsideLoop => "E0035",
+            ErrorCode::NonIntRangeBound => "E0036",
+            ErrorCode::ReturnTypeMismatch => "E0037",
+            ErrorCode::CannotCaptureVariable => "E0038",
+            ErrorCode::CallDepthExceeded => "E0039",
+            ErrorCode::CannotInferParamType => "E0040",
+            ErrorCode::AssignToCapturedVariable => "E0041",
+            ErrorCode::FunctionUsedAsValue => "E0042",
+            ErrorCode::CannotInferListType => "E0043",
+            ErrorCode::ListElementTypeMismatch => "E0044",
+            ErrorCode::NotIndexable => "E0045",
+            ErrorCode::NonIntIndex => "E0046",
+            ErrorCode::IndexOutOfBounds => "E0047",
+            ErrorCode::UnknownField => "E0048",
+            ErrorCode::DestructuringMismatch => "E0049",
+            ErrorCode::UnknownType => "E0050",
+            ErrorCode::FieldTypeMismatch => "E0051",
+            ErrorCode::MissingField => "E0052",
+            ErrorCode::DuplicateField => "E0053",
+            ErrorCode::NonExhaustiveMatch => "E0054",
+            ErrorCode::UnreachablePattern => "E0055",
+            ErrorCode::PatternTypeMismatch => "E0056",
+            ErrorCode::DuplicateVariant => "E0057",
+            ErrorCode::CannotInferOptionType => "E0058",
+            ErrorCode::UnboundVariable => "E0059",
+            ErrorCode::DuplicateType => "E0060",
+            ErrorCode::NestingTooDeep => "E0061",
+            ErrorCode::DuplicateParameter => "E0062",
+            ErrorCode::DuplicateBinding => "E0063",
+            ErrorCode::DuplicateFunction => "E0064",
+        }
+    }
+
+    /// Look up a code by its spelling, e.g., "E0007" (case-insensitively).
+    pub fn from_code(code: &str) -> Option<ErrorCode> {
+        ErrorCode::ALL
+            .iter()
+            .find(|err_code| err_code.as_str().eq_ignore_ascii_case(code))
+            .copied()
+    }
+
+    /// The phase that reports errors with this code.
+    pub fn kind(&self) -> ExprErrorKind {
+        match *self {
+            ErrorCode::SourceFileUnreadable
+            | ErrorCode::UnexpectedCharacter
+            | ErrorCode::OutOfCharacters
+            | ErrorCode::InvalidEscape
+            | ErrorCode::UnterminatedString => ExprErrorKind::ScannerError,
+
+            ErrorCode::NotABinaryOperator
+            | ErrorCode::MissingRightParen
+            | ErrorCode::IntegerOutOfRange
+            | ErrorCode::UnexpectedEndOfInput
+            | ErrorCode::UnexpectedToken
+            | ErrorCode::FloatOutOfRange
+            | ErrorCode::ExpectedType
+            | ErrorCode::ExpectedToken
+            | ErrorCode::NestingTooDeep => ExprErrorKind::ParserError,
+
+            ErrorCode::UnknownOperandType
+            | ErrorCode::UnaryOperandMismatch
+            | ErrorCode::UndeclaredOperator
+            | ErrorCode::VariableTypeChanged
+            | ErrorCode::InvalidAssignmentTarget
+            | ErrorCode::OperandTypesDiffer
+            | ErrorCode::OperandTypeMismatch
+            | ErrorCode::UnusedVariable
+            | ErrorCode::DeadAssignment
+            | ErrorCode::SelfAssignment
+            | ErrorCode::ConstantCondition
+            | ErrorCode::UnaryPlus
+            | ErrorCode::InvalidCast
+            | ErrorCode::NotCallable
+            | ErrorCode::ArgumentCountMismatch
+            | ErrorCode::ArgumentTypeMismatch
+            | ErrorCode::UndeclaredVariable
+            | ErrorCode::AnnotationMismatch
+            | ErrorCode::NonBoolCondition
+            | ErrorCode::BranchTypeMismatch
+            | ErrorCode::BreakOutsideLoop
+            | ErrorCode::NonIntRangeBound
+            | ErrorCode::ReturnTypeMismatch
+            | ErrorCode::CannotCaptureVariable
+            | ErrorCode::CannotInferParamType
+            | ErrorCode::AssignToCapturedVariable
+            | ErrorCode::FunctionUsedAsValue
+            | ErrorCode::CannotInferListType
+            | ErrorCode::ListElementTypeMismatch
+            | ErrorCode::NotIndexable
+            | ErrorCode::NonIntIndex
+            | ErrorCode::UnknownField
+            | ErrorCode::DestructuringMismatch
+            | ErrorCode::UnknownType
+            | ErrorCode::FieldTypeMismatch
+            | ErrorCode::MissingField
+            | ErrorCode::DuplicateField
+            | ErrorCode::NonExhaustiveMatch
+            | ErrorCode::UnreachablePattern
+            | ErrorCode::PatternTypeMismatch
+            | ErrorCode::DuplicateVariant
+            | ErrorCode::CannotInferOptionType
+            | ErrorCode::DuplicateType
+            | ErrorCode::DuplicateParameter
+            | ErrorCode::DuplicateBinding
+            | ErrorCode::DuplicateFunction => ExprErrorKind::CheckerError,
+
+            ErrorCode::ArithmeticOverflow
+            | ErrorCode::DivisionByZero
+            | ErrorCode::InvalidOperandValues
+            | ErrorCode::UnsupportedOperator
+            | ErrorCode::InvalidAssignmentTargetValue
+            | ErrorCode::FloatToIntOverflow
+            | ErrorCode::CallDepthExceeded
+            | ErrorCode::IndexOutOfBounds
+            | ErrorCode::UnboundVariable => ExprErrorKind::InterpreterError,
+        }
+    }
+
+    pub fn explanation(&self) -> Explanation {
+        match *self {
+            ErrorCode::SourceFileUnreadable => Explanation {
+                title: "the source file could not be read",
+                description: "\
+The source file given on the command line (or to :load in the REPL) does not
+exist, is not readable, or is not valid UTF-8.",
+                failing: None,
+                fixed: None,
+            },
+
+            ErrorCode::UnexpectedCharacter => Explanation {
+                title: "unexpected character",
+                description: "\
+The scanner found a character that does not start any token of the language.
+The character is skipped, so that the rest of the file is still checked.",
+                failing: Some("let a = 1 $ 2"),
+                fixed: Some("let a = 1 + 2"),
+            },
+
+            ErrorCode::OutOfCharacters => Explanation {
+                title: "ran out of characters",
+                description: "\
+The scanner tried to read past the end of the source file. Every source file
+ends with a NUL character that stops the scanner, so this indicates a bug in
+the scanner rather than in the program.",
+                failing: None,
+                fixed: None,
+            },
+
+            ErrorCode::NotABinaryOperator => Explanation {
+                title: "not a binary operator",
+                description: "\
+The parser tried to build a binary expression around a token that is not a
+binary operator. Only binary operators bind to a preceding expression, so
+this indicates a bug in the parser rather than in the program.",
+                failing: None,
+                fixed: None,
+            },
+
+            ErrorCode::MissingRightParen => Explanation {
+                title: "missing right parenthesis",
+                description: "\
+A parenthesised expression was not closed. The error points at the opening
+parenthesis that has no matching closing parenthesis.",
+                failing: Some("print((1 + 2)"),
+                fixed: Some("print((1 + 2))"),
+            },
+
+            ErrorCode::IntegerOutOfRange => Explanation {
+                title: "integer literal out of range",
+                description: "\
+Integers are 32-bit signed values, so integer literals cannot be larger than
+2147483647. Note that a negative literal is a unary minus applied to a
+positive literal, so -2147483648 cannot be written directly either.",
+                failing: Some("let a = 3000000000"),
+                fixed: Some("let a = 300000000"),
+            },
+
+            ErrorCode::UnexpectedEndOfInput => Explanation {
+                title: "unexpected end of input",
+                description: "\
+The source ended in the middle of an expression, typically right after a
+binary operator that is missing its right operand.",
+                failing: Some("let a = 1 +"),
+                fixed: Some("let a = 1 + 2"),
+            },
+
+            ErrorCode::UnexpectedToken => Explanation {
+                title: "unexpected token at start of expression",
+                description: "\
+An expression was expected, but the token found cannot start one. Expressions
+start with a literal, a variable, a parenthesis, a unary operator, let or
+print.",
+                failing: Some("let a = * 2"),
+                fixed: Some("let a = 2"),
+            },
+
+            ErrorCode::UnknownOperandType => Explanation {
+                title: "the type of an operand could not be determined",
+                description: "\
+Every expression has a type once the variables that it uses have been
+declared (see E0031), so this indicates a bug in the checker rather than in
+the program.",
+                failing: None,
+                fixed: None,
+            },
+
+            ErrorCode::UnaryOperandMismatch => Explanation {
+                title: "mismatched operand type for unary operator",
+                description: "\
+The operand of a unary operator does not have the type that the operator
+expects - for example, - expects an int and ! expects a bool.",
+                failing: Some("let a = -true"),
+                fixed: Some("let a = -1"),
+            },
+
+            ErrorCode::UndeclaredOperator => Explanation {
+                title: "operator is not declared",
+                description: "\
+The checker looks up the type signature of every operator in the standard
+environment. This error means that an operator is missing from it, which
+indicates a bug in the checker rather than in the program.",
+                failing: None,
+                fixed: None,
+            },
+
+            ErrorCode::VariableTypeChanged => Explanation {
+                title: "the type of a variable cannot change",
+                description: "\
+The type of a variable is given by its declaration, and every later
+assignment must be of the same type. Use a different variable for a value of
+another type.",
+                failing: Some("let a = 1\na = true"),
+                fixed: Some("let a = 1\nlet b = true"),
+            },
+
+            ErrorCode::InvalidAssignmentTarget => Explanation {
+                title: "invalid assignment target",
+                description: "\
+Only variables can be assigned to - the left-hand side of =, +=, -= and the
+other assignment operators must be a variable name.",
+                failing: Some("1 = 2"),
+                fixed: Some("let a = 1\na = 2"),
+            },
+
+            ErrorCode::OperandTypesDiffer => Explanation {
+                title: "operands have different types",
+                description: "\
+The operators == and != accept operands of any type, but both operands must
+have the same type.",
+                failing: Some("print(1 != true)"),
+                fixed: Some("print(1 != 2)"),
+            },
+
+            ErrorCode::OperandTypeMismatch => Explanation {
+                title: "mismatched operand type for binary operator",
+                description: "\
+An operand of a binary operator does not have the type that the operator
+expects - for example, arithmetic operators expect ints, and && and || expect
+bools.",
+                failing: Some("print(1 + true)"),
+                fixed: Some("print(1 + 2)"),
+            },
+
+            ErrorCode::ArithmeticOverflow => Explanation {
+                title: "arithmetic overflow",
+                description: "\
+The result of an arithmetic operation does not fit in a 32-bit signed integer.
+Integer arithmetic is checked, so overflow stops the program instead of
+silently wrapping around.",
+                failing: Some("let a = 2147483647 + 1"),
+                fixed: Some("let a = 2147483646 + 1"),
+            },
+
+            ErrorCode::DivisionByZero => Explanation {
+                title: "division by zero",
+                description: "\
+The right operand of / or % (or of /= or %=) evaluated to zero.",
+                failing: Some("let a = 1 / 0"),
+                fixed: Some("let a = 1 / 1"),
+            },
+
+            ErrorCode::InvalidOperandValues => Explanation {
+                title: "invalid operand values",
+                description: "\
+An operator was applied to values it does not support while running the
+program. The checker rejects such programs, so this indicates a bug in the
+checker or the interpreter rather than in the program.",
+                failing: None,
+                fixed: None,
+            },
+
+            ErrorCode::UnsupportedOperator => Explanation {
+                title: "unsupported operator",
+                description: "\
+The interpreter does not know how to evaluate an operator. This indicates a
+bug in the interpreter rather than in the program.",
+                failing: None,
+                fixed: None,
+            },
+
+            ErrorCode::InvalidAssignmentTargetValue => Explanation {
+                title: "invalid assignment target while running",
+                description: "\
+The interpreter found an assignment to something other than a variable. The
+checker rejects such programs (see E0013), so this indicates a bug in the
+checker or the interpreter rather than in the program.",
+                failing: None,
+                fixed: None,
+            },
+
+            ErrorCode::UnusedVariable => Explanation {
+                title: "variable is assigned but never read",
+                description: "\
+A variable is assigned to, but its value is never read, so the assignment is
+most likely a mistake. This is the unused_variables lint.",
+                failing: Some("let a = 1\nprint(2)"),
+                fixed: Some("let a = 1\nprint(a + 1)"),
+            },
+
+            ErrorCode::DeadAssignment => Explanation {
+                title: "value is overwritten before it is read",
+                description: "\
+A value assigned to a variable is replaced by another assignment before it is
+ever read, so the first assignment has no effect. This is the
+dead_assignments lint.",
+                failing: Some("let a = 1\na = 2\nprint(a)"),
+                fixed: Some("let a = 2\nprint(a)"),
+            },
+
+            ErrorCode::SelfAssignment => Explanation {
+                title: "variable is assigned to itself",
+                description: "\
+Assigning a variable to itself has no effect, and usually means that another
+variable was meant on either side. This is the self_assignment lint.",
+                failing: Some("let a = 1\nlet b = 2\na = a\nprint(a + b)"),
+                fixed: Some("let a = 1\nlet b = 2\na = b\nprint(a + b)"),
+            },
+
+            ErrorCode::ConstantCondition => Explanation {
+                title: "comparison is always true or always false",
+                description: "\
+Both sides of a comparison are the same expression, so its result is known
+without running the program - for example, x == x is always true and x < x
+is always false. This is the constant_conditions lint.",
+                failing: Some("let a = 1\nlet b = 2\nprint(a == a)"),
+                fixed: Some("let a = 1\nlet b = 2\nprint(a == b)"),
+            },
+
+            ErrorCode::UnaryPlus => Explanation {
+                title: "unary plus has no effect",
+                description: "\
+Unary plus returns its operand unchanged, so it can be removed. It is often
+a typo for += or for a binary plus. This is the unary_plus lint.",
+                failing: Some("let a = +1\nprint(a)"),
+                fixed: Some("let a = 1\nprint(a)"),
+            },
+
+            ErrorCode::FloatOutOfRange => Explanation {
+                title: "float literal out of range",
+                description: "\
+Floats are 64-bit IEEE 754 values, so float literals cannot be larger than
+about 1.8e308.",
+                failing: Some("let a = 1e400"),
+                fixed: Some("let a = 1e300"),
+            },
+
+            ErrorCode::ExpectedType => Explanation {
+                title: "expected a type",
+                description: "\
+A type was expected, e.g., after as or after the : of an annotation. The types
+are int, float, bool, string, function types such as fn(int) -> int, list
+types such as List<int>, tuple types such as (int, bool), and the names of
+structs and enums.",
+                failing: Some("let a: 1 = 1"),
+                fixed: Some("let a: int = 1"),
+            },
+
+            ErrorCode::InvalidCast => Explanation {
+                title: "invalid conversion",
+                description: "\
+Only ints and floats can be converted to each other with as. Converting a
+float to an int rounds it towards zero.",
+                failing: Some("let a = true as int"),
+                fixed: Some("let a = 1.5 as int"),
+            },
+
+            ErrorCode::FloatToIntOverflow => Explanation {
+                title: "float cannot be converted to int",
+                description: "\
+A float that is not a number, infinite, or outside the range of 32-bit signed
+integers was converted to an int.",
+                failing: Some("let a = 1e10 as int"),
+                fixed: Some("let a = 1e9 as int"),
+            },
+
+            ErrorCode::InvalidEscape => Explanation {
+                title: "invalid escape in string literal",
+                description: "\
+A backslash in a string literal starts an escape sequence. The escapes are
+\\n (newline), \\t (tab), \\r (carriage return), \\0 (nul), \\\" (quote),
+\\\\ (backslash) and \\u{...}, which is a unicode scalar value of one to
+six hex digits.",
+                failing: Some("let a = \"C:\\q\""),
+                fixed: Some("let a = \"C:\\\\q\""),
+            },
+
+            ErrorCode::UnterminatedString => Explanation {
+                title: "unterminated string literal",
+                description: "\
+A string literal has no closing quote before the end of the line. Strings
+cannot span lines - use \\n for a newline.",
+                failing: Some("let a = \"abc"),
+                fixed: Some("let a = \"abc\""),
+            },
+
+            ErrorCode::NotCallable => Explanation {
+                title: "not a function",
+                description: "\
+Only functions can be called. The builtin functions are:
+
+    len(s: string) -> int   the number of characters in s",
+                failing: Some("let a = 1\nprint(a(2))"),
+                fixed: Some("let a = \"ab\"\nprint(len(a))"),
+            },
+
+            ErrorCode::ArgumentCountMismatch => Explanation {
+                title: "wrong number of arguments",
+                description: "\
+A function was called with more or fewer arguments than it has parameters.",
+                failing: Some("print(len(\"a\", \"b\"))"),
+                fixed: Some("print(len(\"ab\"))"),
+            },
+
+            ErrorCode::ArgumentTypeMismatch => Explanation {
+                title: "mismatched argument type",
+                description: "\
+An argument does not have the type of the corresponding parameter of the
+function. There are no implicit conversions.",
+                failing: Some("print(len(12))"),
+                fixed: Some("print(len(\"12\"))"),
+            },
+
+            ErrorCode::ExpectedToken => Explanation {
+                title: "expected a different token",
+                description: "\
+The parser expected a particular token, e.g., an = after the variable name
+in a let declaration, but found another one.",
+                failing: Some("let a 1"),
+                fixed: Some("let a = 1"),
+            },
+
+            ErrorCode::UndeclaredVariable => Explanation {
+                title: "undeclared variable",
+                description: "\
+A variable or a function was used before it was declared. Variables are
+declared with let, which gives them their initial value. A function can call
+itself and the functions declared before it, but not those declared after it,
+so two functions cannot call each other.",
+                failing: Some("a = 1\nprint(a)"),
+                fixed: Some("let a = 1\nprint(a)"),
+            },
+
+            ErrorCode::AnnotationMismatch => Explanation {
+                title: "mismatched type annotation",
+                description: "\
+The type annotation of a let declaration does not match the type of its
+initial value. There are no implicit conversions - use as to convert between
+int and float.",
+                failing: Some("let a: float = 1\nprint(a)"),
+                fixed: Some("let a: float = 1.0\nprint(a)"),
+            },
+
+            ErrorCode::NonBoolCondition => Explanation {
+                title: "condition is not a bool",
+                description: "\
+The condition of an if, a while loop or a ?: must have type bool. Ints are not
+implicitly converted to bools, so compare them explicitly instead.",
+                failing: Some("let a = 1\nif a { print(a) }"),
+                fixed: Some("let a = 1\nif a != 0 { print(a) }"),
+            },
+
+            ErrorCode::BranchTypeMismatch => Explanation {
+                title: "if and else have different types",
+                description: "\
+An if with an else is an expression whose value is the value of the branch
+that was taken, so both branches must have the same type. An if without an
+else has type (), whatever the type of its branch. The same goes for the two
+arms of a ?:, cond ? a : b.",
+                failing: Some("let a = 1\nlet b = if a > 0 { 1 } else { \"negative\" }"),
+                fixed: Some(
+                    "let a = 1\nlet b = if a > 0 { \"positive\" } else { \"negative\" }\nprint(b)",
+                ),
+            },
+
+            ErrorCode::BreakOutsideLoop => Explanation {
+                title: "break or continue outside of a loop",
+                description: "\
+break and continue can only be used inside the body of a while or a for loop,
+where they leave the loop or skip to its next iteration.",
+                failing: Some("let a = 1\nif a > 0 { break }"),
+                fixed: Some("let a = 1\nwhile a > 0 { a -= 1 }"),
+            },
+
+            ErrorCode::NonIntRangeBound => Explanation {
+                title: "range bound is not an int",
+                description: "\
+A for loop counts over a range of ints, a..b, from a up to (but not
+including) b, so both bounds must have type int.",
+                failing: Some("for i in 0..2.5 { print(i) }"),
+                fixed: Some("for i in 0..3 { print(i) }"),
+            },
+
+            ErrorCode::ReturnTypeMismatch => Explanation {
+                title: "function body does not match the return type",
+                description: "\
+The value of a function is the value of the last expression in its body,
+which must have the return type of the function. A function without a
+return type (-> type) returns (), i.e., it has no value.",
+                failing: Some("fn half(x: int) -> float { x / 2 }"),
+                fixed: Some("fn half(x: int) -> float { x as float / 2.0 }"),
+            },
+
+            ErrorCode::CannotCaptureVariable => Explanation {
+                title: "function refers to a variable outside of it",
+                description: "\
+The body of a function can refer to its parameters, to the variables declared
+in it and to other functions, but not to the variables of the scope that the
+function is declared in. Pass such variables to the function as arguments
+instead, or use a closure, which can capture them.",
+                failing: Some("let k = 2\nfn scale(x: int) -> int { x * k }\nprint(scale(3))"),
+                fixed: Some("let k = 2\nfn scale(x: int, k: int) -> int { x * k }\nprint(scale(3, k))"),
+            },
+
+            ErrorCode::CallDepthExceeded => Explanation {
+                title: "calls are nested too deeply",
+                description: "\
+A function called itself (or other functions) so many times without
+returning that the limit on nested calls was reached. This usually means that
+a recursive function is missing its base case. The limit can be raised with
+--max-call-depth.",
+                failing: Some("fn down(n: int) -> int { down(n - 1) }\nprint(down(3))"),
+                fixed: Some("fn down(n: int) -> int { if n == 0 { 0 } else { down(n - 1) } }\nprint(down(3))"),
+            },
+
+            ErrorCode::CannotInferParamType => Explanation {
+                title: "the type of a closure parameter is unknown",
+                description: "\
+A parameter of a closure was not given a type, and the type could not be
+inferred from where the closure is used. Types are inferred for closures that
+are passed directly to a function, or that initialise a variable with a type
+annotation. Otherwise, annotate the parameter, e.g., |x: int| x + 1.",
+                failing: Some("let inc = |x| x + 1\nprint(inc(1))"),
+                fixed: Some("let inc = |x: int| x + 1\nprint(inc(1))"),
+            },
+
+            ErrorCode::AssignToCapturedVariable => Explanation {
+                title: "closure assigns to a variable outside of it",
+                description: "\
+A closure captures the values of the variables that it refers to when it is
+created, so assigning to such a variable in its body would only change the
+copy of the closure, not the variable itself. Return the new value from the
+closure and assign it outside of it instead.",
+                failing: Some("let total = 0\nlet add = |x: int| total += x\nadd(1)"),
+                fixed: Some("let total = 0\nlet add = |x: int| total + x\ntotal = add(1)\nprint(total)"),
+            },
+
+            ErrorCode::FunctionUsedAsValue => Explanation {
+                title: "function used as a value",
+                description: "\
+A function declared with fn can only be called. To store a function in a
+variable or to pass it to another function, wrap the call in a closure.",
+                failing: Some("fn square(x: int) -> int { x * x }\nlet f = square\nprint(f(3))"),
+                fixed: Some("fn square(x: int) -> int { x * x }\nlet f = |x: int| square(x)\nprint(f(3))"),
+            },
+
+            ErrorCode::CannotInferListType => Explanation {
+                title: "the element type of an empty list is unknown",
+                description: "\
+An empty list literal was used where its element type could not be inferred.
+Give the variable a type annotation, such as List<int>, or start the list with
+at least one element.",
+                failing: Some("let xs = []\npush(xs, 1)"),
+                fixed: Some("let xs: List<int> = []\npush(xs, 1)"),
+            },
+
+            ErrorCode::ListElementTypeMismatch => Explanation {
+                title: "list elements have different types",
+                description: "\
+All the elements of a list must have the same type, and a value stored in a
+list with an indexed assignment must have the type of its elements. Convert
+the values with as where needed.",
+                failing: Some("let xs = [1, 2.5]"),
+                fixed: Some("let xs = [1 as float, 2.5]"),
+            },
+
+            ErrorCode::NotIndexable => Explanation {
+                title: "indexed value is not a list",
+                description: "\
+Only lists can be indexed with [...]. The value being indexed has some other
+type.",
+                failing: Some("let n = 42\nprint(n[0])"),
+                fixed: Some("let ns = [42]\nprint(ns[0])"),
+            },
+
+            ErrorCode::NonIntIndex => Explanation {
+                title: "list index is not an int",
+                description: "\
+Lists are indexed by position, so the index must be an int. Convert other
+numbers with as.",
+                failing: Some("let xs = [1, 2, 3]\nprint(xs[1.0])"),
+                fixed: Some("let xs = [1, 2, 3]\nprint(xs[1.0 as int])"),
+            },
+
+            ErrorCode::IndexOutOfBounds => Explanation {
+                title: "list index out of bounds",
+                description: "\
+A list was indexed at a position that it does not have. Indices start at 0,
+so the last element of a list xs is at len(xs) - 1. Popping from an empty list
+is reported in the same way.",
+                failing: Some("let xs = [1, 2, 3]\nprint(xs[3])"),
+                fixed: Some("let xs = [1, 2, 3]\nprint(xs[len(xs) - 1])"),
+            },
+
+            ErrorCode::UnknownField => Explanation {
+                title: "no such field",
+                description: "\
+A field was accessed on a value that does not have it. The elements of a tuple
+are accessed by position, starting at 0, so a pair p has the fields p.0 and
+p.1. The fields of a record are those declared in its struct.",
+                failing: Some("let p = (1, \"one\")\nprint(p.2)"),
+                fixed: Some("let p = (1, \"one\")\nprint(p.1)"),
+            },
+
+            ErrorCode::DestructuringMismatch => Explanation {
+                title: "tuple assignment does not match the value",
+                description: "\
+A tuple of targets, such as (a, b), was assigned a value of another shape.
+The value must be a tuple with as many elements as there are targets, and
+each element must have the type of its target.",
+                failing: Some("let a = 1\nlet b = 2\n(a, b) = (b, a, 3)"),
+                fixed: Some("let a = 1\nlet b = 2\n(a, b) = (b, a)"),
+            },
+
+            ErrorCode::UnknownType => Explanation {
+                title: "cannot find type",
+                description: "\
+A type was named that is neither one of the builtin types (int, float, bool
+and string) nor a struct or an enum declared (with struct or enum) earlier in
+an enclosing scope.",
+                failing: Some("let a: integer = 1"),
+                fixed: Some("let a: int = 1"),
+            },
+
+            ErrorCode::FieldTypeMismatch => Explanation {
+                title: "field has the wrong type",
+                description: "\
+The value given for a field of a record, or assigned to it, does not have the
+type that the field is declared with in its struct.",
+                failing: Some("struct Order { amount: int, urgent: bool }\nlet o = Order { amount: 10, urgent: 1 }"),
+                fixed: Some("struct Order { amount: int, urgent: bool }\nlet o = Order { amount: 10, urgent: true }"),
+            },
+
+            ErrorCode::MissingField => Explanation {
+                title: "missing field in record",
+                description: "\
+A record must give a value for every field of its struct.",
+                failing: Some("struct Order { amount: int, urgent: bool }\nlet o = Order { amount: 10 }"),
+                fixed: Some("struct Order { amount: int, urgent: bool }\nlet o = Order { amount: 10, urgent: false }"),
+            },
+
+            ErrorCode::DuplicateField => Explanation {
+                title: "field given more than once",
+                description: "\
+A field was declared twice in a struct, or given a value twice in a record.
+Each field can only appear once.",
+                failing: Some("struct Point { x: int, x: int }"),
+                fixed: Some("struct Point { x: int, y: int }"),
+            },
+
+            ErrorCode::NonExhaustiveMatch => Explanation {
+                title: "match is not exhaustive",
+                description: "\
+The arms of a match must cover every value of the matched expression, so that
+one of them always applies. A bool is covered by true and false, and an enum by
+each of its variants, but ints and strings can only be covered by a _ or a name,
+which match any value.",
+                failing: Some("enum Status { Ok, Err(int) }\nlet s = Err(2)\nprint(match s { Ok => 0 })"),
+                fixed: Some("enum Status { Ok, Err(int) }\nlet s = Err(2)\nprint(match s { Ok => 0, Err(code) => code })"),
+            },
+
+            ErrorCode::UnreachablePattern => Explanation {
+                title: "unreachable match arm",
+                description: "\
+The pattern of an arm only matches values that are already matched by the arms
+before it, so the arm can never be taken. The arms are tried in order, so a _
+or a name, which match any value, must come last.",
+                failing: Some("let n = 3\nprint(match n { _ => 0, 1 => 1 })"),
+                fixed: Some("let n = 3\nprint(match n { 1 => 1, _ => 0 })"),
+            },
+
+            ErrorCode::PatternTypeMismatch => Explanation {
+                title: "pattern does not match the type",
+                description: "\
+A pattern can never match a value of the type of the matched expression, e.g.,
+a string literal when matching an int, or a variant of another enum. A variant
+pattern must also have a pattern for each value of the payload of the variant.",
+                failing: Some("let n = 3\nprint(match n { true => 1, _ => 0 })"),
+                fixed: Some("let n = 3\nprint(match n { 1 => 1, _ => 0 })"),
+            },
+
+            ErrorCode::DuplicateVariant => Explanation {
+                title: "duplicate variant",
+                description: "\
+An enum declares a variant more than once. The variants of an enum must have
+different names.",
+                failing: Some("enum Status { Ok, Ok }"),
+                fixed: Some("enum Status { Ok, Err }"),
+            },
+
+            ErrorCode::CannotInferOptionType => Explanation {
+                title: "the type of none is unknown",
+                description: "\
+The empty option none was used where its type could not be inferred. Give the
+variable a type annotation, such as Option<int>, or use it where an option of a
+known type is expected.",
+                failing: Some("let x = none\nprint(x ?? 1)"),
+                fixed: Some("let x: Option<int> = none\nprint(x ?? 1)"),
+            },
+
+            ErrorCode::UnboundVariable => Explanation {
+                title: "unbound variable while running",
+                description: "\
+The interpreter found no value bound to a variable. The checker rejects such
+programs (see E0031), so this indicates a bug in the checker or the interpreter
+rather than in the program.",
+                failing: None,
+                fixed: None,
+            },
+
+            ErrorCode::DuplicateType => Explanation {
+                title: "type declared twice in the same scope",
+                description: "\
+A struct or an enum was declared with the name of another struct or enum that
+is declared in the same scope. Give it another name, or declare it in a block
+of its own, where it shadows the outer one.",
+                failing: Some("struct Point { x: int }\nstruct Point { x: int, y: int }"),
+                fixed: Some("struct Point { x: int }\n{\n  struct Point { x: int, y: int }\n  print(Point { x: 1, y: 2 })\n}"),
+            },
+
+            ErrorCode::NestingTooDeep => Explanation {
+                title: "expression nested too deeply",
+                description: "\
+An expression, a type or a pattern was nested inside more than 256 others, e.g.,
+in parentheses, blocks or calls, or in a chain of more than 256 operators such
+as 1 + 1 + ... + 1. Such deep nesting would exhaust the stack of the later
+phases, so it is rejected by the parser. Move the inner parts into variables or
+functions of their own.",
+                failing: None,
+                fixed: None,
+            },
+
+            ErrorCode::DuplicateParameter => Explanation {
+                title: "parameter declared twice",
+                description: "\
+A function or a closure has two parameters with the same name, so the second
+would hide the first in the body. Give each parameter a name of its own.",
+                failing: Some("fn area(w: int, w: int) -> int { w * w }"),
+                fixed: Some("fn area(w: int, h: int) -> int { w * h }\nprint(area(2, 3))"),
+            },
+
+            ErrorCode::DuplicateBinding => Explanation {
+                title: "name bound twice in a pattern",
+                description: "\
+A pattern binds the same name more than once, e.g., in the payload of a variant,
+so it is not clear which value the name would have in the arm. Give each
+binding a name of its own, or use _ for the values that are not needed.",
+                failing: Some("enum Pair { P(int, int) }\nprint(match P(1, 2) { P(x, x) => x })"),
+                fixed: Some("enum Pair { P(int, int) }\nprint(match P(1, 2) { P(x, y) => x + y })"),
+            },
+
+            ErrorCode::DuplicateFunction => Explanation {
+                title: "function declared twice in the same scope",
+                description: "\
+A function was declared with the name of another function that is declared in
+the same scope, which would silently replace it. Give it another name, or
+declare it in a block of its own, where it shadows the outer one.",
+                failing: Some("fn f() -> int { 1 }\nfn f() -> int { 2 }\nprint(f())"),
+                fixed: Some("fn f() -> int { 1 }\nfn g() -> int { 2 }\nprint(f() + g())"),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indent = |example: &str| {
+            example
+                .lines()
+                .map(|line| format!("    {}", line))
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+
+        write!(f, "{}", self.description)?;
+
+        if let Some(failing) = self.failing {
+            write!(
+                f,
+                "\n\nFor example, this is reported:\n\n{}",
+                indent(failing)
+            )?;
+        }
+        if let Some(fixed) = self.fixed {
+            write!(f, "\n\nwhereas this is not:\n\n{}", indent(fixed))?;
+        }
+
+        Ok(())
+    }
+}
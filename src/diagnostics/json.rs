@@ -0,0 +1,96 @@
+use crate::error::{ExprError, Label};
+use crate::front::source_file::{Location, Span};
+use std::fmt::Write;
+
+/// Renders an error as a single line of JSON, for tools that consume
+/// diagnostics programmatically. For example:
+///
+/// ```text
+/// {"code":"E0007","kind":"ParserError","severity":"error","message":"...",
+///  "file":"a.expr","span":{"start":{"line":1,"col":3},"end":{"line":1,"col":3}},
+///  "labels":[],"notes":[],"help":null}
+/// ```
+///
+/// `file` and `span` are null for errors without a location.
+pub fn to_json(err: &ExprError) -> String {
+    let mut out = String::new();
+
+    out.push('{');
+    let _ = write!(out, "\"code\":{}", string(err.code().as_str()));
+    let _ = write!(out, ",\"kind\":{}", string(&format!("{:?}", err.kind())));
+    let _ = write!(out, ",\"severity\":{}", string(&err.severity().to_string()));
+    let _ = write!(out, ",\"message\":{}", string(err.message()));
+
+    match err.span() {
+        Some(span) => {
+            let file = span.start.source_file.display().to_string();
+            let _ = write!(out, ",\"file\":{}", string(&file));
+            let _ = write!(out, ",\"span\":{}", span_object(span));
+        }
+        None => out.push_str(",\"file\":null,\"span\":null"),
+    }
+
+    let labels = err.labels().iter().map(label_object).collect::<Vec<_>>();
+    let _ = write!(out, ",\"labels\":[{}]", labels.join(","));
+
+    let notes = err
+        .notes()
+        .iter()
+        .map(|note| string(note))
+        .collect::<Vec<_>>();
+    let _ = write!(out, ",\"notes\":[{}]", notes.join(","));
+
+    match err.help() {
+        Some(help) => {
+            let _ = write!(out, ",\"help\":{}", string(help));
+        }
+        None => out.push_str(",\"help\":null"),
+    }
+    out.push('}');
+
+    out
+}
+
+fn location_object(loc: &Location) -> String {
+    format!("{{\"line\":{},\"col\":{}}}", loc.line, loc.col)
+}
+
+fn span_object(span: &Span) -> String {
+    format!(
+        "{{\"start\":{},\"end\":{}}}",
+        location_object(&span.start),
+        location_object(&span.end)
+    )
+}
+
+fn label_object(label: &Label) -> String {
+    format!(
+        "{{\"file\":{},\"span\":{},\"message\":{}}}",
+        string(&label.span.start.source_file.display().to_string()),
+        span_object(&label.span),
+        string(&label.message)
+    )
+}
+
+/// Quote and escape `s` as a JSON string.
+fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+
+    out
+}
@@ -0,0 +1,69 @@
+pub mod codes;
+pub mod json;
+pub mod render;
+
+use crate::error::ExprError;
+use std::cmp::Ordering;
+
+/// A sink for the errors (and warnings) reported by the different phases, so
+/// that a single run can report every problem in a source file.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    errors: Vec<ExprError>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Diagnostics { errors: Vec::new() }
+    }
+
+    pub fn push(&mut self, err: ExprError) {
+        self.errors.push(err);
+    }
+
+    /// Check if any errors have been reported - warnings do not count.
+    pub fn has_errors(&self) -> bool {
+        self.errors.iter().any(|err| err.is_error())
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.errors.iter().filter(|err| err.is_error()).count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.errors.len() - self.error_count()
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The first error that was reported, i.e., one from the earliest phase
+    /// that failed. Warnings are skipped.
+    pub fn first_error(&self) -> Option<&ExprError> {
+        self.errors.iter().find(|err| err.is_error())
+    }
+
+    /// The collected errors ordered by location. Errors without a location
+    /// are placed at the end, in the order in which they were reported.
+    pub fn sorted(&self) -> Vec<&ExprError> {
+        let mut errors = self.errors.iter().collect::<Vec<&ExprError>>();
+        errors.sort_by(|lhs, rhs| match (lhs.location(), rhs.location()) {
+            (Some(lhs), Some(rhs)) => {
+                (&lhs.source_file, lhs.line, lhs.col).cmp(&(&rhs.source_file, rhs.line, rhs.col))
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+        errors
+    }
+
+    pub fn clear(&mut self) {
+        self.errors.clear();
+    }
+}
@@ -0,0 +1,227 @@
+use crate::error::{ExprError, Severity};
+use crate::front::source_file::{SourceFile, Span};
+use std::fmt::Write;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// An underline to be drawn beneath a single line of source.
+struct Annotation<'a> {
+    line: usize,
+    start_col: usize,
+    end_col: usize,
+    message: Option<&'a str>,
+    primary: bool,
+}
+
+/// Renders errors in the style of rustc - the header, the offending source
+/// lines with the primary span underlined with carets and the secondary spans
+/// with dashes, followed by any notes and help text.
+pub struct Renderer<'a> {
+    source_file: &'a SourceFile,
+    colour: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(source_file: &'a SourceFile, colour: bool) -> Self {
+        Renderer {
+            source_file,
+            colour,
+        }
+    }
+
+    fn paint(&self, colour: &str, text: &str) -> String {
+        if self.colour {
+            format!("{}{}{}", colour, text, RESET)
+        } else {
+            text.to_owned()
+        }
+    }
+
+    fn in_source_file(&self, span: &Span) -> bool {
+        *span.start.source_file == self.source_file.path
+    }
+
+    /// Split a span into one annotation per source line that it covers. The
+    /// message, if any, is attached to the annotation on the last line.
+    fn annotate<'b>(
+        &self,
+        span: &Span,
+        message: Option<&'b str>,
+        primary: bool,
+        annotations: &mut Vec<Annotation<'b>>,
+    ) {
+        if !self.in_source_file(span) {
+            return;
+        }
+
+        for line in span.start.line..=span.end.line {
+            let line_len = self.source_file.line(line).map_or(0, |text| text.len());
+            let start_col = if line == span.start.line {
+                span.start.col
+            } else {
+                1
+            };
+            let end_col = if line == span.end.line {
+                span.end.col
+            } else {
+                line_len.max(start_col)
+            };
+
+            annotations.push(Annotation {
+                line,
+                start_col,
+                end_col: end_col.max(start_col),
+                message: if line == span.end.line { message } else { None },
+                primary,
+            });
+        }
+    }
+
+    pub fn render(&self, err: &ExprError) -> String {
+        let mut out = String::new();
+        let severity_colour = match err.severity() {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        let _ = writeln!(
+            out,
+            "{}{}",
+            self.paint(
+                severity_colour,
+                &format!("{}[{}]", err.severity(), err.code())
+            ),
+            self.paint(BOLD, &format!(": {}", err.message()))
+        );
+
+        let mut annotations = Vec::new();
+        if let Some(span) = err.span() {
+            self.annotate(span, None, true, &mut annotations);
+        }
+        for label in err.labels() {
+            self.annotate(&label.span, Some(&label.message), false, &mut annotations);
+        }
+
+        let mut lines = annotations.iter().map(|ann| ann.line).collect::<Vec<_>>();
+        lines.sort_unstable();
+        lines.dedup();
+
+        let gutter_width = lines.last().map_or(1, |line| line.to_string().len());
+        let pad = " ".repeat(gutter_width);
+        let bar = self.paint(BLUE, "|");
+
+        if let Some(loc) = err.location() {
+            let _ = writeln!(
+                out,
+                "{}{} {}:{}:{}",
+                pad,
+                self.paint(BLUE, "-->"),
+                loc.source_file.display(),
+                loc.line,
+                loc.col
+            );
+        }
+
+        if !lines.is_empty() {
+            let _ = writeln!(out, "{} {}", pad, bar);
+        }
+
+        let mut prev_line = None;
+        for line in lines {
+            if let Some(prev_line) = prev_line {
+                if line > prev_line + 1 {
+                    let _ = writeln!(out, "{}", self.paint(BLUE, "..."));
+                }
+            }
+            prev_line = Some(line);
+
+            let text = self.source_file.line(line).unwrap_or("").replace('\t', " ");
+            let _ = writeln!(
+                out,
+                "{} {} {}",
+                self.paint(BLUE, &format!("{:>width$}", line, width = gutter_width)),
+                bar,
+                text
+            );
+
+            for ann in annotations
+                .iter()
+                .filter(|ann| ann.line == line && ann.primary)
+                .chain(
+                    annotations
+                        .iter()
+                        .filter(|ann| ann.line == line && !ann.primary),
+                )
+            {
+                let (marker, colour) = if ann.primary {
+                    ("^", severity_colour)
+                } else {
+                    ("-", BLUE)
+                };
+
+                let mut underline = marker.repeat(ann.end_col - ann.start_col + 1);
+                if let Some(message) = ann.message {
+                    underline.push(' ');
+                    underline.push_str(message);
+                }
+
+                let _ = writeln!(
+                    out,
+                    "{} {} {}{}",
+                    pad,
+                    bar,
+                    " ".repeat(ann.start_col.saturating_sub(1)),
+                    self.paint(colour, &underline)
+                );
+            }
+        }
+
+        // labels in other source files cannot be shown inline, so they are
+        // listed as notes along with their location instead.
+        let mut notes = err
+            .labels()
+            .iter()
+            .filter(|label| !self.in_source_file(&label.span))
+            .map(|label| {
+                format!(
+                    "{} ({}:{}:{})",
+                    label.message,
+                    label.span.start.source_file.display(),
+                    label.span.start.line,
+                    label.span.start.col
+                )
+            })
+            .collect::<Vec<String>>();
+        notes.extend(err.notes().iter().cloned());
+
+        if !notes.is_empty() || err.help().is_some() {
+            let _ = writeln!(out, "{} {}", pad, bar);
+        }
+
+        for note in notes {
+            let _ = writeln!(
+                out,
+                "{} {} {}",
+                pad,
+                self.paint(BLUE, "="),
+                self.paint(BOLD, "note:") + " " + &note
+            );
+        }
+
+        if let Some(help) = err.help() {
+            let _ = writeln!(
+                out,
+                "{} {} {}",
+                pad,
+                self.paint(BLUE, "="),
+                self.paint(BOLD, "help:") + " " + help
+            );
+        }
+
+        out
+    }
+}
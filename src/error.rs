@@ -1,34 +1,152 @@
-use crate::front::source_file::Location;
+use crate::diagnostics::codes::ErrorCode;
+use crate::front::source_file::{Location, Span};
 use std::error::Error;
 use std::fmt;
 
-pub type ExprResult<T> = Result<T, Box<dyn Error>>;
+pub type ExprResult<T> = Result<T, ExprError>;
 
-#[derive(Debug)]
+/// A secondary span attached to an error, along with a message explaining
+/// its relevance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(span: Span, message: String) -> Self {
+        Label { span, message }
+    }
+}
+
+/// Errors stop a program from running, whereas warnings only point out
+/// likely mistakes. Warnings can be turned into errors (see `LintLevel`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct ExprError {
-    kind: ExprErrorKind,
+    code: ErrorCode,
+    severity: Severity,
     message: String,
+    span: Option<Box<Span>>,
+    labels: Vec<Label>,
+    notes: Vec<String>,
+    help: Option<String>,
 }
 
 impl ExprError {
-    pub fn new(kind: ExprErrorKind, message: String) -> Self {
-        ExprError { kind, message }
+    pub fn new(code: ErrorCode, message: String) -> Self {
+        ExprError {
+            code,
+            severity: Severity::Error,
+            message,
+            span: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn with_span(code: ErrorCode, message: String, span: Span) -> Self {
+        ExprError {
+            code,
+            severity: Severity::Error,
+            message,
+            span: Some(Box::new(span)),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    /// Attach `span` to the error, unless it already has a more precise one.
+    pub fn or_span(mut self, span: &Span) -> Self {
+        if self.span.is_none() {
+            self.span = Some(Box::new(span.clone()));
+        }
+        self
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: String) -> Self {
+        self.labels.push(Label::new(span, message));
+        self
     }
-}
 
-pub fn report_error(err: ExprError, loc: Option<&Location>) {
-    if let Some(loc) = loc {
-        eprintln!(
-            "[{:?}] line: {}, col: {} - {}",
-            loc.source_file, loc.line, loc.col, err
-        );
-    } else {
-        eprintln!("{}", err);
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Self {
+        self.help = Some(help);
+        self
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// The phase that reported the error, which is implied by its code.
+    pub fn kind(&self) -> ExprErrorKind {
+        self.code.kind()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> Option<&Span> {
+        self.span.as_deref()
+    }
+
+    pub fn location(&self) -> Option<&Location> {
+        self.span().map(|span| &span.start)
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    pub fn help(&self) -> Option<&str> {
+        self.help.as_deref()
     }
-    panic!()
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ExprErrorKind {
     ScannerError,
     ParserError,
@@ -36,6 +154,18 @@ pub enum ExprErrorKind {
     InterpreterError,
 }
 
+impl ExprErrorKind {
+    /// The process exit code for a run that failed with this kind of error.
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            ExprErrorKind::ScannerError => 3,
+            ExprErrorKind::ParserError => 4,
+            ExprErrorKind::CheckerError => 5,
+            ExprErrorKind::InterpreterError => 6,
+        }
+    }
+}
+
 impl fmt::Display for ExprErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -53,7 +183,20 @@ impl fmt::Display for ExprErrorKind {
 
 impl fmt::Display for ExprError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}{}", self.kind, self.message)
+        if let Some(loc) = self.location() {
+            write!(
+                f,
+                "[{:?}] line: {}, col: {} - {} [{}]: {}",
+                loc.source_file,
+                loc.line,
+                loc.col,
+                self.kind(),
+                self.code,
+                self.message
+            )
+        } else {
+            write!(f, "{} [{}]: {}", self.kind(), self.code, self.message)
+        }
     }
 }
 
@@ -1,3 +1,6 @@
+use super::source_file::Span;
+use std::fmt;
+
 #[derive(Debug)]
 pub struct Ast {
     pub exprs: Vec<Expr>,
@@ -9,82 +12,894 @@ impl Ast {
     }
 }
 
-#[derive(Debug)]
+/// The larger variants are boxed to keep `Expr` small, as every level of a
+/// nested expression holds a few of them on the stack while it is parsed.
+#[derive(Debug, Clone)]
 pub enum Expr {
-    AssignExpr(AssignExpr),
-    BinaryExpr(BinaryExpr),
-    BoolExpr(bool),
-    IntegerExpr(i32),
-    PrintExpr(Box<Expr>),
+    AssignExpr(Box<AssignExpr>),
+    BinaryExpr(Box<BinaryExpr>),
+    BlockExpr(BlockExpr),
+    BoolExpr(BoolExpr),
+    BreakExpr(BreakExpr),
+    CallExpr(CallExpr),
+    CastExpr(Box<CastExpr>),
+    ContinueExpr(ContinueExpr),
+    EnumExpr(Box<EnumExpr>),
+    FieldExpr(Box<FieldExpr>),
+    FloatExpr(FloatExpr),
+    FnExpr(Box<FnExpr>),
+    ForExpr(Box<ForExpr>),
+    IfExpr(IfExpr),
+    IndexExpr(IndexExpr),
+    IntegerExpr(IntegerExpr),
+    LambdaExpr(Box<LambdaExpr>),
+    LetExpr(Box<LetExpr>),
+    ListExpr(ListExpr),
+    MatchExpr(MatchExpr),
+    NoneExpr(NoneExpr),
+    PrintExpr(PrintExpr),
+    RecordExpr(Box<RecordExpr>),
+    StringExpr(StringExpr),
+    StructExpr(Box<StructExpr>),
+    TernaryExpr(TernaryExpr),
+    TupleExpr(TupleExpr),
     UnaryExpr(UnaryExpr),
-    VnameExpr(VnameExpr),
+    VnameExpr(Box<VnameExpr>),
+    WhileExpr(WhileExpr),
+}
+
+impl Expr {
+    pub fn span(&self) -> &Span {
+        match *self {
+            Expr::AssignExpr(ref ass_expr) => &ass_expr.span,
+            Expr::BinaryExpr(ref bin_expr) => &bin_expr.span,
+            Expr::BlockExpr(ref block_expr) => &block_expr.span,
+            Expr::BoolExpr(ref bool_expr) => &bool_expr.span,
+            Expr::BreakExpr(ref break_expr) => &break_expr.span,
+            Expr::CallExpr(ref call_expr) => &call_expr.span,
+            Expr::CastExpr(ref cast_expr) => &cast_expr.span,
+            Expr::ContinueExpr(ref continue_expr) => &continue_expr.span,
+            Expr::EnumExpr(ref enum_expr) => &enum_expr.span,
+            Expr::FieldExpr(ref field_expr) => &field_expr.span,
+            Expr::FloatExpr(ref float_expr) => &float_expr.span,
+            Expr::FnExpr(ref fn_expr) => &fn_expr.span,
+            Expr::ForExpr(ref for_expr) => &for_expr.span,
+            Expr::IfExpr(ref if_expr) => &if_expr.span,
+            Expr::IndexExpr(ref index_expr) => &index_expr.span,
+            Expr::IntegerExpr(ref int_expr) => &int_expr.span,
+            Expr::LambdaExpr(ref lambda_expr) => &lambda_expr.span,
+            Expr::LetExpr(ref let_expr) => &let_expr.span,
+            Expr::ListExpr(ref list_expr) => &list_expr.span,
+            Expr::MatchExpr(ref match_expr) => &match_expr.span,
+            Expr::NoneExpr(ref none_expr) => &none_expr.span,
+            Expr::PrintExpr(ref print_expr) => &print_expr.span,
+            Expr::RecordExpr(ref record_expr) => &record_expr.span,
+            Expr::StringExpr(ref str_expr) => &str_expr.span,
+            Expr::StructExpr(ref struct_expr) => &struct_expr.span,
+            Expr::TernaryExpr(ref ternary_expr) => &ternary_expr.span,
+            Expr::TupleExpr(ref tuple_expr) => &tuple_expr.span,
+            Expr::UnaryExpr(ref un_expr) => &un_expr.span,
+            Expr::VnameExpr(ref vname_expr) => &vname_expr.span,
+            Expr::WhileExpr(ref while_expr) => &while_expr.span,
+        }
+    }
+
+    /// The type of the expression, as inferred by the checker.
+    pub fn typ(&self) -> Option<Type> {
+        match *self {
+            Expr::AssignExpr(ref ass_expr) => ass_expr.typ.clone(),
+            Expr::BinaryExpr(ref bin_expr) => bin_expr.typ.clone(),
+            Expr::BlockExpr(ref block_expr) => block_expr.typ.clone(),
+            Expr::BoolExpr(_) => Some(Type::BoolType),
+            Expr::BreakExpr(_) => Some(Type::UnitType),
+            Expr::CallExpr(ref call_expr) => call_expr.typ.clone(),
+            Expr::CastExpr(ref cast_expr) => cast_expr.typ.clone(),
+            Expr::ContinueExpr(_) => Some(Type::UnitType),
+            Expr::EnumExpr(_) => Some(Type::UnitType),
+            Expr::FieldExpr(ref field_expr) => field_expr.typ.clone(),
+            Expr::FloatExpr(_) => Some(Type::FloatType),
+            Expr::FnExpr(_) => Some(Type::UnitType),
+            Expr::ForExpr(_) => Some(Type::UnitType),
+            Expr::IfExpr(ref if_expr) => if_expr.typ.clone(),
+            Expr::IndexExpr(ref index_expr) => index_expr.typ.clone(),
+            Expr::IntegerExpr(_) => Some(Type::IntType),
+            Expr::LambdaExpr(ref lambda_expr) => lambda_expr.typ.clone(),
+            Expr::LetExpr(ref let_expr) => let_expr.typ.clone(),
+            Expr::ListExpr(ref list_expr) => list_expr.typ.clone(),
+            Expr::MatchExpr(ref match_expr) => match_expr.typ.clone(),
+            Expr::NoneExpr(ref none_expr) => none_expr.typ.clone(),
+            Expr::PrintExpr(_) => Some(Type::UnitType),
+            Expr::RecordExpr(ref record_expr) => record_expr.typ.clone(),
+            Expr::StringExpr(_) => Some(Type::StringType),
+            Expr::StructExpr(_) => Some(Type::UnitType),
+            Expr::TernaryExpr(ref ternary_expr) => ternary_expr.typ.clone(),
+            Expr::TupleExpr(ref tuple_expr) => tuple_expr.typ.clone(),
+            Expr::UnaryExpr(ref un_expr) => un_expr.typ.clone(),
+            Expr::VnameExpr(ref vname_expr) => vname_expr.typ.clone(),
+            Expr::WhileExpr(_) => Some(Type::UnitType),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoolExpr {
+    pub value: bool,
+    pub span: Span,
+}
+
+impl BoolExpr {
+    pub fn new(value: bool, span: Span) -> Self {
+        BoolExpr { value, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegerExpr {
+    pub value: i32,
+    pub span: Span,
+}
+
+impl IntegerExpr {
+    pub fn new(value: i32, span: Span) -> Self {
+        IntegerExpr { value, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatExpr {
+    pub value: f64,
+    pub span: Span,
+}
+
+impl FloatExpr {
+    pub fn new(value: f64, span: Span) -> Self {
+        FloatExpr { value, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StringExpr {
+    pub value: String,
+    pub span: Span,
+}
+
+impl StringExpr {
+    pub fn new(value: String, span: Span) -> Self {
+        StringExpr { value, span }
+    }
+}
+
+/// An explicit conversion, `expr as target`.
+#[derive(Debug, Clone)]
+pub struct CastExpr {
+    pub expr: Box<Expr>,
+    pub target: Type,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl CastExpr {
+    pub fn new(expr: Box<Expr>, target: Type, target_span: Span) -> Self {
+        let span = expr.span().to(&target_span);
+        CastExpr {
+            expr,
+            target,
+            typ: None,
+            span,
+        }
+    }
+}
+
+/// A call of a function, `callee(args)`.
+#[derive(Debug, Clone)]
+pub struct CallExpr {
+    pub callee: Box<Expr>,
+    pub args: Vec<Expr>,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl CallExpr {
+    pub fn new(callee: Box<Expr>, args: Vec<Expr>, rparen_span: Span) -> Self {
+        let span = callee.span().to(&rparen_span);
+        CallExpr {
+            callee,
+            args,
+            typ: None,
+            span,
+        }
+    }
+}
+
+/// A sequence of expressions in a scope of its own, `{ e1 e2 ... en }`, whose
+/// value is the value of the last expression (or `()` if there are none).
+#[derive(Debug, Clone)]
+pub struct BlockExpr {
+    pub exprs: Vec<Expr>,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl BlockExpr {
+    pub fn new(exprs: Vec<Expr>, span: Span) -> Self {
+        BlockExpr {
+            exprs,
+            typ: None,
+            span,
+        }
+    }
+}
+
+/// A conditional, `if cond { ... } else { ... }`, where the else branch is
+/// optional and is either a block or another if expression.
+#[derive(Debug, Clone)]
+pub struct IfExpr {
+    pub cond: Box<Expr>,
+    pub then_branch: Box<Expr>,         // BlockExpr
+    pub else_branch: Option<Box<Expr>>, // BlockExpr or IfExpr
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl IfExpr {
+    pub fn new(
+        cond: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Option<Box<Expr>>,
+        if_span: Span,
+    ) -> Self {
+        let span = if_span.to(else_branch.as_ref().unwrap_or(&then_branch).span());
+        IfExpr {
+            cond,
+            then_branch,
+            else_branch,
+            typ: None,
+            span,
+        }
+    }
+}
+
+/// The empty option, `none`, whose type can only be inferred from where it is
+/// used.
+#[derive(Debug, Clone)]
+pub struct NoneExpr {
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl NoneExpr {
+    pub fn new(span: Span) -> Self {
+        NoneExpr { typ: None, span }
+    }
+}
+
+/// A list literal, `[e1, e2, ..., en]`.
+#[derive(Debug, Clone)]
+pub struct ListExpr {
+    pub elems: Vec<Expr>,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl ListExpr {
+    pub fn new(elems: Vec<Expr>, lbracket_span: Span, rbracket_span: Span) -> Self {
+        ListExpr {
+            elems,
+            typ: None,
+            span: lbracket_span.to(&rbracket_span),
+        }
+    }
+}
+
+/// An element of a list, `list[index]`, which can also be assigned to.
+#[derive(Debug, Clone)]
+pub struct IndexExpr {
+    pub list: Box<Expr>,
+    pub index: Box<Expr>,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl IndexExpr {
+    pub fn new(list: Box<Expr>, index: Box<Expr>, rbracket_span: Span) -> Self {
+        let span = list.span().to(&rbracket_span);
+        IndexExpr {
+            list,
+            index,
+            typ: None,
+            span,
+        }
+    }
+}
+
+/// A tuple literal, `(e1, e2, ..., en)`, which has at least two elements.
+/// A tuple of variables (or of other targets) can also be assigned to, which
+/// assigns each element of the value to the corresponding target.
+#[derive(Debug, Clone)]
+pub struct TupleExpr {
+    pub elems: Vec<Expr>,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl TupleExpr {
+    pub fn new(elems: Vec<Expr>, lparen_span: Span, rparen_span: Span) -> Self {
+        TupleExpr {
+            elems,
+            typ: None,
+            span: lparen_span.to(&rparen_span),
+        }
+    }
+}
+
+/// A field of a value, `expr.field`, e.g., `pair.0` for the first element
+/// of a tuple, or `order.amount` for a field of a record. The fields of
+/// records (but not of tuples) can also be assigned to.
+#[derive(Debug, Clone)]
+pub struct FieldExpr {
+    pub expr: Box<Expr>,
+    pub field: String,
+    pub field_span: Span,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl FieldExpr {
+    pub fn new(expr: Box<Expr>, field: String, field_span: Span) -> Self {
+        let span = expr.span().to(&field_span);
+        FieldExpr {
+            expr,
+            field,
+            field_span,
+            typ: None,
+            span,
+        }
+    }
+}
+
+/// A conditional expression, `cond ? then_expr : else_expr`, whose value is
+/// the value of the arm selected by the condition.
+#[derive(Debug, Clone)]
+pub struct TernaryExpr {
+    pub cond: Box<Expr>,
+    pub then_expr: Box<Expr>,
+    pub else_expr: Box<Expr>,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl TernaryExpr {
+    pub fn new(cond: Box<Expr>, then_expr: Box<Expr>, else_expr: Box<Expr>) -> Self {
+        let span = cond.span().to(else_expr.span());
+        TernaryExpr {
+            cond,
+            then_expr,
+            else_expr,
+            typ: None,
+            span,
+        }
+    }
+}
+
+/// A loop, `while cond { ... }`, that runs its body for as long as the
+/// condition holds. Loops have no value.
+#[derive(Debug, Clone)]
+pub struct WhileExpr {
+    pub cond: Box<Expr>,
+    pub body: Box<Expr>, // BlockExpr
+    pub span: Span,
+}
+
+impl WhileExpr {
+    pub fn new(cond: Box<Expr>, body: Box<Expr>, while_span: Span) -> Self {
+        let span = while_span.to(body.span());
+        WhileExpr { cond, body, span }
+    }
+}
+
+/// A counted loop, `for vname in start..end { ... }`, that runs its body with
+/// vname bound to each int from start up to (but not including) end, in a
+/// scope of its own.
+#[derive(Debug, Clone)]
+pub struct ForExpr {
+    pub vname: VnameExpr,
+    pub start: Box<Expr>,
+    pub end: Box<Expr>,
+    pub body: Box<Expr>, // BlockExpr
+    pub span: Span,
+}
+
+impl ForExpr {
+    pub fn new(
+        vname: VnameExpr,
+        start: Box<Expr>,
+        end: Box<Expr>,
+        body: Box<Expr>,
+        for_span: Span,
+    ) -> Self {
+        let span = for_span.to(body.span());
+        ForExpr {
+            vname,
+            start,
+            end,
+            body,
+            span,
+        }
+    }
+}
+
+/// A function declaration, `fn vname(params) -> ret_typ { ... }`, where the
+/// return type is `()` if it is not given. The function can be called (and
+/// can call itself) in the rest of the enclosing scope.
+#[derive(Debug, Clone)]
+pub struct FnExpr {
+    pub vname: VnameExpr,
+    pub params: Vec<Param>,
+    pub ret_typ: Type,
+    pub ret_span: Option<Span>,
+    pub body: Box<Expr>, // BlockExpr
+    /// the functions declared outside of the body that it refers to, as
+    /// found by the checker.
+    pub captures: Vec<String>,
+    pub span: Span,
+}
+
+impl FnExpr {
+    pub fn new(
+        vname: VnameExpr,
+        params: Vec<Param>,
+        ret: Option<(Type, Span)>,
+        body: Box<Expr>,
+        fn_span: Span,
+    ) -> Self {
+        let span = fn_span.to(body.span());
+        let (ret_typ, ret_span) = match ret {
+            Some((typ, span)) => (typ, Some(span)),
+            None => (Type::UnitType, None),
+        };
+
+        FnExpr {
+            vname,
+            params,
+            ret_typ,
+            ret_span,
+            body,
+            captures: Vec::new(),
+            span,
+        }
+    }
+}
+
+/// A struct declaration, `struct Name { field: typ, ... }`. The struct can be
+/// used as a type, and its records constructed, in the rest of the enclosing
+/// scope.
+#[derive(Debug, Clone)]
+pub struct StructExpr {
+    pub name: Identifier,
+    pub fields: Vec<StructField>,
+    pub span: Span,
+}
+
+impl StructExpr {
+    pub fn new(
+        name: Identifier,
+        fields: Vec<StructField>,
+        struct_span: Span,
+        rbrace_span: Span,
+    ) -> Self {
+        StructExpr {
+            name,
+            fields,
+            span: struct_span.to(&rbrace_span),
+        }
+    }
+}
+
+/// A field of a struct declaration, `name: typ`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructField {
+    pub name: Identifier,
+    pub typ: Type,
+    pub typ_span: Span,
+}
+
+impl StructField {
+    pub fn new(name: Identifier, typ: Type, typ_span: Span) -> Self {
+        StructField {
+            name,
+            typ,
+            typ_span,
+        }
+    }
+}
+
+/// A record of a struct, `Name { field: expr, ... }`, with a value for each
+/// field of the struct (in any order).
+#[derive(Debug, Clone)]
+pub struct RecordExpr {
+    pub name: Identifier,
+    pub fields: Vec<(Identifier, Expr)>,
+    /// the names of the fields in the order of the struct declaration, as
+    /// found by the checker.
+    pub decl_fields: Vec<String>,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl RecordExpr {
+    pub fn new(name: Identifier, fields: Vec<(Identifier, Expr)>, rbrace_span: Span) -> Self {
+        let span = name.span.to(&rbrace_span);
+        RecordExpr {
+            name,
+            fields,
+            decl_fields: Vec::new(),
+            typ: None,
+            span,
+        }
+    }
+}
+
+/// An enum declaration, `enum Name { Variant, Variant(typ, ...), ... }`. The
+/// enum can be used as a type, and its variants as values (or called with
+/// their payload), in the rest of the enclosing scope.
+#[derive(Debug, Clone)]
+pub struct EnumExpr {
+    pub name: Identifier,
+    pub variants: Vec<EnumVariant>,
+    pub span: Span,
+}
+
+impl EnumExpr {
+    pub fn new(
+        name: Identifier,
+        variants: Vec<EnumVariant>,
+        enum_span: Span,
+        rbrace_span: Span,
+    ) -> Self {
+        EnumExpr {
+            name,
+            variants,
+            span: enum_span.to(&rbrace_span),
+        }
+    }
+}
+
+/// A variant of an enum declaration, with the types of its payload (if any),
+/// `Name` or `Name(typ, ...)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumVariant {
+    pub name: Identifier,
+    pub payload: Vec<(Type, Span)>,
+}
+
+impl EnumVariant {
+    pub fn new(name: Identifier, payload: Vec<(Type, Span)>) -> Self {
+        EnumVariant { name, payload }
+    }
+}
+
+/// `match expr { pattern => body, ... }`, whose value is that of the body of
+/// the first arm whose pattern matches the value of the expr.
+#[derive(Debug, Clone)]
+pub struct MatchExpr {
+    pub expr: Box<Expr>,
+    pub arms: Vec<MatchArm>,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl MatchExpr {
+    pub fn new(expr: Box<Expr>, arms: Vec<MatchArm>, match_span: Span, rbrace_span: Span) -> Self {
+        MatchExpr {
+            expr,
+            arms,
+            typ: None,
+            span: match_span.to(&rbrace_span),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Expr,
+    pub span: Span,
+}
+
+impl MatchArm {
+    pub fn new(pattern: Pattern, body: Expr) -> Self {
+        let span = pattern.span().to(body.span());
+        MatchArm {
+            pattern,
+            body,
+            span,
+        }
+    }
+}
+
+/// The pattern of a match arm.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// `_`, which matches any value.
+    Wildcard(Span),
+    /// a name, which matches any value and binds the name to it in the body
+    /// of the arm. The checker turns a name that is a variant of an enum
+    /// into a variant pattern instead.
+    Binding(VnameExpr),
+    /// literals match the values equal to them (ints may be negative).
+    Bool(BoolExpr),
+    Int(IntegerExpr),
+    Str(StringExpr),
+    Variant(VariantPattern),
+}
+
+impl Pattern {
+    pub fn span(&self) -> &Span {
+        match *self {
+            Pattern::Wildcard(ref span) => span,
+            Pattern::Binding(ref vname) => &vname.span,
+            Pattern::Bool(ref bool_expr) => &bool_expr.span,
+            Pattern::Int(ref int_expr) => &int_expr.span,
+            Pattern::Str(ref str_expr) => &str_expr.span,
+            Pattern::Variant(ref variant) => &variant.span,
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Pattern::Wildcard(_) => write!(f, "_"),
+            Pattern::Binding(ref vname) => write!(f, "{}", vname.id.spelling),
+            Pattern::Bool(ref bool_expr) => write!(f, "{}", bool_expr.value),
+            Pattern::Int(ref int_expr) => write!(f, "{}", int_expr.value),
+            Pattern::Str(ref str_expr) => write!(f, "{:?}", str_expr.value),
+            Pattern::Variant(ref variant) if variant.args.is_empty() => {
+                write!(f, "{}", variant.name.spelling)
+            }
+            Pattern::Variant(ref variant) => {
+                let args = variant
+                    .args
+                    .iter()
+                    .map(|arg| arg.to_string())
+                    .collect::<Vec<_>>();
+                write!(f, "{}({})", variant.name.spelling, args.join(", "))
+            }
+        }
+    }
+}
+
+/// A variant of an enum, with a pattern for each value of its payload,
+/// `Name(pattern, ...)`, or just `Name` for a variant without a payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantPattern {
+    pub name: Identifier,
+    pub args: Vec<Pattern>,
+    pub span: Span,
+}
+
+impl VariantPattern {
+    pub fn new(name: Identifier, args: Vec<Pattern>, span: Span) -> Self {
+        VariantPattern { name, args, span }
+    }
+}
+
+/// A parameter of a function, `vname: typ`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub vname: VnameExpr,
+    pub typ: Type,
+    pub typ_span: Span,
+}
+
+impl Param {
+    pub fn new(vname: VnameExpr, typ: Type, typ_span: Span) -> Self {
+        Param {
+            vname,
+            typ,
+            typ_span,
+        }
+    }
+}
+
+/// An anonymous function, `|params| body`, whose value is a closure. The
+/// types of the parameters may be left out when they can be inferred from
+/// where the closure is used.
+#[derive(Debug, Clone)]
+pub struct LambdaExpr {
+    pub params: Vec<LambdaParam>,
+    pub body: Box<Expr>,
+    /// the variables and functions declared outside of the body that it
+    /// refers to, as found by the checker.
+    pub captures: Vec<String>,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl LambdaExpr {
+    pub fn new(params: Vec<LambdaParam>, body: Box<Expr>, lbar_span: Span) -> Self {
+        let span = lbar_span.to(body.span());
+        LambdaExpr {
+            params,
+            body,
+            captures: Vec::new(),
+            typ: None,
+            span,
+        }
+    }
+}
+
+/// A parameter of an anonymous function, `vname` or `vname: typ`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LambdaParam {
+    pub vname: VnameExpr,
+    pub annotation: Option<(Type, Span)>,
+}
+
+impl LambdaParam {
+    pub fn new(vname: VnameExpr, annotation: Option<(Type, Span)>) -> Self {
+        LambdaParam { vname, annotation }
+    }
+}
+
+/// Leaves the innermost enclosing loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreakExpr {
+    pub span: Span,
+}
+
+impl BreakExpr {
+    pub fn new(span: Span) -> Self {
+        BreakExpr { span }
+    }
+}
+
+/// Skips to the next iteration of the innermost enclosing loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContinueExpr {
+    pub span: Span,
+}
+
+impl ContinueExpr {
+    pub fn new(span: Span) -> Self {
+        ContinueExpr { span }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PrintExpr {
+    pub expr: Box<Expr>,
+    pub span: Span,
+}
+
+impl PrintExpr {
+    pub fn new(expr: Box<Expr>, print_span: Span) -> Self {
+        let span = print_span.to(expr.span());
+        PrintExpr { expr, span }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct VnameExpr {
     pub id: Identifier,
     pub typ: Option<Type>,
+    pub span: Span,
 }
 
 impl VnameExpr {
-    pub fn new(id: Identifier) -> Self {
-        VnameExpr { id, typ: None }
+    pub fn new(id: Identifier, span: Span) -> Self {
+        VnameExpr {
+            id,
+            typ: None,
+            span,
+        }
     }
 }
 
-impl VnameExpr {}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AssignExpr {
-    pub vname: Box<Expr>, // VnameExpr
+    pub target: Box<Expr>, // VnameExpr or IndexExpr
     pub op: BinaryOperator,
+    pub op_span: Span,
     pub expr: Box<Expr>,
     pub typ: Option<Type>,
+    pub span: Span,
 }
 
 impl AssignExpr {
-    pub fn new(vname: Box<Expr>, op: BinaryOperator, expr: Box<Expr>) -> Self {
+    pub fn new(target: Box<Expr>, op: BinaryOperator, op_span: Span, expr: Box<Expr>) -> Self {
+        let span = target.span().to(expr.span());
         AssignExpr {
-            vname,
+            target,
             op,
+            op_span,
             expr,
             typ: None,
+            span,
         }
     }
 }
 
-#[derive(Debug)]
+/// A variable declaration, `let vname: annotation = expr`, where the type
+/// annotation is optional.
+#[derive(Debug, Clone)]
+pub struct LetExpr {
+    pub vname: VnameExpr,
+    pub annotation: Option<(Type, Span)>,
+    pub expr: Box<Expr>,
+    pub typ: Option<Type>,
+    pub span: Span,
+}
+
+impl LetExpr {
+    pub fn new(
+        vname: VnameExpr,
+        annotation: Option<(Type, Span)>,
+        expr: Box<Expr>,
+        let_span: Span,
+    ) -> Self {
+        let span = let_span.to(expr.span());
+        LetExpr {
+            vname,
+            annotation,
+            expr,
+            typ: None,
+            span,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct UnaryExpr {
     pub op: UnaryOperator,
     pub elem: Box<Expr>,
     pub typ: Option<Type>,
+    pub span: Span,
 }
 
 impl UnaryExpr {
-    pub fn new(op: UnaryOperator, elem: Box<Expr>) -> Self {
+    pub fn new(op: UnaryOperator, op_span: Span, elem: Box<Expr>) -> Self {
+        let span = op_span.to(elem.span());
         UnaryExpr {
             op,
             elem,
             typ: None,
+            span,
+        }
+    }
+
+    /// A unary expr whose operator follows its operand, e.g., `a++`.
+    pub fn postfix(op: UnaryOperator, elem: Box<Expr>, op_span: Span) -> Self {
+        let span = elem.span().to(&op_span);
+        UnaryExpr {
+            op,
+            elem,
+            typ: None,
+            span,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BinaryExpr {
     pub lhs: Box<Expr>,
     pub op: BinaryOperator,
+    pub op_span: Span,
     pub rhs: Box<Expr>,
     pub typ: Option<Type>,
+    pub span: Span,
 }
 
 impl BinaryExpr {
-    pub fn new(lhs: Box<Expr>, op: BinaryOperator, rhs: Box<Expr>) -> Self {
+    pub fn new(lhs: Box<Expr>, op: BinaryOperator, op_span: Span, rhs: Box<Expr>) -> Self {
+        let span = lhs.span().to(rhs.span());
         BinaryExpr {
             lhs,
             op,
+            op_span,
             rhs,
             typ: None,
+            span,
         }
     }
 }
@@ -93,26 +908,46 @@ impl BinaryExpr {
 pub struct Identifier {
     pub spelling: String,
     pub typ: Option<Type>,
+    pub span: Span,
 }
 
 impl Identifier {
-    pub fn new(spelling: String) -> Self {
+    pub fn new(spelling: String, span: Span) -> Self {
         Identifier {
             spelling,
             typ: None,
+            span,
         }
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum UnaryOperator {
     BitwiseNot,
     LogicalNot,
+    PostfixDecrement,
+    PostfixIncrement,
+    PrefixDecrement,
+    PrefixIncrement,
     UnaryMinus,
     UnaryPlus,
 }
 
-#[derive(Debug, Copy, Clone)]
+impl UnaryOperator {
+    /// Whether the operator assigns to its operand, i.e., it is one of the
+    /// forms of ++ and --.
+    pub fn is_assignment(&self) -> bool {
+        matches!(
+            *self,
+            UnaryOperator::PostfixDecrement
+                | UnaryOperator::PostfixIncrement
+                | UnaryOperator::PrefixDecrement
+                | UnaryOperator::PrefixIncrement
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum BinaryOperator {
     Add,
     AddAssign,
@@ -123,6 +958,9 @@ pub enum BinaryOperator {
     BitwiseOrAssign,
     BitwiseXor,
     BitwiseXorAssign,
+    /// `opt ?? default`, the value of the option, or the default if it is
+    /// none.
+    Coalesce,
     Div,
     DivAssign,
     Equal,
@@ -147,19 +985,130 @@ pub enum BinaryOperator {
     SubAssign,
 }
 
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                UnaryOperator::BitwiseNot => "~",
+                UnaryOperator::LogicalNot => "!",
+                UnaryOperator::PostfixDecrement | UnaryOperator::PrefixDecrement => "--",
+                UnaryOperator::PostfixIncrement | UnaryOperator::PrefixIncrement => "++",
+                UnaryOperator::UnaryMinus => "-",
+                UnaryOperator::UnaryPlus => "+",
+            }
+        )
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                BinaryOperator::Add => "+",
+                BinaryOperator::AddAssign => "+=",
+                BinaryOperator::Assign => "=",
+                BinaryOperator::BitwiseAnd => "&",
+                BinaryOperator::BitwiseAndAssign => "&=",
+                BinaryOperator::BitwiseOr => "|",
+                BinaryOperator::BitwiseOrAssign => "|=",
+                BinaryOperator::BitwiseXor => "^",
+                BinaryOperator::BitwiseXorAssign => "^=",
+                BinaryOperator::Coalesce => "??",
+                BinaryOperator::Div => "/",
+                BinaryOperator::DivAssign => "/=",
+                BinaryOperator::Equal => "==",
+                BinaryOperator::GreaterThan => ">",
+                BinaryOperator::GreaterThanOrEqual => ">=",
+                BinaryOperator::LeftShift => "<<",
+                BinaryOperator::LeftShiftAssign => "<<=",
+                BinaryOperator::LessThan => "<",
+                BinaryOperator::LessThanOrEqual => "<=",
+                BinaryOperator::LogicalAnd => "&&",
+                BinaryOperator::LogicalAndAssign => "&&=",
+                BinaryOperator::LogicalOr => "||",
+                BinaryOperator::LogicalOrAssign => "||=",
+                BinaryOperator::Mod => "%",
+                BinaryOperator::ModAssign => "%=",
+                BinaryOperator::Mul => "*",
+                BinaryOperator::MulAssign => "*=",
+                BinaryOperator::NotEqual => "!=",
+                BinaryOperator::RightShift => ">>",
+                BinaryOperator::RightShiftAssign => ">>=",
+                BinaryOperator::Sub => "-",
+                BinaryOperator::SubAssign => "-=",
+            }
+        )
+    }
+}
+
 // for the std env
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     AnyType,
     BoolType,
+    FloatType,
     IntType,
+    StringType,
+    UnitType,
+    /// the type of functions and closures, `fn(param_typs) -> ret_typ`.
+    FnType(Vec<Type>, Box<Type>),
+    /// the type of lists of elements of the given type, `List<elem_typ>`.
+    ListType(Box<Type>),
+    /// the type of values that may be missing, `Option<elem_typ>`, which are
+    /// either `some(value)` or `none`.
+    OptionType(Box<Type>),
+    /// the type of tuples with elements of the given types, `(int, bool)`.
+    TupleType(Vec<Type>),
+    /// the type of the records of the struct, or of the values of the enum,
+    /// with the given name. The checker fills in the id of the declaration
+    /// that the name refers to, so that a struct or an enum that shadows
+    /// another one of the same name is a different type.
+    NamedType(String, Option<usize>),
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Type::AnyType => write!(f, "any"),
+            Type::BoolType => write!(f, "bool"),
+            Type::FloatType => write!(f, "float"),
+            Type::IntType => write!(f, "int"),
+            Type::StringType => write!(f, "string"),
+            Type::UnitType => write!(f, "()"),
+            Type::FnType(ref param_typs, ref ret_typ) => {
+                let param_typs = param_typs
+                    .iter()
+                    .map(|typ| typ.to_string())
+                    .collect::<Vec<_>>();
+                write!(f, "fn({}) -> {}", param_typs.join(", "), ret_typ)
+            }
+            Type::ListType(ref elem_typ) => write!(f, "List<{}>", elem_typ),
+            Type::OptionType(ref elem_typ) => write!(f, "Option<{}>", elem_typ),
+            Type::TupleType(ref elem_typs) => {
+                let elem_typs = elem_typs
+                    .iter()
+                    .map(|typ| typ.to_string())
+                    .collect::<Vec<_>>();
+                write!(f, "({})", elem_typs.join(", "))
+            }
+            Type::NamedType(ref name, _) => write!(f, "{}", name),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Decl {
     ConstDecl(ConstDecl),
     OperatorDecl(OperatorDecl),
+    FunctionDecl(FunctionDecl),
+    StructDecl(StructDecl),
+    EnumDecl(EnumDecl),
+    VariantDecl(VariantDecl),
     IdDecl(),
 }
 
@@ -203,3 +1152,82 @@ impl BinaryOperatorDecl {
         }
     }
 }
+
+/// A builtin function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDecl {
+    pub param_typs: Vec<Type>,
+    pub ret_typ: Type,
+}
+
+impl FunctionDecl {
+    pub fn new(param_typs: Vec<Type>, ret_typ: Type) -> Self {
+        FunctionDecl {
+            param_typs,
+            ret_typ,
+        }
+    }
+}
+
+/// A struct declared in the program, with the id of the declaration (see
+/// `Type::NamedType`) and the names and the types of its fields in the order
+/// of the declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDecl {
+    pub id: usize,
+    pub fields: Vec<(String, Type)>,
+}
+
+impl StructDecl {
+    pub fn new(id: usize, fields: Vec<(String, Type)>) -> Self {
+        StructDecl { id, fields }
+    }
+
+    /// The type of the field `name`, if the struct has one.
+    pub fn field_typ(&self, name: &str) -> Option<&Type> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, typ)| typ)
+    }
+}
+
+/// An enum declared in the program, with the id of the declaration (see
+/// `Type::NamedType`) and the names and the payload types of its variants in
+/// the order of the declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDecl {
+    pub id: usize,
+    pub variants: Vec<(String, Vec<Type>)>,
+}
+
+impl EnumDecl {
+    pub fn new(id: usize, variants: Vec<(String, Vec<Type>)>) -> Self {
+        EnumDecl { id, variants }
+    }
+
+    /// The payload types of the variant `name`, if the enum has one.
+    pub fn payload_typs(&self, name: &str) -> Option<&Vec<Type>> {
+        self.variants
+            .iter()
+            .find(|(variant, _)| variant == name)
+            .map(|(_, typs)| typs)
+    }
+}
+
+/// A variant of an enum, which is declared under its own name so that it can
+/// be used as a value, or called with its payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantDecl {
+    pub enum_typ: Type,
+    pub payload_typs: Vec<Type>,
+}
+
+impl VariantDecl {
+    pub fn new(enum_typ: Type, payload_typs: Vec<Type>) -> Self {
+        VariantDecl {
+            enum_typ,
+            payload_typs,
+        }
+    }
+}
@@ -1,20 +1,32 @@
 use super::ast::*;
+use super::source_file::Span;
 use super::token::{Token, TokenKind};
-use crate::error::{report_error, ExprError, ExprErrorKind};
+use crate::diagnostics::codes::ErrorCode;
+use crate::diagnostics::Diagnostics;
+use crate::error::{ExprError, ExprResult};
 
 pub struct Parser {
     tokens: Vec<Token>,
     curr_idx: usize,
+    /// the number of expressions, types and patterns that the current one
+    /// is nested in.
+    depth: usize,
 }
 
 impl Parser {
     const MIN_BINDING_POWER: i32 = -1;
     const MAX_BINDING_POWER: i32 = 120;
 
+    /// The deepest nesting that is accepted. The parser, and every later
+    /// phase, walks the ast recursively, so deeper nesting could exhaust the
+    /// stack (of the main thread, in a debug build).
+    pub const MAX_NESTING_DEPTH: usize = 256;
+
     pub fn new(tokens: Vec<Token>) -> Self {
         Parser {
             tokens,
             curr_idx: 0,
+            depth: 0,
         }
     }
 
@@ -22,8 +34,46 @@ impl Parser {
         &self.tokens[self.curr_idx]
     }
 
+    fn prev_token(&self) -> &Token {
+        &self.tokens[self.curr_idx.saturating_sub(1)]
+    }
+
+    /// The token `offset` tokens after the current one, or the last token
+    /// (the end of the input) if there are not as many.
+    fn peek_token(&self, offset: usize) -> &Token {
+        let idx = (self.curr_idx + offset).min(self.tokens.len() - 1);
+        &self.tokens[idx]
+    }
+
     fn advance(&mut self) {
-        self.curr_idx += 1;
+        if self.curr_idx + 1 < self.tokens.len() {
+            self.curr_idx += 1;
+        }
+    }
+
+    /// Start parsing an expression, a type or a pattern at `token`, one level
+    /// deeper. Every successful `enter` is followed by a `leave` (or by
+    /// restoring the depth, see `parse_expression`), unless an error abandons
+    /// the whole top level expression.
+    fn enter(&mut self, token: &Token) -> ExprResult<()> {
+        if self.depth > Parser::MAX_NESTING_DEPTH {
+            return Err(ExprError::with_span(
+                ErrorCode::NestingTooDeep,
+                format!(
+                    "expression nested more than {} levels deep",
+                    Parser::MAX_NESTING_DEPTH
+                ),
+                token.span.clone(),
+            )
+            .with_help("move the inner parts into variables or functions".to_owned()));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn leave(&mut self) {
+        self.depth -= 1;
     }
 
     fn lbp(kind: TokenKind) -> i32 {
@@ -38,6 +88,8 @@ impl Parser {
             | TokenKind::RightShiftAssign
             | TokenKind::BitwiseOrAssign
             | TokenKind::BitwiseXorAssign => 10,
+            TokenKind::Question => 15,
+            TokenKind::QuestionQuestion => 17,
             TokenKind::LogicalOr => 20,
             TokenKind::LogicalAnd => 30,
             TokenKind::BitwiseOr => 40,
@@ -50,31 +102,123 @@ impl Parser {
             TokenKind::LeftShift | TokenKind::RightShift => 90,
             TokenKind::Plus | TokenKind::Minus => 100,
             TokenKind::Star | TokenKind::Slash | TokenKind::Mod => 110,
+            TokenKind::As => 115,
             TokenKind::LogicalNot | TokenKind::BitwiseNot => 120,
+            TokenKind::LeftParen
+            | TokenKind::LeftBracket
+            | TokenKind::Dot
+            | TokenKind::Increment
+            | TokenKind::Decrement => 130,
             _ => Parser::MIN_BINDING_POWER,
         }
     }
 
+    /// Check if the given token can begin an expression.
+    fn starts_expression(kind: TokenKind) -> bool {
+        matches!(
+            kind,
+            TokenKind::LeftParen
+                | TokenKind::LeftBrace
+                | TokenKind::LeftBracket
+                | TokenKind::If
+                | TokenKind::While
+                | TokenKind::For
+                | TokenKind::Break
+                | TokenKind::Continue
+                | TokenKind::Fn
+                | TokenKind::Struct
+                | TokenKind::Enum
+                | TokenKind::Match
+                | TokenKind::BitwiseOr
+                | TokenKind::LogicalOr
+                | TokenKind::Plus
+                | TokenKind::Minus
+                | TokenKind::Increment
+                | TokenKind::Decrement
+                | TokenKind::Print
+                | TokenKind::Let
+                | TokenKind::Integer
+                | TokenKind::Float
+                | TokenKind::String
+                | TokenKind::False
+                | TokenKind::True
+                | TokenKind::None
+                | TokenKind::Identifier
+        )
+    }
+
     /// Check if the given operator is right-associative or not.
     fn is_right_associative(kind: TokenKind) -> bool {
-        match kind {
+        matches!(
+            kind,
             TokenKind::LogicalNot
-            | TokenKind::BitwiseNot
-            | TokenKind::Assign
-            | TokenKind::PlusAssign
-            | TokenKind::MinusAssign
-            | TokenKind::StarAssign
-            | TokenKind::SlashAssign
-            | TokenKind::ModAssign
-            | TokenKind::BitwiseOrAssign
-            | TokenKind::BitwiseXorAssign => true,
-            _ => false,
+                | TokenKind::BitwiseNot
+                | TokenKind::Assign
+                | TokenKind::Question
+                | TokenKind::QuestionQuestion
+                | TokenKind::PlusAssign
+                | TokenKind::MinusAssign
+                | TokenKind::StarAssign
+                | TokenKind::SlashAssign
+                | TokenKind::ModAssign
+                | TokenKind::BitwiseOrAssign
+                | TokenKind::BitwiseXorAssign
+        )
+    }
+
+    /// The left denotations - handles the operators after an expression.
+    fn led(&mut self, left: Expr, token: Token) -> ExprResult<Expr> {
+        match token.kind {
+            TokenKind::LeftParen => self.parse_call(left, token),
+
+            TokenKind::LeftBracket => self.parse_index(left, token),
+
+            TokenKind::Dot => self.parse_field(left),
+
+            // postfix operators have no rhs.
+            TokenKind::Increment => Ok(Expr::UnaryExpr(UnaryExpr::postfix(
+                UnaryOperator::PostfixIncrement,
+                Box::new(left),
+                token.span,
+            ))),
+
+            TokenKind::Decrement => Ok(Expr::UnaryExpr(UnaryExpr::postfix(
+                UnaryOperator::PostfixDecrement,
+                Box::new(left),
+                token.span,
+            ))),
+
+            // the ternary operator has a middle operand, which extends up to
+            // the colon.
+            TokenKind::Question => self.parse_ternary(left, token),
+
+            TokenKind::As => self.parse_cast(left),
+
+            _ => self.parse_binary(left, token),
         }
     }
 
-    /// The left denotations - handles binary expressions.
-    fn led(&mut self, lhs: Expr, kind: TokenKind, rhs: Expr) -> Expr {
-        let op = match kind {
+    /// CastExpr ::= Expr "as" Type
+    fn parse_cast(&mut self, expr: Expr) -> ExprResult<Expr> {
+        // the rhs of a cast is a type rather than an expression.
+        let (target, target_span) = self.parse_type()?;
+        Ok(Expr::CastExpr(Box::new(CastExpr::new(
+            Box::new(expr),
+            target,
+            target_span,
+        ))))
+    }
+
+    /// BinaryExpr ::= Expr BinaryOperator Expr
+    /// AssignExpr ::= Expr AssignOperator Expr
+    fn parse_binary(&mut self, lhs: Expr, token: Token) -> ExprResult<Expr> {
+        let rhs = if Parser::is_right_associative(token.kind) {
+            self.parse_expression(Parser::lbp(token.kind) - 1)?
+        } else {
+            self.parse_expression(Parser::lbp(token.kind))?
+        };
+
+        let op = match token.kind {
             TokenKind::Assign => BinaryOperator::Assign,
             TokenKind::BitwiseAnd => BinaryOperator::BitwiseAnd,
             TokenKind::BitwiseAndAssign => BinaryOperator::BitwiseAndAssign,
@@ -97,6 +241,7 @@ impl Parser {
             TokenKind::ModAssign => BinaryOperator::ModAssign,
             TokenKind::NotEqual => BinaryOperator::NotEqual,
             TokenKind::Plus => BinaryOperator::Add,
+            TokenKind::QuestionQuestion => BinaryOperator::Coalesce,
             TokenKind::PlusAssign => BinaryOperator::AddAssign,
             TokenKind::RightShift => BinaryOperator::RightShift,
             TokenKind::RightShiftAssign => BinaryOperator::RightShiftAssign,
@@ -104,10 +249,16 @@ impl Parser {
             TokenKind::SlashAssign => BinaryOperator::DivAssign,
             TokenKind::Star => BinaryOperator::Mul,
             TokenKind::StarAssign => BinaryOperator::MulAssign,
-            _ => unreachable!(),
+            _ => {
+                return Err(ExprError::with_span(
+                    ErrorCode::NotABinaryOperator,
+                    format!("{:?} is not a binary operator", token.spelling),
+                    token.span,
+                ))
+            }
         };
 
-        match kind {
+        Ok(match token.kind {
             TokenKind::Assign
             | TokenKind::PlusAssign
             | TokenKind::MinusAssign
@@ -117,92 +268,1015 @@ impl Parser {
             | TokenKind::LeftShiftAssign
             | TokenKind::RightShiftAssign
             | TokenKind::BitwiseOrAssign
-            | TokenKind::BitwiseXorAssign => {
-                Expr::AssignExpr(AssignExpr::new(Box::new(lhs), op, Box::new(rhs)))
+            | TokenKind::BitwiseXorAssign => Expr::AssignExpr(Box::new(AssignExpr::new(
+                Box::new(lhs),
+                op,
+                token.span,
+                Box::new(rhs),
+            ))),
+            _ => Expr::BinaryExpr(Box::new(BinaryExpr::new(
+                Box::new(lhs),
+                op,
+                token.span,
+                Box::new(rhs),
+            ))),
+        })
+    }
+
+    /// The null denotation - handles unary expressions. Each kind of
+    /// expression is parsed by a function of its own, which keeps the stack
+    /// frame of this function (that is part of every level of nesting) small.
+    fn nud(&mut self, token: Token) -> ExprResult<Expr> {
+        match token.kind {
+            TokenKind::LeftParen => self.parse_paren(token),
+
+            TokenKind::Plus => self.parse_prefix(UnaryOperator::UnaryPlus, token),
+
+            TokenKind::Minus => self.parse_prefix(UnaryOperator::UnaryMinus, token),
+
+            TokenKind::Increment => self.parse_prefix(UnaryOperator::PrefixIncrement, token),
+
+            TokenKind::Decrement => self.parse_prefix(UnaryOperator::PrefixDecrement, token),
+
+            TokenKind::Let => self.parse_let(token),
+
+            TokenKind::LeftBrace => self.parse_block(token),
+
+            TokenKind::LeftBracket => self.parse_list(token),
+
+            TokenKind::If => self.parse_if(token),
+
+            TokenKind::While => self.parse_while(token),
+
+            TokenKind::For => self.parse_for(token),
+
+            TokenKind::Fn => self.parse_fn(token),
+
+            TokenKind::Struct => self.parse_struct(token),
+
+            TokenKind::Enum => self.parse_enum(token),
+
+            TokenKind::Match => self.parse_match(token),
+
+            TokenKind::BitwiseOr | TokenKind::LogicalOr => self.parse_lambda(token),
+
+            TokenKind::Print => self.parse_print(token),
+
+            // a name followed by { and a field name and a : on the same line
+            // starts a record, rather than being followed by a block.
+            TokenKind::Identifier
+                if self.curr_token().kind == TokenKind::LeftBrace
+                    && self.curr_token().span.start.line == token.span.end.line
+                    && self.peek_token(1).kind == TokenKind::Identifier
+                    && self.peek_token(2).kind == TokenKind::Colon =>
+            {
+                self.parse_record(token)
             }
-            _ => Expr::BinaryExpr(BinaryExpr::new(Box::new(lhs), op, Box::new(rhs))),
+
+            _ => Parser::parse_atom(token),
         }
     }
 
-    /// The null denotation - handles unary expressions.
-    fn nud(&mut self, token: Token) -> Expr {
-        match token.kind {
-            TokenKind::LeftParen => {
-                let expr = self.parse_expression(Parser::MIN_BINDING_POWER);
-                if self.curr_token().kind != TokenKind::RightParen {
-                    report_error(
-                        ExprError::new(
-                            ExprErrorKind::ParserError,
-                            format!("Missing right parenthesis while parsing expression"),
+    /// ParenExpr ::= "(" Expr ")"
+    fn parse_paren(&mut self, lparen: Token) -> ExprResult<Expr> {
+        let expr = self.parse_expression(Parser::MIN_BINDING_POWER)?;
+        // a comma after the first expr makes it the first element of a
+        // tuple, rather than a parenthesised expr.
+        if self.curr_token().kind == TokenKind::Comma {
+            return self.parse_tuple(lparen, expr);
+        }
+        if self.curr_token().kind != TokenKind::RightParen {
+            return Err(ExprError::with_span(
+                ErrorCode::MissingRightParen,
+                "Missing right parenthesis while parsing expression".to_owned(),
+                lparen.span,
+            ));
+        }
+        self.advance();
+        Ok(expr)
+    }
+
+    /// UnaryExpr ::= ("+" | "-" | "++" | "--") Expr
+    ///
+    /// The operand is parsed with the maximum binding power, so a prefix
+    /// operator binds tighter than every binary one: `-1 + 2` is `(-1) + 2`,
+    /// i.e. 1, as in most languages.
+    fn parse_prefix(&mut self, op: UnaryOperator, op_token: Token) -> ExprResult<Expr> {
+        Ok(Expr::UnaryExpr(UnaryExpr::new(
+            op,
+            op_token.span,
+            Box::new(self.parse_expression(Parser::MAX_BINDING_POWER)?),
+        )))
+    }
+
+    /// PrintExpr ::= "print" Expr
+    fn parse_print(&mut self, print_token: Token) -> ExprResult<Expr> {
+        Ok(Expr::PrintExpr(PrintExpr::new(
+            Box::new(self.parse_expression(Parser::MIN_BINDING_POWER)?),
+            print_token.span,
+        )))
+    }
+
+    /// The expressions made of a single token - literals, names, break and
+    /// continue.
+    fn parse_atom(token: Token) -> ExprResult<Expr> {
+        let expr = match token.kind {
+            TokenKind::Break => Expr::BreakExpr(BreakExpr::new(token.span)),
+
+            TokenKind::Continue => Expr::ContinueExpr(ContinueExpr::new(token.span)),
+
+            TokenKind::Integer => match token.spelling.parse::<i32>() {
+                Ok(ival) => Expr::IntegerExpr(IntegerExpr::new(ival, token.span)),
+                Err(_) => {
+                    return Err(ExprError::with_span(
+                        ErrorCode::IntegerOutOfRange,
+                        format!("integer literal {} is out of range", token.spelling),
+                        token.span,
+                    ))
+                }
+            },
+
+            TokenKind::Float => match token.spelling.parse::<f64>() {
+                Ok(fval) if fval.is_finite() => Expr::FloatExpr(FloatExpr::new(fval, token.span)),
+                _ => {
+                    return Err(ExprError::with_span(
+                        ErrorCode::FloatOutOfRange,
+                        format!("float literal {} is out of range", token.spelling),
+                        token.span,
+                    ))
+                }
+            },
+
+            TokenKind::String => Expr::StringExpr(StringExpr::new(token.spelling, token.span)),
+
+            TokenKind::False | TokenKind::True => {
+                Expr::BoolExpr(BoolExpr::new(token.kind == TokenKind::True, token.span))
+            }
+
+            TokenKind::None => Expr::NoneExpr(NoneExpr::new(token.span)),
+
+            TokenKind::Identifier => Expr::VnameExpr(Box::new(VnameExpr::new(
+                Identifier::new(token.spelling, token.span.clone()),
+                token.span,
+            ))),
+
+            TokenKind::Eof => {
+                return Err(ExprError::with_span(
+                    ErrorCode::UnexpectedEndOfInput,
+                    "unexpected end of input while parsing expression".to_owned(),
+                    token.span,
+                ))
+            }
+
+            _ => {
+                return Err(ExprError::with_span(
+                    ErrorCode::UnexpectedToken,
+                    format!(
+                        "unexpected token {:?} at start of expression",
+                        token.spelling
+                    ),
+                    token.span,
+                ))
+            }
+        };
+
+        Ok(expr)
+    }
+
+    /// The binding power of the current token as an operator. A left
+    /// parenthesis or bracket (or a ++ or a --) only continues the expression
+    /// (as a call, an index, or a postfix operator) on the same line,
+    /// otherwise it starts the next expression. Likewise, a | or a || only
+    /// continues it as a binary operator on the same line, otherwise it
+    /// starts a closure.
+    fn curr_lbp(&self) -> i32 {
+        let token = self.curr_token();
+        if matches!(
+            token.kind,
+            TokenKind::LeftParen
+                | TokenKind::LeftBracket
+                | TokenKind::Increment
+                | TokenKind::Decrement
+                | TokenKind::BitwiseOr
+                | TokenKind::LogicalOr
+        ) && token.span.start.line > self.prev_token().span.end.line
+        {
+            return Parser::MIN_BINDING_POWER;
+        }
+
+        Parser::lbp(token.kind)
+    }
+
+    fn parse_expression(&mut self, rbp: i32) -> ExprResult<Expr> {
+        let depth = self.depth;
+        let token = self.curr_token().clone();
+        self.enter(&token)?;
+        self.advance();
+        let mut left = self.nud(token)?;
+
+        // each operator wraps the expression parsed so far, so a long chain
+        // of them, e.g., 1 + 1 + ... + 1, nests as deeply as parentheses.
+        while rbp < self.curr_lbp() {
+            let token = self.curr_token().clone();
+            self.enter(&token)?;
+            self.advance();
+            left = self.led(left, token)?;
+        }
+
+        self.depth = depth;
+        Ok(left)
+    }
+
+    /// Consume the current token if it is of the given kind, and report an
+    /// error otherwise.
+    fn expect(&mut self, kind: TokenKind, what: &str) -> ExprResult<Token> {
+        let token = self.curr_token().clone();
+        if token.kind != kind {
+            return Err(ExprError::with_span(
+                ErrorCode::ExpectedToken,
+                format!("expected {}, but found {:?}", what, token.spelling),
+                token.span,
+            ));
+        }
+
+        self.advance();
+        Ok(token)
+    }
+
+    /// BlockExpr ::= "{" Expr* "}"
+    fn parse_block(&mut self, lbrace: Token) -> ExprResult<Expr> {
+        let mut exprs = Vec::new();
+
+        while !matches!(
+            self.curr_token().kind,
+            TokenKind::RightBrace | TokenKind::Eof
+        ) {
+            exprs.push(self.parse_expression(Parser::MIN_BINDING_POWER)?);
+        }
+
+        if self.curr_token().kind != TokenKind::RightBrace {
+            return Err(ExprError::with_span(
+                ErrorCode::ExpectedToken,
+                "expected } to close the block".to_owned(),
+                self.curr_token().span.clone(),
+            )
+            .with_label(lbrace.span, "the block starts here".to_owned()));
+        }
+
+        let span = lbrace.span.to(&self.curr_token().span);
+        self.advance();
+        Ok(Expr::BlockExpr(BlockExpr::new(exprs, span)))
+    }
+
+    /// IfExpr ::= "if" Expr BlockExpr ("else" (IfExpr | BlockExpr))?
+    fn parse_if(&mut self, if_token: Token) -> ExprResult<Expr> {
+        let cond = self.parse_expression(Parser::MIN_BINDING_POWER)?;
+        let lbrace = self.expect(TokenKind::LeftBrace, "{ after the if condition")?;
+        let then_branch = self.parse_block(lbrace)?;
+
+        let else_branch = if self.curr_token().kind == TokenKind::Else {
+            self.advance();
+            let token = self.curr_token().clone();
+            self.advance();
+            match token.kind {
+                TokenKind::If => Some(Box::new(self.parse_if(token)?)),
+                TokenKind::LeftBrace => Some(Box::new(self.parse_block(token)?)),
+                _ => {
+                    return Err(ExprError::with_span(
+                        ErrorCode::ExpectedToken,
+                        format!(
+                            "expected {{ or if after else, but found {:?}",
+                            token.spelling
                         ),
-                        Some(&token.loc),
-                    );
+                        token.span,
+                    ))
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(Expr::IfExpr(IfExpr::new(
+            Box::new(cond),
+            Box::new(then_branch),
+            else_branch,
+            if_token.span,
+        )))
+    }
+
+    /// WhileExpr ::= "while" Expr BlockExpr
+    fn parse_while(&mut self, while_token: Token) -> ExprResult<Expr> {
+        let cond = self.parse_expression(Parser::MIN_BINDING_POWER)?;
+        let lbrace = self.expect(TokenKind::LeftBrace, "{ after the while condition")?;
+        let body = self.parse_block(lbrace)?;
+
+        Ok(Expr::WhileExpr(WhileExpr::new(
+            Box::new(cond),
+            Box::new(body),
+            while_token.span,
+        )))
+    }
+
+    /// ForExpr ::= "for" Identifier "in" Expr ".." Expr BlockExpr
+    fn parse_for(&mut self, for_token: Token) -> ExprResult<Expr> {
+        let id_token = self.expect(TokenKind::Identifier, "a variable name after for")?;
+        let vname = VnameExpr::new(
+            Identifier::new(id_token.spelling, id_token.span.clone()),
+            id_token.span,
+        );
+
+        self.expect(TokenKind::In, "in after the loop variable")?;
+        let start = self.parse_expression(Parser::MIN_BINDING_POWER)?;
+        self.expect(TokenKind::DotDot, ".. in the range of the loop")
+            .map_err(|err| err.with_help("a range is written start..end".to_owned()))?;
+        let end = self.parse_expression(Parser::MIN_BINDING_POWER)?;
+        let lbrace = self.expect(TokenKind::LeftBrace, "{ after the range of the loop")?;
+        let body = self.parse_block(lbrace)?;
+
+        Ok(Expr::ForExpr(Box::new(ForExpr::new(
+            vname,
+            Box::new(start),
+            Box::new(end),
+            Box::new(body),
+            for_token.span,
+        ))))
+    }
+
+    /// FnExpr ::= "fn" Identifier "(" (Param ("," Param)*)? ")" ("->" Type)? BlockExpr
+    fn parse_fn(&mut self, fn_token: Token) -> ExprResult<Expr> {
+        let id_token = self.expect(TokenKind::Identifier, "a function name after fn")?;
+        let vname = VnameExpr::new(
+            Identifier::new(id_token.spelling, id_token.span.clone()),
+            id_token.span,
+        );
+
+        let lparen = self.expect(TokenKind::LeftParen, "( before the parameters")?;
+        let mut params = Vec::new();
+        if self.curr_token().kind != TokenKind::RightParen {
+            loop {
+                params.push(self.parse_param()?);
+                if self.curr_token().kind != TokenKind::Comma {
+                    break;
                 }
                 self.advance();
-                expr
             }
+        }
+        self.expect(TokenKind::RightParen, ") after the parameters")
+            .map_err(|err| err.with_label(lparen.span, "the parameters start here".to_owned()))?;
 
-            TokenKind::Plus => Expr::UnaryExpr(UnaryExpr::new(
-                UnaryOperator::UnaryPlus,
-                Box::new(self.parse_expression(Parser::MAX_BINDING_POWER)),
-            )),
+        let ret = if self.curr_token().kind == TokenKind::Arrow {
+            self.advance();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
 
-            TokenKind::Minus => Expr::UnaryExpr(UnaryExpr::new(
-                UnaryOperator::UnaryMinus,
-                Box::new(self.parse_expression(Parser::MIN_BINDING_POWER)),
-            )),
+        let lbrace = self.expect(TokenKind::LeftBrace, "{ before the function body")?;
+        let body = self.parse_block(lbrace)?;
+
+        Ok(Expr::FnExpr(Box::new(FnExpr::new(
+            vname,
+            params,
+            ret,
+            Box::new(body),
+            fn_token.span,
+        ))))
+    }
+
+    /// StructExpr ::= "struct" Identifier "{" StructField ("," StructField)* ","? "}"
+    fn parse_struct(&mut self, struct_token: Token) -> ExprResult<Expr> {
+        let id_token = self.expect(TokenKind::Identifier, "a struct name after struct")?;
+        let lbrace = self.expect(TokenKind::LeftBrace, "{ before the fields of the struct")?;
+
+        let mut fields = Vec::new();
+        loop {
+            fields.push(self.parse_struct_field()?);
+            if self.curr_token().kind != TokenKind::Comma {
+                break;
+            }
+            self.advance();
+            if self.curr_token().kind == TokenKind::RightBrace {
+                break;
+            }
+        }
+
+        let rbrace = self
+            .expect(TokenKind::RightBrace, "} after the fields of the struct")
+            .map_err(|err| err.with_label(lbrace.span, "the fields start here".to_owned()))?;
+
+        Ok(Expr::StructExpr(Box::new(StructExpr::new(
+            Identifier::new(id_token.spelling, id_token.span),
+            fields,
+            struct_token.span,
+            rbrace.span,
+        ))))
+    }
 
-            TokenKind::Print => {
-                Expr::PrintExpr(Box::new(self.parse_expression(Parser::MIN_BINDING_POWER)))
+    /// StructField ::= Identifier ":" Type
+    fn parse_struct_field(&mut self) -> ExprResult<StructField> {
+        let id_token = self.expect(TokenKind::Identifier, "a field name")?;
+        self.expect(TokenKind::Colon, ": after the field name")
+            .map_err(|err| err.with_help("fields need a type, e.g., amount: int".to_owned()))?;
+        let (typ, typ_span) = self.parse_type()?;
+
+        Ok(StructField::new(
+            Identifier::new(id_token.spelling, id_token.span),
+            typ,
+            typ_span,
+        ))
+    }
+
+    /// RecordExpr ::= Identifier "{" Identifier ":" Expr ("," Identifier ":" Expr)* ","? "}"
+    fn parse_record(&mut self, id_token: Token) -> ExprResult<Expr> {
+        let lbrace = self.curr_token().clone();
+        self.advance();
+
+        let mut fields = Vec::new();
+        loop {
+            let field_token = self.expect(TokenKind::Identifier, "a field name")?;
+            self.expect(TokenKind::Colon, ": after the field name")?;
+            let expr = self.parse_expression(Parser::MIN_BINDING_POWER)?;
+            fields.push((
+                Identifier::new(field_token.spelling, field_token.span),
+                expr,
+            ));
+
+            if self.curr_token().kind != TokenKind::Comma {
+                break;
+            }
+            self.advance();
+            if self.curr_token().kind == TokenKind::RightBrace {
+                break;
+            }
+        }
+
+        let rbrace = self
+            .expect(TokenKind::RightBrace, "} after the fields of the record")
+            .map_err(|err| err.with_label(lbrace.span, "the record starts here".to_owned()))?;
+
+        Ok(Expr::RecordExpr(Box::new(RecordExpr::new(
+            Identifier::new(id_token.spelling, id_token.span),
+            fields,
+            rbrace.span,
+        ))))
+    }
+
+    /// EnumExpr ::= "enum" Identifier "{" EnumVariant ("," EnumVariant)* ","? "}"
+    fn parse_enum(&mut self, enum_token: Token) -> ExprResult<Expr> {
+        let id_token = self.expect(TokenKind::Identifier, "an enum name after enum")?;
+        let lbrace = self.expect(TokenKind::LeftBrace, "{ before the variants of the enum")?;
+
+        let mut variants = Vec::new();
+        loop {
+            variants.push(self.parse_enum_variant()?);
+            if self.curr_token().kind != TokenKind::Comma {
+                break;
+            }
+            self.advance();
+            if self.curr_token().kind == TokenKind::RightBrace {
+                break;
+            }
+        }
+
+        let rbrace = self
+            .expect(TokenKind::RightBrace, "} after the variants of the enum")
+            .map_err(|err| err.with_label(lbrace.span, "the variants start here".to_owned()))?;
+
+        Ok(Expr::EnumExpr(Box::new(EnumExpr::new(
+            Identifier::new(id_token.spelling, id_token.span),
+            variants,
+            enum_token.span,
+            rbrace.span,
+        ))))
+    }
+
+    /// EnumVariant ::= Identifier ("(" Type ("," Type)* ")")?
+    fn parse_enum_variant(&mut self) -> ExprResult<EnumVariant> {
+        let id_token = self.expect(TokenKind::Identifier, "a variant name")?;
+
+        let mut payload = Vec::new();
+        if self.curr_token().kind == TokenKind::LeftParen {
+            let lparen = self.curr_token().clone();
+            self.advance();
+            loop {
+                payload.push(self.parse_type()?);
+                if self.curr_token().kind != TokenKind::Comma {
+                    break;
+                }
+                self.advance();
             }
+            self.expect(TokenKind::RightParen, ") after the payload types")
+                .map_err(|err| err.with_label(lparen.span, "the payload starts here".to_owned()))?;
+        }
+
+        Ok(EnumVariant::new(
+            Identifier::new(id_token.spelling, id_token.span),
+            payload,
+        ))
+    }
+
+    /// MatchExpr ::= "match" Expr "{" (MatchArm ","?)* "}"
+    ///
+    /// Like the exprs of a block, the arms need no separator, but they are
+    /// usually written one per line, or separated by commas.
+    fn parse_match(&mut self, match_token: Token) -> ExprResult<Expr> {
+        let expr = self.parse_expression(Parser::MIN_BINDING_POWER)?;
+        let lbrace = self.expect(TokenKind::LeftBrace, "{ after the matched expression")?;
+
+        let mut arms = Vec::new();
+        while !matches!(
+            self.curr_token().kind,
+            TokenKind::RightBrace | TokenKind::Eof
+        ) {
+            arms.push(self.parse_match_arm()?);
+            if self.curr_token().kind == TokenKind::Comma {
+                self.advance();
+            }
+        }
+
+        let rbrace = self
+            .expect(TokenKind::RightBrace, "} after the arms of the match")
+            .map_err(|err| err.with_label(lbrace.span, "the arms start here".to_owned()))?;
+
+        Ok(Expr::MatchExpr(MatchExpr::new(
+            Box::new(expr),
+            arms,
+            match_token.span,
+            rbrace.span,
+        )))
+    }
 
-            TokenKind::Integer => Expr::IntegerExpr(token.spelling.parse::<i32>().unwrap()),
+    /// MatchArm ::= Pattern "=>" Expr
+    fn parse_match_arm(&mut self) -> ExprResult<MatchArm> {
+        let pattern = self.parse_pattern()?;
+        self.expect(TokenKind::FatArrow, "=> after the pattern")?;
+        let body = self.parse_expression(Parser::MIN_BINDING_POWER)?;
+
+        Ok(MatchArm::new(pattern, body))
+    }
+
+    /// Pattern ::= "_" | "true" | "false" | "-"? Integer | String
+    ///           | Identifier ("(" Pattern ("," Pattern)* ")")?
+    fn parse_pattern(&mut self) -> ExprResult<Pattern> {
+        let token = self.curr_token().clone();
+        self.enter(&token)?;
+        self.advance();
+
+        let pattern = match token.kind {
+            TokenKind::Underscore => Pattern::Wildcard(token.span),
 
             TokenKind::False | TokenKind::True => {
-                Expr::BoolExpr(token.spelling.parse::<bool>().unwrap())
+                Pattern::Bool(BoolExpr::new(token.kind == TokenKind::True, token.span))
             }
 
-            TokenKind::Identifier => {
-                Expr::VnameExpr(VnameExpr::new(Identifier::new(token.spelling.clone())))
+            TokenKind::String => Pattern::Str(StringExpr::new(token.spelling, token.span)),
+
+            TokenKind::Minus | TokenKind::Integer => {
+                let int_token = if token.kind == TokenKind::Minus {
+                    self.expect(TokenKind::Integer, "an integer after - in a pattern")?
+                } else {
+                    token.clone()
+                };
+                let spelling = match token.kind {
+                    TokenKind::Minus => format!("-{}", int_token.spelling),
+                    _ => int_token.spelling,
+                };
+                let span = token.span.to(&int_token.span);
+                match spelling.parse::<i32>() {
+                    Ok(ival) => Pattern::Int(IntegerExpr::new(ival, span)),
+                    Err(_) => {
+                        return Err(ExprError::with_span(
+                            ErrorCode::IntegerOutOfRange,
+                            format!("integer literal {} is out of range", spelling),
+                            span,
+                        ))
+                    }
+                }
             }
 
+            TokenKind::Identifier if self.curr_token().kind == TokenKind::LeftParen => {
+                let lparen = self.curr_token().clone();
+                self.advance();
+
+                let mut args = Vec::new();
+                loop {
+                    args.push(self.parse_pattern()?);
+                    if self.curr_token().kind != TokenKind::Comma {
+                        break;
+                    }
+                    self.advance();
+                }
+                let rparen = self
+                    .expect(TokenKind::RightParen, ") after the payload patterns")
+                    .map_err(|err| {
+                        err.with_label(lparen.span, "the payload starts here".to_owned())
+                    })?;
+
+                Pattern::Variant(VariantPattern::new(
+                    Identifier::new(token.spelling, token.span.clone()),
+                    args,
+                    token.span.to(&rparen.span),
+                ))
+            }
+
+            TokenKind::Identifier => Pattern::Binding(VnameExpr::new(
+                Identifier::new(token.spelling, token.span.clone()),
+                token.span,
+            )),
+
             _ => {
-                eprintln!(
-                    "In unreachable inside nud, token = {:?}, curr_token = {:?}",
-                    token,
-                    self.curr_token()
-                );
-                unreachable!()
+                return Err(ExprError::with_span(
+                    ErrorCode::ExpectedToken,
+                    format!("expected a pattern, but found {:?}", token.spelling),
+                    token.span,
+                )
+                .with_help(
+                    "the patterns are _, names, bool, int and string literals, and variants"
+                        .to_owned(),
+                ))
+            }
+        };
+
+        self.leave();
+        Ok(pattern)
+    }
+
+    /// Param ::= Identifier ":" Type
+    fn parse_param(&mut self) -> ExprResult<Param> {
+        let id_token = self.expect(TokenKind::Identifier, "a parameter name")?;
+        let vname = VnameExpr::new(
+            Identifier::new(id_token.spelling, id_token.span.clone()),
+            id_token.span,
+        );
+
+        self.expect(TokenKind::Colon, ": after the parameter name")
+            .map_err(|err| err.with_help("parameters need a type, e.g., x: int".to_owned()))?;
+        let (typ, typ_span) = self.parse_type()?;
+
+        Ok(Param::new(vname, typ, typ_span))
+    }
+
+    /// LambdaExpr ::= "|" (LambdaParam ("," LambdaParam)*)? "|" Expr
+    ///              | "||" Expr
+    fn parse_lambda(&mut self, lbar: Token) -> ExprResult<Expr> {
+        let mut params = Vec::new();
+        // || is scanned as a single token, for a closure without parameters.
+        if lbar.kind == TokenKind::BitwiseOr {
+            if self.curr_token().kind != TokenKind::BitwiseOr {
+                loop {
+                    params.push(self.parse_lambda_param()?);
+                    if self.curr_token().kind != TokenKind::Comma {
+                        break;
+                    }
+                    self.advance();
+                }
             }
+            self.expect(TokenKind::BitwiseOr, "| after the parameters")
+                .map_err(|err| {
+                    err.with_label(lbar.span.clone(), "the parameters start here".to_owned())
+                })?;
         }
+
+        let body = self.parse_expression(Parser::lbp(TokenKind::Assign) - 1)?;
+
+        Ok(Expr::LambdaExpr(Box::new(LambdaExpr::new(
+            params,
+            Box::new(body),
+            lbar.span,
+        ))))
     }
 
-    fn parse_expression(&mut self, rbp: i32) -> Expr {
+    /// LambdaParam ::= Identifier (":" Type)?
+    fn parse_lambda_param(&mut self) -> ExprResult<LambdaParam> {
+        let id_token = self.expect(TokenKind::Identifier, "a parameter name")?;
+        let vname = VnameExpr::new(
+            Identifier::new(id_token.spelling, id_token.span.clone()),
+            id_token.span,
+        );
+
+        let annotation = if self.curr_token().kind == TokenKind::Colon {
+            self.advance();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+
+        Ok(LambdaParam::new(vname, annotation))
+    }
+
+    /// LetExpr ::= "let" Identifier (":" Type)? "=" Expr
+    fn parse_let(&mut self, let_token: Token) -> ExprResult<Expr> {
+        let id_token = self.expect(TokenKind::Identifier, "a variable name after let")?;
+        let vname = VnameExpr::new(
+            Identifier::new(id_token.spelling, id_token.span.clone()),
+            id_token.span,
+        );
+
+        let annotation = if self.curr_token().kind == TokenKind::Colon {
+            self.advance();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+
+        self.expect(TokenKind::Assign, "= in let declaration")
+            .map_err(|err| {
+                err.with_help("a variable must be initialised when it is declared".to_owned())
+            })?;
+        let expr = self.parse_expression(Parser::lbp(TokenKind::Assign) - 1)?;
+
+        Ok(Expr::LetExpr(Box::new(LetExpr::new(
+            vname,
+            annotation,
+            Box::new(expr),
+            let_token.span,
+        ))))
+    }
+
+    /// ListExpr ::= "[" (Expr ("," Expr)*)? "]"
+    fn parse_list(&mut self, lbracket: Token) -> ExprResult<Expr> {
+        let mut elems = Vec::new();
+        if self.curr_token().kind != TokenKind::RightBracket {
+            loop {
+                elems.push(self.parse_expression(Parser::MIN_BINDING_POWER)?);
+                if self.curr_token().kind != TokenKind::Comma {
+                    break;
+                }
+                self.advance();
+            }
+        }
+
+        let rbracket = self
+            .expect(TokenKind::RightBracket, "] after the elements of the list")
+            .map_err(|err| {
+                err.with_label(lbracket.span.clone(), "the list starts here".to_owned())
+            })?;
+
+        Ok(Expr::ListExpr(ListExpr::new(
+            elems,
+            lbracket.span,
+            rbracket.span,
+        )))
+    }
+
+    /// IndexExpr ::= Expr "[" Expr "]"
+    fn parse_index(&mut self, list: Expr, lbracket: Token) -> ExprResult<Expr> {
+        let index = self.parse_expression(Parser::MIN_BINDING_POWER)?;
+        let rbracket = self
+            .expect(TokenKind::RightBracket, "] after the index")
+            .map_err(|err| err.with_label(lbracket.span, "the index starts here".to_owned()))?;
+
+        Ok(Expr::IndexExpr(IndexExpr::new(
+            Box::new(list),
+            Box::new(index),
+            rbracket.span,
+        )))
+    }
+
+    /// TupleExpr ::= "(" Expr ("," Expr)+ ")"
+    fn parse_tuple(&mut self, lparen: Token, first: Expr) -> ExprResult<Expr> {
+        let mut elems = vec![first];
+        while self.curr_token().kind == TokenKind::Comma {
+            self.advance();
+            elems.push(self.parse_expression(Parser::MIN_BINDING_POWER)?);
+        }
+
+        let rparen = self
+            .expect(TokenKind::RightParen, ") after the elements of the tuple")
+            .map_err(|err| {
+                err.with_label(lparen.span.clone(), "the tuple starts here".to_owned())
+            })?;
+
+        Ok(Expr::TupleExpr(TupleExpr::new(
+            elems,
+            lparen.span,
+            rparen.span,
+        )))
+    }
+
+    /// FieldExpr ::= Expr "." (Integer | Identifier)
+    fn parse_field(&mut self, expr: Expr) -> ExprResult<Expr> {
+        let field = self.curr_token().clone();
+        if !matches!(field.kind, TokenKind::Integer | TokenKind::Identifier) {
+            return Err(ExprError::with_span(
+                ErrorCode::ExpectedToken,
+                format!("expected a field after ., but found {:?}", field.spelling),
+                field.span,
+            )
+            .with_help(
+                "the elements of a tuple t are t.0, t.1, ..., and the fields of a record r are r.name"
+                    .to_owned(),
+            ));
+        }
+        self.advance();
+
+        Ok(Expr::FieldExpr(Box::new(FieldExpr::new(
+            Box::new(expr),
+            field.spelling,
+            field.span,
+        ))))
+    }
+
+    /// TernaryExpr ::= Expr "?" Expr ":" Expr
+    fn parse_ternary(&mut self, cond: Expr, question: Token) -> ExprResult<Expr> {
+        let then_expr = self.parse_expression(Parser::MIN_BINDING_POWER)?;
+        self.expect(TokenKind::Colon, ": between the arms of ?")
+            .map_err(|err| err.with_label(question.span, "the arms start here".to_owned()))?;
+        let else_expr = self.parse_expression(Parser::lbp(TokenKind::Question) - 1)?;
+
+        Ok(Expr::TernaryExpr(TernaryExpr::new(
+            Box::new(cond),
+            Box::new(then_expr),
+            Box::new(else_expr),
+        )))
+    }
+
+    /// CallExpr ::= Expr "(" (Expr ("," Expr)*)? ")"
+    fn parse_call(&mut self, callee: Expr, lparen: Token) -> ExprResult<Expr> {
+        let mut args = Vec::new();
+
+        if self.curr_token().kind != TokenKind::RightParen {
+            loop {
+                args.push(self.parse_expression(Parser::MIN_BINDING_POWER)?);
+                if self.curr_token().kind != TokenKind::Comma {
+                    break;
+                }
+                self.advance();
+            }
+        }
+
+        if self.curr_token().kind != TokenKind::RightParen {
+            return Err(ExprError::with_span(
+                ErrorCode::MissingRightParen,
+                "Missing right parenthesis while parsing call arguments".to_owned(),
+                lparen.span,
+            ));
+        }
+
+        let rparen_span = self.curr_token().span.clone();
+        self.advance();
+        Ok(Expr::CallExpr(CallExpr::new(
+            Box::new(callee),
+            args,
+            rparen_span,
+        )))
+    }
+
+    /// Type ::= "int" | "float" | "bool" | "string"
+    ///        | "fn" "(" (Type ("," Type)*)? ")" ("->" Type)?
+    ///        | "List" "<" Type ">"
+    ///        | "Option" "<" Type ">"
+    ///        | "(" Type ("," Type)+ ")"
+    ///        | Identifier
+    fn parse_type(&mut self) -> ExprResult<(Type, Span)> {
         let token = self.curr_token().clone();
+        self.enter(&token)?;
         self.advance();
-        let mut left = self.nud(token);
 
-        while rbp < Parser::lbp(self.curr_token().kind) {
-            let token = self.curr_token().clone();
+        let typ = match token.kind {
+            TokenKind::LeftParen => self.parse_tuple_type(token)?,
+            TokenKind::Fn => self.parse_fn_type(token)?,
+            TokenKind::Identifier if matches!(token.spelling.as_str(), "List" | "Option") => {
+                self.parse_elem_type(token)?
+            }
+            _ => Parser::parse_type_name(token)?,
+        };
+
+        self.leave();
+        Ok(typ)
+    }
+
+    /// The types written as a single name.
+    fn parse_type_name(token: Token) -> ExprResult<(Type, Span)> {
+        let typ = match (token.kind, token.spelling.as_str()) {
+            (TokenKind::Identifier, "int") => Type::IntType,
+            (TokenKind::Identifier, "float") => Type::FloatType,
+            (TokenKind::Identifier, "bool") => Type::BoolType,
+            (TokenKind::Identifier, "string") => Type::StringType,
+            // any other name is that of a struct or an enum, which the
+            // checker looks up.
+            (TokenKind::Identifier, name) => Type::NamedType(name.to_owned(), None),
+            _ => return Err(ExprError::with_span(
+                ErrorCode::ExpectedType,
+                format!("expected a type, but found {:?}", token.spelling),
+                token.span,
+            )
+            .with_help(
+                "the types are int, float, bool, string, fn(...) -> ..., List<...>, Option<...>, (..., ...), structs and enums"
+                    .to_owned(),
+            )),
+        };
+
+        Ok((typ, token.span))
+    }
+
+    /// The rest of a tuple type, after the (. A single type in parentheses
+    /// is just that type.
+    fn parse_tuple_type(&mut self, lparen: Token) -> ExprResult<(Type, Span)> {
+        let mut elem_typs = vec![self.parse_type()?.0];
+        while self.curr_token().kind == TokenKind::Comma {
             self.advance();
-            let right = if Parser::is_right_associative(token.kind) {
-                self.parse_expression(Parser::lbp(token.kind) - 1)
-            } else {
-                self.parse_expression(Parser::lbp(token.kind))
-            };
+            elem_typs.push(self.parse_type()?.0);
+        }
+        let rparen = self.expect(TokenKind::RightParen, ") after the element types")?;
 
-            left = self.led(left, token.kind, right);
+        let typ = match elem_typs.len() {
+            1 => elem_typs.pop().unwrap(),
+            _ => Type::TupleType(elem_typs),
+        };
+        Ok((typ, lparen.span.to(&rparen.span)))
+    }
+
+    /// The rest of a list or an option type, after the List or the Option.
+    fn parse_elem_type(&mut self, name_token: Token) -> ExprResult<(Type, Span)> {
+        let name = name_token.spelling.as_str();
+        self.expect(TokenKind::LessThan, &format!("< after {}", name))
+            .map_err(|err| {
+                err.with_help(format!("{} types are written {}<elem_type>", name, name))
+            })?;
+        let (elem_typ, _) = self.parse_type()?;
+        let typ = match name {
+            "List" => Type::ListType(Box::new(elem_typ)),
+            _ => Type::OptionType(Box::new(elem_typ)),
+        };
+
+        // the >> that closes nested types is scanned as a single token, so it
+        // is split into two >s here.
+        if self.curr_token().kind == TokenKind::RightShift {
+            let token = &mut self.tokens[self.curr_idx];
+            let mut rangle_span = token.span.clone();
+            rangle_span.end.col -= 1;
+            token.kind = TokenKind::GreaterThan;
+            token.spelling = ">".to_owned();
+            token.span.start.col += 1;
+            return Ok((typ, name_token.span.to(&rangle_span)));
         }
 
-        left
+        let rangle = self.expect(TokenKind::GreaterThan, "> after the element type")?;
+        Ok((typ, name_token.span.to(&rangle.span)))
+    }
+
+    /// The rest of a function type, after the fn.
+    fn parse_fn_type(&mut self, fn_token: Token) -> ExprResult<(Type, Span)> {
+        let lparen = self.expect(TokenKind::LeftParen, "( after fn in a type")?;
+        let mut param_typs = Vec::new();
+        if self.curr_token().kind != TokenKind::RightParen {
+            loop {
+                param_typs.push(self.parse_type()?.0);
+                if self.curr_token().kind != TokenKind::Comma {
+                    break;
+                }
+                self.advance();
+            }
+        }
+        let rparen = self
+            .expect(TokenKind::RightParen, ") after the parameter types")
+            .map_err(|err| err.with_label(lparen.span, "the parameters start here".to_owned()))?;
+
+        let (ret_typ, end_span) = if self.curr_token().kind == TokenKind::Arrow {
+            self.advance();
+            self.parse_type()?
+        } else {
+            (Type::UnitType, rparen.span)
+        };
+
+        Ok((
+            Type::FnType(param_typs, Box::new(ret_typ)),
+            fn_token.span.to(&end_span),
+        ))
+    }
+
+    /// Error recovery - skip tokens until the start of the next expression,
+    /// which is taken to be the first token on a later line than the one the
+    /// error was found on that can begin an expression.
+    fn synchronize(&mut self) {
+        let err_line = self.prev_token().span.end.line;
+
+        while self.curr_token().kind != TokenKind::Eof {
+            let token = self.curr_token();
+            if token.span.start.line > err_line && Parser::starts_expression(token.kind) {
+                break;
+            }
+            self.advance();
+        }
     }
 
     /// Ast ::= Expr* Eof
-    pub fn parse(&mut self) -> Ast {
+    ///
+    /// Malformed expressions are reported to `diagnostics` and skipped, so the
+    /// returned ast only contains the expressions that parsed successfully.
+    pub fn parse(&mut self, diagnostics: &mut Diagnostics) -> Ast {
         let mut exprs = Vec::new();
 
         while self.curr_token().kind != TokenKind::Eof {
-            exprs.push(self.parse_expression(Parser::MIN_BINDING_POWER));
+            match self.parse_expression(Parser::MIN_BINDING_POWER) {
+                Ok(expr) => exprs.push(expr),
+                Err(err) => {
+                    diagnostics.push(err);
+                    self.depth = 0;
+                    self.synchronize();
+                }
+            }
         }
 
         Ast::new(exprs)
@@ -1,6 +1,8 @@
-use super::source_file::{Char, Location, NUL};
+use super::source_file::{Char, Location, Span, NUL};
 use super::token::{Token, TokenKind};
-use crate::error::{report_error, ExprError, ExprErrorKind};
+use crate::diagnostics::codes::ErrorCode;
+use crate::diagnostics::Diagnostics;
+use crate::error::{ExprError, ExprResult};
 
 pub struct Scanner {
     chars: Vec<Char>,
@@ -21,21 +23,13 @@ impl Scanner {
         }
     }
 
-    fn check_character_stream(&self) {
-        if self.curr_idx >= self.chars.len() {
-            report_error(
-                ExprError::new(
-                    ExprErrorKind::ScannerError,
-                    format!("ran out of characters"),
-                ),
-                None,
-            );
-        }
-    }
-
-    fn curr_char(&self) -> &Char {
-        self.check_character_stream();
-        &self.chars[self.curr_idx]
+    fn curr_char(&self) -> ExprResult<&Char> {
+        self.chars.get(self.curr_idx).ok_or_else(|| {
+            ExprError::new(
+                ErrorCode::OutOfCharacters,
+                "ran out of characters".to_owned(),
+            )
+        })
     }
 
     fn peek_char(&self, offset: usize) -> Option<&Char> {
@@ -50,23 +44,25 @@ impl Scanner {
         self.curr_idx += 1;
     }
 
-    fn eat_it(&mut self) {
-        self.curr_buf.push(self.curr_char().c);
+    fn eat_it(&mut self) -> ExprResult<()> {
+        let c = self.curr_char()?.c;
+        self.curr_buf.push(c);
         self.curr_idx += 1;
+        Ok(())
     }
 
-    fn skip_whitespace(&mut self) {
-        match self.curr_char().c {
+    fn skip_whitespace(&mut self) -> ExprResult<()> {
+        match self.curr_char()?.c {
             '/' => {
                 if let Some(ch) = self.peek_char(1) {
                     if ch.c == '/' {
                         self.skip_it();
                         self.skip_it();
-                        while self.curr_char().c != NUL && self.curr_char().c != '\n' {
+                        while self.curr_char()?.c != NUL && self.curr_char()?.c != '\n' {
                             self.skip_it();
                         }
 
-                        if self.curr_char().c == '\n' {
+                        if self.curr_char()?.c == '\n' {
                             self.skip_it();
                         }
                     }
@@ -74,52 +70,121 @@ impl Scanner {
             }
 
             c if c.is_whitespace() => {
-                while self.curr_char().c.is_whitespace() {
+                while self.curr_char()?.c.is_whitespace() {
                     self.skip_it();
                 }
             }
             _ => {}
         }
+
+        Ok(())
     }
 
-    fn scan_token(&mut self) -> TokenKind {
-        self.curr_loc = self.curr_char().loc.clone();
+    fn scan_token(&mut self) -> ExprResult<TokenKind> {
+        self.curr_loc = self.curr_char()?.loc.clone();
 
-        match self.curr_char().c {
+        let kind = match self.curr_char()?.c {
             '(' => {
-                self.eat_it();
+                self.eat_it()?;
                 TokenKind::LeftParen
             }
 
             ')' => {
-                self.eat_it();
+                self.eat_it()?;
                 TokenKind::RightParen
             }
 
+            '{' => {
+                self.eat_it()?;
+                TokenKind::LeftBrace
+            }
+
+            '}' => {
+                self.eat_it()?;
+                TokenKind::RightBrace
+            }
+
+            ',' => {
+                self.eat_it()?;
+                TokenKind::Comma
+            }
+
+            ':' => {
+                self.eat_it()?;
+                TokenKind::Colon
+            }
+
+            '[' => {
+                self.eat_it()?;
+                TokenKind::LeftBracket
+            }
+
+            ']' => {
+                self.eat_it()?;
+                TokenKind::RightBracket
+            }
+
+            '?' => {
+                self.eat_it()?;
+                if self.curr_char()?.c == '?' {
+                    self.eat_it()?;
+                    TokenKind::QuestionQuestion
+                } else {
+                    TokenKind::Question
+                }
+            }
+
+            '_' => {
+                self.eat_it()?;
+                TokenKind::Underscore
+            }
+
+            '.' if self.peek_char(1).is_some_and(|ch| ch.c == '.') => {
+                self.eat_it()?;
+                self.eat_it()?;
+                TokenKind::DotDot
+            }
+
+            '.' => {
+                self.eat_it()?;
+                TokenKind::Dot
+            }
+
+            '"' => self.scan_string()?,
+
             '+' => {
-                self.eat_it();
-                if self.curr_char().c == '=' {
-                    self.eat_it();
+                self.eat_it()?;
+                if self.curr_char()?.c == '=' {
+                    self.eat_it()?;
                     TokenKind::PlusAssign
+                } else if self.curr_char()?.c == '+' {
+                    self.eat_it()?;
+                    TokenKind::Increment
                 } else {
                     TokenKind::Plus
                 }
             }
 
             '-' => {
-                self.eat_it();
-                if self.curr_char().c == '=' {
-                    self.eat_it();
+                self.eat_it()?;
+                if self.curr_char()?.c == '=' {
+                    self.eat_it()?;
                     TokenKind::MinusAssign
+                } else if self.curr_char()?.c == '>' {
+                    self.eat_it()?;
+                    TokenKind::Arrow
+                } else if self.curr_char()?.c == '-' {
+                    self.eat_it()?;
+                    TokenKind::Decrement
                 } else {
                     TokenKind::Minus
                 }
             }
 
             '*' => {
-                self.eat_it();
-                if self.curr_char().c == '=' {
-                    self.eat_it();
+                self.eat_it()?;
+                if self.curr_char()?.c == '=' {
+                    self.eat_it()?;
                     TokenKind::StarAssign
                 } else {
                     TokenKind::Star
@@ -127,9 +192,9 @@ impl Scanner {
             }
 
             '/' => {
-                self.eat_it();
-                if self.curr_char().c == '=' {
-                    self.eat_it();
+                self.eat_it()?;
+                if self.curr_char()?.c == '=' {
+                    self.eat_it()?;
                     TokenKind::SlashAssign
                 } else {
                     TokenKind::Slash
@@ -137,9 +202,9 @@ impl Scanner {
             }
 
             '%' => {
-                self.eat_it();
-                if self.curr_char().c == '=' {
-                    self.eat_it();
+                self.eat_it()?;
+                if self.curr_char()?.c == '=' {
+                    self.eat_it()?;
                     TokenKind::ModAssign
                 } else {
                     TokenKind::Mod
@@ -147,18 +212,22 @@ impl Scanner {
             }
 
             '=' => {
-                self.eat_it();
-                if self.curr_char().c == '=' {
+                self.eat_it()?;
+                if self.curr_char()?.c == '=' {
+                    self.eat_it()?;
                     TokenKind::Equal
+                } else if self.curr_char()?.c == '>' {
+                    self.eat_it()?;
+                    TokenKind::FatArrow
                 } else {
                     TokenKind::Assign
                 }
             }
 
             '!' => {
-                self.eat_it();
-                if self.curr_char().c == '=' {
-                    self.eat_it();
+                self.eat_it()?;
+                if self.curr_char()?.c == '=' {
+                    self.eat_it()?;
                     TokenKind::NotEqual
                 } else {
                     TokenKind::LogicalNot
@@ -166,9 +235,9 @@ impl Scanner {
             }
 
             '&' => {
-                self.eat_it();
-                if self.curr_char().c == '&' {
-                    self.eat_it();
+                self.eat_it()?;
+                if self.curr_char()?.c == '&' {
+                    self.eat_it()?;
                     TokenKind::LogicalAnd
                 } else {
                     TokenKind::BitwiseAnd
@@ -176,9 +245,9 @@ impl Scanner {
             }
 
             '|' => {
-                self.eat_it();
-                if self.curr_char().c == '|' {
-                    self.eat_it();
+                self.eat_it()?;
+                if self.curr_char()?.c == '|' {
+                    self.eat_it()?;
                     TokenKind::LogicalOr
                 } else {
                     TokenKind::BitwiseOr
@@ -186,9 +255,9 @@ impl Scanner {
             }
 
             '^' => {
-                self.eat_it();
-                if self.curr_char().c == '=' {
-                    self.eat_it();
+                self.eat_it()?;
+                if self.curr_char()?.c == '=' {
+                    self.eat_it()?;
                     TokenKind::BitwiseXorAssign
                 } else {
                     TokenKind::BitwiseXor
@@ -196,22 +265,22 @@ impl Scanner {
             }
 
             '~' => {
-                self.eat_it();
+                self.eat_it()?;
                 TokenKind::BitwiseNot
             }
 
             '<' => {
-                self.eat_it();
-                if self.curr_char().c == '<' {
-                    self.eat_it();
-                    if self.curr_char().c == '=' {
-                        self.eat_it();
+                self.eat_it()?;
+                if self.curr_char()?.c == '<' {
+                    self.eat_it()?;
+                    if self.curr_char()?.c == '=' {
+                        self.eat_it()?;
                         TokenKind::LeftShiftAssign
                     } else {
                         TokenKind::LeftShift
                     }
-                } else if self.curr_char().c == '=' {
-                    self.eat_it();
+                } else if self.curr_char()?.c == '=' {
+                    self.eat_it()?;
                     TokenKind::LessThanOrEqual
                 } else {
                     TokenKind::LessThan
@@ -219,72 +288,262 @@ impl Scanner {
             }
 
             '>' => {
-                self.eat_it();
-                if self.curr_char().c == '>' {
-                    self.eat_it();
-                    if self.curr_char().c == '=' {
-                        self.eat_it();
+                self.eat_it()?;
+                if self.curr_char()?.c == '>' {
+                    self.eat_it()?;
+                    if self.curr_char()?.c == '=' {
+                        self.eat_it()?;
                         TokenKind::RightShiftAssign
                     } else {
                         TokenKind::RightShift
                     }
-                } else if self.curr_char().c == '=' {
-                    self.eat_it();
+                } else if self.curr_char()?.c == '=' {
+                    self.eat_it()?;
                     TokenKind::GreaterThanOrEqual
                 } else {
                     TokenKind::GreaterThan
                 }
             }
 
+            // an identifier starts with a letter, as a lone _ is a pattern.
             c if c.is_ascii_alphabetic() => {
-                while self.curr_char().c.is_ascii_alphabetic() {
-                    self.eat_it();
+                while self.curr_char()?.c.is_ascii_alphabetic() || self.curr_char()?.c == '_' {
+                    self.eat_it()?;
                 }
                 TokenKind::Identifier
             }
 
-            c if c.is_ascii_digit() => {
-                while self.curr_char().c.is_ascii_digit() {
-                    self.eat_it();
+            c if c.is_ascii_digit() => self.scan_number()?,
+
+            NUL => TokenKind::Eof,
+
+            c => {
+                self.skip_it();
+                return Err(ExprError::with_span(
+                    ErrorCode::UnexpectedCharacter,
+                    format!("unexpected character {:?}", c),
+                    Span::new(self.curr_loc.clone(), self.curr_loc.clone()),
+                ));
+            }
+        };
+
+        Ok(kind)
+    }
+
+    fn eat_digits(&mut self) -> ExprResult<()> {
+        while self.curr_char()?.c.is_ascii_digit() {
+            self.eat_it()?;
+        }
+        Ok(())
+    }
+
+    fn peek_is_digit(&self, offset: usize) -> bool {
+        self.peek_char(offset)
+            .is_some_and(|ch| ch.c.is_ascii_digit())
+    }
+
+    /// Integer ::= Digit+
+    /// Float ::= Digit+ ("." Digit+)? (("e" | "E") ("+" | "-")? Digit+)?
+    ///
+    /// A number is a float if it has a fraction or an exponent (or both).
+    /// A number right after a . is the index of a field, as in `t.0.1`, so it
+    /// is always an integer.
+    fn scan_number(&mut self) -> ExprResult<TokenKind> {
+        let mut kind = TokenKind::Integer;
+        self.eat_digits()?;
+
+        if self
+            .tokens
+            .last()
+            .is_some_and(|token| token.kind == TokenKind::Dot)
+        {
+            return Ok(kind);
+        }
+
+        if self.curr_char()?.c == '.' && self.peek_is_digit(1) {
+            kind = TokenKind::Float;
+            self.eat_it()?;
+            self.eat_digits()?;
+        }
+
+        if matches!(self.curr_char()?.c, 'e' | 'E') {
+            let sign = matches!(self.peek_char(1).map(|ch| ch.c), Some('+' | '-'));
+            let digit_offset = if sign { 2 } else { 1 };
+
+            if self.peek_is_digit(digit_offset) {
+                kind = TokenKind::Float;
+                self.eat_it()?;
+                if sign {
+                    self.eat_it()?;
                 }
-                TokenKind::Integer
+                self.eat_digits()?;
             }
+        }
 
-            NUL => TokenKind::Eof,
+        Ok(kind)
+    }
+
+    /// String ::= '"' (Char | Escape)* '"'
+    /// Escape ::= "\\" ("n" | "t" | "r" | "0" | '"' | "\\" | "u{" HexDigit+ "}")
+    ///
+    /// The spelling of the token is the value of the string, i.e., without
+    /// the quotes and with the escapes replaced. An invalid escape is reported
+    /// once the whole string has been scanned, so that scanning resumes after
+    /// the string.
+    fn scan_string(&mut self) -> ExprResult<TokenKind> {
+        self.skip_it();
+        let mut escape_err = None;
 
-            _ => {
-                println!(
-                    "unreachable - {}, {}",
-                    self.curr_char().c,
-                    self.curr_char().c as i32
-                );
-                unreachable!();
+        loop {
+            let ch = self.curr_char()?.clone();
+            match ch.c {
+                '"' => {
+                    self.skip_it();
+                    break;
+                }
+
+                NUL | '\n' => {
+                    return Err(ExprError::with_span(
+                        ErrorCode::UnterminatedString,
+                        "unterminated string literal".to_owned(),
+                        Span::new(self.curr_loc.clone(), self.curr_loc.clone()),
+                    )
+                    .with_help("strings cannot span lines - use \\n for a newline".to_owned()));
+                }
+
+                '\\' => {
+                    self.skip_it();
+                    match self.scan_escape() {
+                        Ok(c) => self.curr_buf.push(c),
+                        Err(err) => {
+                            let end_loc = self.chars[self.curr_idx - 1].loc.clone();
+                            let err = err.or_span(&Span::new(ch.loc.clone(), end_loc));
+                            escape_err.get_or_insert(err);
+                        }
+                    }
+                }
+
+                _ => self.eat_it()?,
             }
         }
+
+        match escape_err {
+            Some(err) => Err(err),
+            None => Ok(TokenKind::String),
+        }
     }
 
-    fn scan(&mut self) -> Token {
-        while self.curr_char().c.is_whitespace()
-            || self.curr_char().c == '/'
+    /// Scan the rest of an escape, after the backslash.
+    fn scan_escape(&mut self) -> ExprResult<char> {
+        let c = self.curr_char()?.c;
+        let escaped = match c {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '"' => '"',
+            '\\' => '\\',
+            'u' if self.peek_char(1).is_some_and(|ch| ch.c == '{') => {
+                self.skip_it();
+                self.skip_it();
+
+                let mut digits = String::new();
+                while self.curr_char()?.c.is_ascii_hexdigit() {
+                    digits.push(self.curr_char()?.c);
+                    self.skip_it();
+                }
+
+                let closed = self.curr_char()?.c == '}';
+                if closed {
+                    self.skip_it();
+                }
+
+                return u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .filter(|_| closed && digits.len() <= 6)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| {
+                        ExprError::new(
+                            ErrorCode::InvalidEscape,
+                            "invalid unicode escape".to_owned(),
+                        )
+                        .with_help(
+                            "a unicode escape is \\u{...} with one to six hex digits".to_owned(),
+                        )
+                    });
+            }
+            NUL | '\n' => {
+                return Err(ExprError::new(
+                    ErrorCode::InvalidEscape,
+                    "incomplete escape at the end of the line".to_owned(),
+                ))
+            }
+            c => {
+                self.skip_it();
+                return Err(ExprError::new(
+                    ErrorCode::InvalidEscape,
+                    format!("unknown escape \\{}", c),
+                )
+                .with_help("to include a backslash in a string, escape it as \\\\".to_owned()));
+            }
+        };
+
+        self.skip_it();
+        Ok(escaped)
+    }
+
+    fn scan(&mut self) -> ExprResult<Token> {
+        while self.curr_char()?.c.is_whitespace()
+            || self.curr_char()?.c == '/'
                 && self.peek_char(1).is_some()
                 && self.peek_char(1).unwrap().c == '/'
         {
-            self.skip_whitespace();
+            self.skip_whitespace()?;
         }
 
         self.curr_buf = String::new();
-        let kind = self.scan_token();
-        Token::new(kind, self.curr_buf.clone(), self.curr_loc.clone())
+        let start_idx = self.curr_idx;
+        let kind = self.scan_token()?;
+
+        let end_loc = if self.curr_idx > start_idx {
+            self.chars[self.curr_idx - 1].loc.clone()
+        } else {
+            self.curr_loc.clone()
+        };
+
+        Ok(Token::new(
+            kind,
+            self.curr_buf.clone(),
+            Span::new(self.curr_loc.clone(), end_loc),
+        ))
     }
 
-    pub fn scan_all(&mut self) {
+    /// Scan the whole character stream, reporting every invalid character to
+    /// `diagnostics` and continuing with the next one.
+    pub fn scan_all(&mut self, diagnostics: &mut Diagnostics) {
         loop {
-            let token = self.scan();
-            let is_eof = token.kind == TokenKind::Eof;
-            self.tokens.push(token);
+            match self.scan() {
+                Ok(token) => {
+                    let is_eof = token.kind == TokenKind::Eof;
+                    self.tokens.push(token);
+
+                    if is_eof {
+                        break;
+                    }
+                }
+
+                Err(err) => {
+                    diagnostics.push(err);
 
-            if is_eof {
-                break;
+                    if self.curr_idx >= self.chars.len() {
+                        self.tokens.push(Token::new(
+                            TokenKind::Eof,
+                            String::new(),
+                            Span::new(self.curr_loc.clone(), self.curr_loc.clone()),
+                        ));
+                        break;
+                    }
+                }
             }
         }
     }
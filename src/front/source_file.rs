@@ -1,22 +1,25 @@
-use crate::error::{report_error, ExprError, ExprErrorKind, ExprResult};
-use std::default::Default;
+use crate::diagnostics::codes::ErrorCode;
+use crate::error::{ExprError, ExprResult};
 use std::fmt::Debug;
 use std::fs;
 use std::io;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 pub const NUL: char = '\u{0}';
 
+/// The path is shared by all the locations in a source file, since every
+/// character, token and node of the ast carries (at least) one location.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Location {
-    pub source_file: PathBuf,
+    pub source_file: Arc<Path>,
     pub line: usize,
     pub col: usize,
 }
 
 impl Location {
-    pub fn new(source_file: PathBuf, line: usize, col: usize) -> Self {
+    pub fn new(source_file: Arc<Path>, line: usize, col: usize) -> Self {
         Location {
             source_file,
             line,
@@ -27,15 +30,30 @@ impl Location {
 
 impl Default for Location {
     fn default() -> Self {
-        Location {
-            source_file: PathBuf::default(),
-            line: usize::default(),
-            col: usize::default(),
-        }
+        Location::new(Arc::from(Path::new("")), 0, 0)
     }
 }
 
-#[derive(Debug)]
+/// A region of source text, from the location of its first character to the
+/// location of its last character (both inclusive).
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+impl Span {
+    pub fn new(start: Location, end: Location) -> Self {
+        Span { start, end }
+    }
+
+    /// The span starting at the start of `self` and ending at the end of `other`.
+    pub fn to(&self, other: &Span) -> Span {
+        Span::new(self.start.clone(), other.end.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Char {
     pub c: char,
     pub loc: Location,
@@ -49,44 +67,35 @@ impl Char {
 
 #[derive(Debug)]
 pub struct SourceFile {
+    pub path: PathBuf,
+    pub contents: String,
     pub chars: Vec<Char>,
 }
 
 impl SourceFile {
-    pub fn new<P: AsRef<Path> + Debug>(source_file_path: P) -> Self {
-        let chars = SourceFile::init_source_file(&source_file_path);
-
-        if chars.is_err() {
-            report_error(
-                ExprError::new(
-                    ExprErrorKind::ScannerError,
-                    format!("Could not open source file {:?}", source_file_path),
-                ),
-                None,
-            );
-        }
-
-        SourceFile {
-            chars: chars.unwrap(),
-        }
+    pub fn new<P: AsRef<Path> + Debug>(source_file_path: P) -> ExprResult<Self> {
+        let contents = SourceFile::read_source_file(&source_file_path).map_err(|_| {
+            ExprError::new(
+                ErrorCode::SourceFileUnreadable,
+                format!("Could not open source file {:?}", source_file_path),
+            )
+        })?;
+
+        Ok(SourceFile::from_contents(source_file_path, &contents))
     }
 
-    fn init_source_file<P: AsRef<Path>>(source_file_path: &P) -> ExprResult<Vec<Char>> {
-        let mut source_reader = io::BufReader::new(fs::File::open(source_file_path)?);
-        let mut file_contents = String::new();
-        source_reader.read_to_string(&mut file_contents)?;
-        file_contents.push(NUL); // for eof
-
+    /// Create a source file from in-memory contents, with locations
+    /// reported against `source_file_path`.
+    pub fn from_contents<P: AsRef<Path>>(source_file_path: P, contents: &str) -> Self {
+        let path: Arc<Path> = Arc::from(source_file_path.as_ref());
         let mut line = 1;
         let mut col = 1;
 
-        let chars = file_contents
+        let chars = contents
             .chars()
+            .chain(std::iter::once(NUL)) // for eof
             .map(|c| {
-                let ch = Char::new(
-                    c,
-                    Location::new(source_file_path.as_ref().to_owned(), line, col),
-                );
+                let ch = Char::new(c, Location::new(path.clone(), line, col));
 
                 if c == '\n' {
                     line += 1;
@@ -99,6 +108,25 @@ impl SourceFile {
             })
             .collect::<Vec<Char>>();
 
-        Ok(chars)
+        SourceFile {
+            path: source_file_path.as_ref().to_owned(),
+            contents: contents.to_owned(),
+            chars,
+        }
+    }
+
+    /// The text of the given (1-based) line, without its line terminator.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.contents
+            .lines()
+            .nth(line.checked_sub(1)?)
+            .map(|text| text.trim_end_matches('\r'))
+    }
+
+    fn read_source_file<P: AsRef<Path>>(source_file_path: &P) -> io::Result<String> {
+        let mut source_reader = io::BufReader::new(fs::File::open(source_file_path)?);
+        let mut file_contents = String::new();
+        source_reader.read_to_string(&mut file_contents)?;
+        Ok(file_contents)
     }
 }
@@ -1,8 +1,11 @@
-use super::source_file::Location;
+use super::source_file::Span;
+use std::fmt;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum TokenKind {
+    As,
     Assign,
+    Arrow,
     BitwiseAnd,
     BitwiseAndAssign,
     BitwiseNot,
@@ -10,29 +13,54 @@ pub enum TokenKind {
     BitwiseOrAssign,
     BitwiseXor,
     BitwiseXorAssign,
+    Break,
+    Colon,
+    Comma,
+    Continue,
+    Decrement,
+    Dot,
+    DotDot,
+    Else,
+    Enum,
     Eof,
     Equal,
+    FatArrow,
     False,
+    Float,
+    Fn,
+    For,
     GreaterThan,
     GreaterThanOrEqual,
     Identifier,
+    If,
+    In,
+    Increment,
     Integer,
+    LeftBrace,
+    LeftBracket,
     LeftParen,
     LeftShift,
     LeftShiftAssign,
     LessThan,
     LessThanOrEqual,
+    Let,
     LogicalAnd,
     LogicalNot,
     LogicalOr,
+    Match,
     Minus,
     MinusAssign,
     Mod,
     ModAssign,
+    None,
     NotEqual,
     Plus,
     PlusAssign,
     Print,
+    Question,
+    QuestionQuestion,
+    RightBrace,
+    RightBracket,
     RightParen,
     RightShift,
     RightShiftAssign,
@@ -40,29 +68,62 @@ pub enum TokenKind {
     SlashAssign,
     Star,
     StarAssign,
+    String,
+    Struct,
     True,
+    Underscore,
+    While,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub kind: TokenKind,
     pub spelling: String,
-    pub loc: Location,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(kind: TokenKind, spelling: String, loc: Location) -> Self {
-        let kind = match &*spelling {
-            "print" => TokenKind::Print,
-            "true" => TokenKind::True,
-            "false" => TokenKind::False,
+    pub fn new(kind: TokenKind, spelling: String, span: Span) -> Self {
+        let kind = match (kind, &*spelling) {
+            (TokenKind::Identifier, "as") => TokenKind::As,
+            (TokenKind::Identifier, "print") => TokenKind::Print,
+            (TokenKind::Identifier, "true") => TokenKind::True,
+            (TokenKind::Identifier, "false") => TokenKind::False,
+            (TokenKind::Identifier, "none") => TokenKind::None,
+            (TokenKind::Identifier, "let") => TokenKind::Let,
+            (TokenKind::Identifier, "if") => TokenKind::If,
+            (TokenKind::Identifier, "else") => TokenKind::Else,
+            (TokenKind::Identifier, "while") => TokenKind::While,
+            (TokenKind::Identifier, "for") => TokenKind::For,
+            (TokenKind::Identifier, "in") => TokenKind::In,
+            (TokenKind::Identifier, "break") => TokenKind::Break,
+            (TokenKind::Identifier, "continue") => TokenKind::Continue,
+            (TokenKind::Identifier, "fn") => TokenKind::Fn,
+            (TokenKind::Identifier, "struct") => TokenKind::Struct,
+            (TokenKind::Identifier, "enum") => TokenKind::Enum,
+            (TokenKind::Identifier, "match") => TokenKind::Match,
             _ => kind,
         };
 
         Token {
             kind,
             spelling,
-            loc,
+            span,
         }
     }
 }
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}-{}:{} {:?} {:?}",
+            self.span.start.line,
+            self.span.start.col,
+            self.span.end.line,
+            self.span.end.col,
+            self.kind,
+            self.spelling
+        )
+    }
+}
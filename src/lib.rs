@@ -6,6 +6,8 @@
 //)]
 
 pub mod backend;
+pub mod diagnostics;
 pub mod error;
 pub mod front;
 pub mod middle;
+pub mod repl;
@@ -1,42 +1,393 @@
 use expr_lang::backend::interpreter::Interpreter;
+use expr_lang::diagnostics::{codes::ErrorCode, json, render::Renderer, Diagnostics};
+use expr_lang::error::ExprError;
+use expr_lang::front::ast::Ast;
 use expr_lang::front::{parser::Parser, scanner::Scanner, source_file::SourceFile};
-use expr_lang::middle::checker::Checker;
+use expr_lang::middle::lints::{Lint, LintLevel, LintLevels, Linter};
+use expr_lang::middle::{checker::Checker, printer::AstPrinter};
+use expr_lang::repl::Repl;
 
 use std::env;
+use std::io::{self, IsTerminal};
+use std::process;
+
+const USAGE: &str = "\
+usage: expr-lang [<command>] [<options>]
+
+commands:
+    run <file>       check and run <file> (the default if only a file is given)
+    check <file>     check <file> without running it
+    tokens <file>    show the tokens of <file>
+    ast <file>       show the ast of <file>
+    eval -e <expr>   check and run <expr>
+    repl             start an interactive session (the default without arguments)
+    explain <code>   describe an error code, e.g., E0007
+
+options:
+    --emit=<stages>  also show the given stages - a comma separated list of
+                     tokens, ast and typed-ast
+    --message-format=<format>
+                     report errors as human readable text (the default) or
+                     as json, one object per line
+    -A, --allow <lint>   do not report <lint>
+    -W, --warn <lint>    report <lint> as a warning (the default)
+    -D, --deny <lint>    report <lint> as an error
+                     where <lint> is one of unused_variables, dead_assignments,
+                     self_assignment, constant_conditions and unary_plus, or
+                     warnings for all of them
+    --max-call-depth <n>
+                     stop a program whose calls are nested more than <n>
+                     deep (the default is 1000, and at most 100000)
+    -h, --help       show this message
+
+exit codes:
+    0  success
+    1  the interactive session failed
+    2  invalid arguments
+    3  scanner error (or the source file could not be read)
+    4  parser error
+    5  checker error
+    6  interpreter error";
+
+/// The exit code for invalid command line arguments.
+const USAGE_EXIT_CODE: i32 = 2;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Command {
+    Run,
+    Check,
+    Tokens,
+    Ast,
+    Eval,
+    Repl,
+    Explain,
+}
+
+/// The intermediate stages to be shown in addition to the command's output.
+#[derive(Debug, Default)]
+struct Emit {
+    tokens: bool,
+    ast: bool,
+    typed_ast: bool,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+enum MessageFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+#[derive(Debug)]
+struct Options {
+    command: Command,
+    path: Option<String>,
+    expr: Option<String>,
+    emit: Emit,
+    message_format: MessageFormat,
+    lint_levels: LintLevels,
+    max_call_depth: usize,
+}
 
 fn main() {
-    println!("Welcome to expr lang");
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("error: {}\n\n{}", msg, USAGE);
+            process::exit(USAGE_EXIT_CODE);
+        }
+    };
+
+    let message_format = options.message_format;
+    let source_file = match (options.command, &options.path, &options.expr) {
+        (Command::Repl, _, _) => {
+            run_repl();
+            return;
+        }
+        (Command::Explain, Some(code), _) => {
+            explain(code);
+            return;
+        }
+        (Command::Eval, _, Some(expr)) => SourceFile::from_contents("<eval>", expr),
+        (_, Some(path), _) => match SourceFile::new(path) {
+            Ok(source_file) => source_file,
+            Err(err) => {
+                match message_format {
+                    MessageFormat::Human => eprintln!("{}", err),
+                    MessageFormat::Json => eprintln!("{}", json::to_json(&err)),
+                }
+                process::exit(err.kind().exit_code());
+            }
+        },
+        _ => unreachable!("the arguments are validated by parse_args"),
+    };
+
+    let mut diagnostics = Diagnostics::new();
+    let ast = compile(&source_file, &options, &mut diagnostics);
+
+    report(&source_file, &diagnostics.sorted(), message_format);
+    if let Some(err) = diagnostics.first_error() {
+        process::exit(err.kind().exit_code());
+    }
 
-    let args = env::args().skip(1).collect::<Vec<String>>();
-    if args.len() != 1 {
-        run_repl();
+    if let Some(ast) = ast {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_max_call_depth(options.max_call_depth);
+        if let Err(err) = interpreter.interpret(&ast) {
+            report(&source_file, &[&err], message_format);
+            process::exit(err.kind().exit_code());
+        }
     }
+}
+
+fn report(source_file: &SourceFile, errors: &[&ExprError], message_format: MessageFormat) {
+    match message_format {
+        MessageFormat::Human => {
+            let renderer = Renderer::new(source_file, io::stderr().is_terminal());
+            for err in errors {
+                eprintln!("{}", renderer.render(err));
+            }
+        }
+
+        MessageFormat::Json => {
+            for err in errors {
+                eprintln!("{}", json::to_json(err));
+            }
+        }
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut args = args.peekable();
+    let has_args = args.peek().is_some();
+    let mut command = None;
+
+    let mut options = Options {
+        command: Command::Repl,
+        path: None,
+        expr: None,
+        emit: Emit::default(),
+        message_format: MessageFormat::default(),
+        lint_levels: LintLevels::new(),
+        max_call_depth: Interpreter::DEFAULT_MAX_CALL_DEPTH,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+
+            "-e" | "--expr" => match args.next() {
+                Some(expr) => options.expr = Some(expr),
+                None => return Err(format!("{} expects an expression", arg)),
+            },
 
-    let source_file_path = &args[0];
-    let source_file = SourceFile::new(source_file_path);
+            "--emit" => match args.next() {
+                Some(stages) => parse_emit(&stages, &mut options.emit)?,
+                None => return Err("--emit expects a list of stages".to_owned()),
+            },
 
-    let mut scanner = Scanner::new(source_file.chars);
-    scanner.scan_all();
+            _ if arg.starts_with("--emit=") => {
+                parse_emit(&arg["--emit=".len()..], &mut options.emit)?
+            }
 
-    for token in &scanner.tokens {
-        println!("{:?}", token);
+            "--message-format" => match args.next() {
+                Some(format) => options.message_format = parse_message_format(&format)?,
+                None => return Err("--message-format expects a format".to_owned()),
+            },
+
+            _ if arg.starts_with("--message-format=") => {
+                options.message_format = parse_message_format(&arg["--message-format=".len()..])?
+            }
+
+            "--max-call-depth" => match args.next() {
+                Some(depth) => options.max_call_depth = parse_max_call_depth(&depth)?,
+                None => return Err("--max-call-depth expects a number".to_owned()),
+            },
+
+            _ if arg.starts_with("--max-call-depth=") => {
+                options.max_call_depth = parse_max_call_depth(&arg["--max-call-depth=".len()..])?
+            }
+
+            "-A" | "--allow" | "-W" | "--warn" | "-D" | "--deny" => {
+                let level = match arg.as_str() {
+                    "-A" | "--allow" => LintLevel::Allow,
+                    "-W" | "--warn" => LintLevel::Warn,
+                    _ => LintLevel::Deny,
+                };
+
+                match args.next().as_deref() {
+                    Some("warnings") => options.lint_levels.set_all(level),
+                    Some(name) => match Lint::from_name(name) {
+                        Some(lint) => options.lint_levels.set(lint, level),
+                        None => return Err(format!("unknown lint {}", name)),
+                    },
+                    None => return Err(format!("{} expects a lint", arg)),
+                }
+            }
+
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("unknown option {}", arg))
+            }
+
+            // the command is the first argument that is not an option, and
+            // may be preceded by options.
+            _ if command.is_none() && options.path.is_none() => match parse_command(&arg) {
+                Some(cmd) => command = Some(cmd),
+                None => options.path = Some(arg),
+            },
+            _ if options.path.is_none() => options.path = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    // without a command, a lone source file is run and no arguments at all
+    // start an interactive session.
+    options.command = command.unwrap_or(if has_args {
+        Command::Run
+    } else {
+        Command::Repl
+    });
+
+    match options.command {
+        _ if options.expr.is_some() && options.command != Command::Eval => {
+            Err("-e is only valid with eval".to_owned())
+        }
+        Command::Repl if options.path.is_some() => {
+            Err("repl does not take a source file".to_owned())
+        }
+        Command::Eval if options.path.is_some() => {
+            Err("eval takes an expression with -e instead of a source file".to_owned())
+        }
+        Command::Explain if options.path.is_none() => {
+            Err("explain expects an error code".to_owned())
+        }
+        Command::Eval if options.expr.is_none() => Err("eval expects -e <expr>".to_owned()),
+        Command::Run | Command::Check | Command::Tokens | Command::Ast
+            if options.path.is_none() =>
+        {
+            Err("missing source file".to_owned())
+        }
+        _ => Ok(options),
+    }
+}
+
+fn parse_command(arg: &str) -> Option<Command> {
+    match arg {
+        "run" => Some(Command::Run),
+        "check" => Some(Command::Check),
+        "tokens" => Some(Command::Tokens),
+        "ast" => Some(Command::Ast),
+        "eval" => Some(Command::Eval),
+        "repl" => Some(Command::Repl),
+        "explain" => Some(Command::Explain),
+        _ => None,
+    }
+}
+
+fn parse_emit(stages: &str, emit: &mut Emit) -> Result<(), String> {
+    for stage in stages.split(',') {
+        match stage.trim() {
+            "tokens" => emit.tokens = true,
+            "ast" => emit.ast = true,
+            "typed-ast" => emit.typed_ast = true,
+            stage => return Err(format!("unknown stage {:?} for --emit", stage)),
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_message_format(format: &str) -> Result<MessageFormat, String> {
+    match format {
+        "human" => Ok(MessageFormat::Human),
+        "json" => Ok(MessageFormat::Json),
+        format => Err(format!("unknown message format {:?}", format)),
+    }
+}
+
+fn parse_max_call_depth(depth: &str) -> Result<usize, String> {
+    match depth.parse::<usize>() {
+        Ok(depth) if depth > 0 && depth <= Interpreter::MAX_CALL_DEPTH => Ok(depth),
+        Ok(depth) if depth > 0 => Err(format!(
+            "call depth {} is larger than the maximum of {}",
+            depth,
+            Interpreter::MAX_CALL_DEPTH
+        )),
+        _ => Err(format!("invalid call depth {:?}", depth)),
+    }
+}
+
+/// Run the phases needed by the command, showing the stages in `emit` along
+/// the way. The ast is returned if it is to be interpreted, i.e., if the
+/// command runs the program and none of the phases failed.
+fn compile(
+    source_file: &SourceFile,
+    options: &Options,
+    diagnostics: &mut Diagnostics,
+) -> Option<Ast> {
+    let command = options.command;
+    let emit = &options.emit;
+
+    let mut scanner = Scanner::new(source_file.chars.clone());
+    scanner.scan_all(diagnostics);
+
+    if emit.tokens || command == Command::Tokens {
+        for token in &scanner.tokens {
+            println!("{}", token);
+        }
+    }
+    if command == Command::Tokens {
+        return None;
     }
 
     let mut parser = Parser::new(scanner.tokens);
-    let mut ast = parser.parse();
-    println!("{:#?}", ast);
+    let mut ast = parser.parse(diagnostics);
+
+    if emit.ast || command == Command::Ast {
+        print!("{}", AstPrinter::print(&mut ast));
+    }
+    if command == Command::Ast {
+        return None;
+    }
 
     let mut checker = Checker::new();
-    checker.check(&mut ast);
-    println!("{:#?}", ast);
+    checker.check(&mut ast, diagnostics);
+
+    if emit.typed_ast {
+        print!("{}", AstPrinter::print(&mut ast));
+    }
+    if diagnostics.has_errors() {
+        return None;
+    }
+
+    Linter::new(&options.lint_levels).lint(&mut ast, diagnostics);
+
+    if command == Command::Check || diagnostics.has_errors() {
+        return None;
+    }
+
+    Some(ast)
+}
 
-    let mut interpreter = Interpreter::new();
-    interpreter.interpret(&mut ast);
+fn explain(code: &str) {
+    match ErrorCode::from_code(code) {
+        Some(code) => {
+            let explanation = code.explanation();
+            println!("{}: {}\n\n{}", code, explanation.title, explanation);
+        }
+        None => {
+            eprintln!("error: {} is not an error code", code);
+            process::exit(USAGE_EXIT_CODE);
+        }
+    }
 }
 
 fn run_repl() {
-    loop {
-        println!("> ");
-        todo!()
+    if let Err(err) = Repl::new().run() {
+        eprintln!("{}", err);
+        process::exit(1);
     }
 }
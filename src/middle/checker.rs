@@ -1,85 +1,1391 @@
+use super::exhaustiveness::{self, Pat};
 use super::id_table::{DeclOrId, IdentificationTable};
-use super::stdenv::{self};
+use super::stdenv::{self, STDENV};
 use super::visitor::VisitorMut;
-use crate::error::report_error;
+use crate::diagnostics::{codes::ErrorCode, Diagnostics};
 use crate::error::*;
 use crate::front::ast::*;
+use crate::front::source_file::Span;
+use std::collections::BTreeSet;
+use std::mem;
 
+#[derive(Clone)]
 pub struct Checker {
     id_table: IdentificationTable,
+    /// the specs of the operators, kept apart from `id_table` so that a
+    /// variable or function named like one, e.g. `sub`, does not shadow it.
+    op_table: IdentificationTable,
+    /// the number of loops that enclose the expr being type-checked, within
+    /// the innermost function.
+    loop_depth: usize,
+    /// the functions whose bodies enclose the expr being type-checked,
+    /// innermost last.
+    functions: Vec<FunctionScope>,
+    /// the structs and the enums declared so far, indexed by their ids (see
+    /// `Type::NamedType`). Unlike the id table, this keeps the declarations
+    /// of the types of values that outlive the scopes they are declared in.
+    types: Vec<Decl>,
+    /// the spans of the names of the types in `types`, by the same ids.
+    type_spans: Vec<Span>,
+}
+
+/// The scope of the body of a function or a closure that is being
+/// type-checked.
+#[derive(Clone)]
+struct FunctionScope {
+    /// the level of the scope of the parameters.
+    level: isize,
+    /// whether the body is that of a closure, which can capture variables.
+    closure: bool,
+    /// the variables and functions declared outside of the body that it
+    /// refers to.
+    captures: BTreeSet<String>,
+}
+
+impl Default for Checker {
+    fn default() -> Self {
+        Checker::new()
+    }
 }
 
 impl Checker {
     pub fn new() -> Self {
         let mut id_table = IdentificationTable::new();
-        stdenv::load_stdenv(&mut id_table);
-        Checker { id_table }
+        let mut op_table = IdentificationTable::new();
+        stdenv::load_stdenv(&mut id_table, &mut op_table);
+        Checker {
+            id_table,
+            op_table,
+            loop_depth: 0,
+            functions: Vec::new(),
+            types: Vec::new(),
+            type_spans: Vec::new(),
+        }
+    }
+
+    /// Type-check every expression in the ast, reporting the errors found to
+    /// `diagnostics`. An error in one expression does not stop the remaining
+    /// expressions from being checked.
+    ///
+    /// Returns the type of the last expression in the ast, if it could be
+    /// determined.
+    pub fn check(&mut self, ast: &mut Ast, diagnostics: &mut Diagnostics) -> Option<Type> {
+        let mut typ = None;
+
+        for expr in &mut ast.exprs {
+            typ = match self.visit_expr(expr) {
+                Ok(typ) => typ,
+                Err(err) => {
+                    diagnostics.push(err);
+                    None
+                }
+            };
+        }
+
+        typ
+    }
+
+    pub fn id_table(&self) -> &IdentificationTable {
+        &self.id_table
+    }
+
+    fn type_name(typ: &Option<Type>) -> String {
+        match *typ {
+            Some(ref typ) => typ.to_string(),
+            None => "unknown".to_owned(),
+        }
+    }
+
+    /// The key of the struct or the enum `name` in the id table, which keeps
+    /// types apart from variables and functions (a key with a space is not a
+    /// name).
+    fn type_key(name: &str) -> String {
+        format!("type {}", name)
+    }
+
+    /// The key that marks `name` as a function declared by the program in
+    /// the scope it is saved in, which the builtin functions lack.
+    fn fn_key(name: &str) -> String {
+        format!("fn {}", name)
+    }
+
+    /// The id of the struct or the enum that `name` refers to in the current
+    /// scope, if any.
+    fn type_id(&self, name: &str) -> Option<usize> {
+        match self.id_table.get_attr(&Checker::type_key(name)) {
+            Some(DeclOrId::Decl(Decl::StructDecl(ref struct_decl))) => Some(struct_decl.id),
+            Some(DeclOrId::Decl(Decl::EnumDecl(ref enum_decl))) => Some(enum_decl.id),
+            _ => None,
+        }
+    }
+
+    fn get_struct(&self, typ: &Type) -> Option<&StructDecl> {
+        match self.named_decl(typ) {
+            Some(Decl::StructDecl(ref struct_decl)) => Some(struct_decl),
+            _ => None,
+        }
+    }
+
+    fn get_enum(&self, typ: &Type) -> Option<&EnumDecl> {
+        match *typ {
+            Type::NamedType(_, Some(id)) => self.get_enum_by_id(id),
+            _ => None,
+        }
+    }
+
+    fn get_enum_by_id(&self, id: usize) -> Option<&EnumDecl> {
+        match self.types.get(id) {
+            Some(Decl::EnumDecl(ref enum_decl)) => Some(enum_decl),
+            _ => None,
+        }
+    }
+
+    fn named_decl(&self, typ: &Type) -> Option<&Decl> {
+        match *typ {
+            Type::NamedType(_, Some(id)) => self.types.get(id),
+            _ => None,
+        }
+    }
+
+    /// Declare the struct or the enum `name` in the current scope, with a new
+    /// id that `decl` is made with. A type that shadows another one of the
+    /// same name is a new type, but a type cannot be declared twice in the
+    /// same scope, since the values of the first one would then pass for
+    /// values of the second.
+    fn declare_type(
+        &mut self,
+        name: &Identifier,
+        decl: impl FnOnce(usize) -> Decl,
+    ) -> ExprResult<usize> {
+        if let Some((level, _)) = self.id_table.lookup(&Checker::type_key(&name.spelling)) {
+            if level == self.id_table.level {
+                return Err(ExprError::with_span(
+                    ErrorCode::DuplicateType,
+                    format!("type {} is already declared in this scope", name.spelling),
+                    name.span.clone(),
+                )
+                .with_help("give it another name".to_owned()));
+            }
+        }
+
+        let id = self.types.len();
+        self.types.push(decl(id));
+        self.type_spans.push(name.span.clone());
+        self.update_type(&name.spelling, id);
+        Ok(id)
+    }
+
+    /// The ids of the first two different types in `lhs` and `rhs` that have
+    /// the same name, e.g., a struct and a struct that shadows it, which are
+    /// shown alike in the messages.
+    fn same_named_types(lhs: &Type, rhs: &Type) -> Option<(usize, usize)> {
+        match (lhs, rhs) {
+            (Type::NamedType(lhs_name, Some(lhs_id)), Type::NamedType(rhs_name, Some(rhs_id)))
+                if lhs_name == rhs_name && lhs_id != rhs_id =>
+            {
+                Some((*lhs_id, *rhs_id))
+            }
+            (Type::ListType(lhs), Type::ListType(rhs))
+            | (Type::OptionType(lhs), Type::OptionType(rhs)) => Checker::same_named_types(lhs, rhs),
+            (Type::TupleType(lhs), Type::TupleType(rhs)) => lhs
+                .iter()
+                .zip(rhs)
+                .find_map(|(lhs, rhs)| Checker::same_named_types(lhs, rhs)),
+            (Type::FnType(lhs_params, lhs_ret), Type::FnType(rhs_params, rhs_ret)) => lhs_params
+                .iter()
+                .zip(rhs_params)
+                .find_map(|(lhs, rhs)| Checker::same_named_types(lhs, rhs))
+                .or_else(|| Checker::same_named_types(lhs_ret, rhs_ret)),
+            _ => None,
+        }
+    }
+
+    /// Point at the declarations of the types that `expected` and `actual`
+    /// name alike, if any, since the message alone cannot tell them apart.
+    fn label_same_named_types(&self, err: ExprError, expected: &Type, actual: &Type) -> ExprError {
+        match Checker::same_named_types(expected, actual) {
+            Some((expected_id, actual_id)) => err
+                .with_label(
+                    self.type_spans[expected_id].clone(),
+                    "the expected type is declared here".to_owned(),
+                )
+                .with_label(
+                    self.type_spans[actual_id].clone(),
+                    "the type of the value is declared here".to_owned(),
+                )
+                .with_note("these are different types that have the same name".to_owned()),
+            None => err,
+        }
+    }
+
+    /// Save the declaration of the type `name` with the id `id` in the id
+    /// table again, once it has changed.
+    fn update_type(&mut self, name: &str, id: usize) {
+        self.id_table.save_attr(
+            &Checker::type_key(name),
+            DeclOrId::Decl(self.types[id].clone()),
+        );
+    }
+
+    /// Validate that the structs and the enums named in the type `typ` (which
+    /// is written at `span`) have been declared, and fill in the ids of their
+    /// declarations.
+    fn resolve_type(&self, typ: &mut Type, span: &Span) -> ExprResult<()> {
+        match *typ {
+            Type::NamedType(ref name, ref mut id) => match self.type_id(name) {
+                Some(decl_id) => {
+                    *id = Some(decl_id);
+                    Ok(())
+                }
+                None => Err(ExprError::with_span(
+                    ErrorCode::UnknownType,
+                    format!("cannot find type {:?}", name),
+                    span.clone(),
+                )
+                .with_help(format!(
+                    "declare it first, e.g., struct {} {{ ... }} or enum {} {{ ... }}",
+                    name, name
+                ))),
+            },
+            Type::FnType(ref mut param_typs, ref mut ret_typ) => {
+                for param_typ in param_typs {
+                    self.resolve_type(param_typ, span)?;
+                }
+                self.resolve_type(ret_typ, span)
+            }
+            Type::ListType(ref mut elem_typ) | Type::OptionType(ref mut elem_typ) => {
+                self.resolve_type(elem_typ, span)
+            }
+            Type::TupleType(ref mut elem_typs) => {
+                for elem_typ in elem_typs {
+                    self.resolve_type(elem_typ, span)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Validate that the field expr is a field of a record, since the
+    /// elements of tuples cannot be assigned to.
+    fn check_field_target(field_expr: &FieldExpr) -> ExprResult<()> {
+        match field_expr.expr.typ() {
+            Some(Type::NamedType(..)) => Ok(()),
+            _ => Err(ExprError::with_span(
+                ErrorCode::InvalidAssignmentTarget,
+                "the elements of a tuple cannot be assigned to".to_owned(),
+                field_expr.span.clone(),
+            )
+            .with_help("assign a new tuple to the variable instead".to_owned())),
+        }
+    }
+
+    /// Look up `name` in the id table. If it is declared outside of the body
+    /// of a function that it is used in, it is a capture of that function -
+    /// closures can capture variables and functions, but functions can only
+    /// capture other functions.
+    fn lookup(&mut self, name: &str, span: &Span) -> ExprResult<Option<DeclOrId>> {
+        let (level, attr) = match self.id_table.lookup(name) {
+            Some((level, attr)) => (level, attr.clone()),
+            None => return Ok(None),
+        };
+
+        for function in self.functions.iter_mut().rev() {
+            if level >= function.level {
+                break;
+            }
+
+            if let (DeclOrId::Id(_), false) = (&attr, function.closure) {
+                return Err(ExprError::with_span(
+                    ErrorCode::CannotCaptureVariable,
+                    format!(
+                        "variable {:?} is declared outside of the function that uses it",
+                        name
+                    ),
+                    span.clone(),
+                )
+                .with_help(
+                    "pass it to the function as an argument, or use a closure instead".to_owned(),
+                ));
+            }
+
+            function.captures.insert(name.to_owned());
+        }
+
+        Ok(Some(attr))
     }
 
-    pub fn check(&mut self, ast: &mut Ast) {
-        self.visit_ast(ast);
+    /// Type-check the body of a function (which has a return type) or a
+    /// closure (which does not), with its parameters (whose types have been
+    /// set and whose names must differ) declared in a scope of their own and
+    /// outside of any loop. Returns the type of the body along with the names
+    /// that it captures.
+    fn check_function_body(
+        &mut self,
+        params: Vec<&mut VnameExpr>,
+        body: &mut Expr,
+        ret_typ: Option<&Type>,
+    ) -> ExprResult<(Option<Type>, Vec<String>)> {
+        for (idx, param) in params.iter().enumerate() {
+            if let Some(prev) = params[..idx]
+                .iter()
+                .find(|prev| prev.id.spelling == param.id.spelling)
+            {
+                return Err(ExprError::with_span(
+                    ErrorCode::DuplicateParameter,
+                    format!(
+                        "parameter {:?} is declared more than once",
+                        param.id.spelling
+                    ),
+                    param.span.clone(),
+                )
+                .with_label(prev.span.clone(), "first declared here".to_owned()));
+            }
+        }
+
+        self.id_table.open_scope();
+        for vname in params {
+            vname.id.typ = vname.typ.clone();
+            self.id_table
+                .save_attr(&vname.id.spelling, DeclOrId::Id(vname.id.clone()));
+        }
+
+        self.functions.push(FunctionScope {
+            level: self.id_table.level,
+            closure: ret_typ.is_none(),
+            captures: BTreeSet::new(),
+        });
+        let loop_depth = mem::replace(&mut self.loop_depth, 0);
+        let body_typ = match ret_typ {
+            Some(ret_typ) => self.visit_expr_expecting(body, ret_typ),
+            None => self.visit_expr(body),
+        };
+        self.loop_depth = loop_depth;
+        let function = self.functions.pop().unwrap();
+
+        self.id_table.close_scope();
+
+        Ok((body_typ?, function.captures.into_iter().collect()))
     }
 
-    fn get_unary_op_spec(&self, op: &UnaryOperator) -> &DeclOrId {
+    /// Type-check an expr whose type is expected to be `expected`, which is
+    /// used to infer the types of the parameters of a closure, of the
+    /// elements of an empty list and of none (that may be the last expr of a
+    /// block, a branch of an if, an arm of a match or the arg of some).
+    fn visit_expr_expecting(
+        &mut self,
+        expr: &mut Expr,
+        expected: &Type,
+    ) -> ExprResult<Option<Type>> {
+        match (expr, expected) {
+            (Expr::IfExpr(ref mut if_expr), _) => self.check_if(if_expr, Some(expected)),
+            (Expr::TernaryExpr(ref mut ternary_expr), _) => {
+                self.check_ternary(ternary_expr, Some(expected))
+            }
+            (Expr::MatchExpr(ref mut match_expr), _) => {
+                self.check_match(match_expr, Some(expected))
+            }
+            (Expr::CallExpr(ref mut call_expr), _) => self.check_call(call_expr, Some(expected)),
+            (Expr::LambdaExpr(ref mut lambda_expr), Type::FnType(ref param_typs, _)) => {
+                self.check_lambda(lambda_expr, Some(param_typs))
+            }
+            (Expr::BlockExpr(ref mut block_expr), _) => {
+                self.check_block(block_expr, Some(expected))
+            }
+            (Expr::TupleExpr(ref mut tuple_expr), Type::TupleType(ref elem_typs))
+                if elem_typs.len() == tuple_expr.elems.len() =>
+            {
+                self.check_tuple(tuple_expr, Some(elem_typs))
+            }
+            (Expr::ListExpr(ref mut list_expr), Type::ListType(ref elem_typ))
+                if **elem_typ != Type::AnyType =>
+            {
+                self.check_list(list_expr, Some(elem_typ))
+            }
+            (Expr::NoneExpr(ref mut none_expr), Type::OptionType(ref elem_typ))
+                if **elem_typ != Type::AnyType =>
+            {
+                none_expr.typ = Some(expected.clone());
+                Ok(none_expr.typ.clone())
+            }
+            (expr, _) => self.visit_expr(expr),
+        }
+    }
+
+    /// Type-check an expr that is expected to have the type `expected`, if
+    /// given, see `visit_expr_expecting`.
+    fn visit_expr_with(
+        &mut self,
+        expr: &mut Expr,
+        expected: Option<&Type>,
+    ) -> ExprResult<Option<Type>> {
+        match expected {
+            Some(expected) => self.visit_expr_expecting(expr, expected),
+            None => self.visit_expr(expr),
+        }
+    }
+
+    /// Whether the type of `expr` can only come from the type that it is
+    /// expected to have, e.g., none or an empty list. Such an expr is
+    /// type-checked after the exprs that must have the same type as it, e.g.,
+    /// the other branch of an if, so that it is expected to have their type.
+    fn needs_expected(expr: &Expr) -> bool {
+        match *expr {
+            Expr::NoneExpr(_) => true,
+            Expr::ListExpr(ref list_expr) => list_expr.elems.iter().all(Checker::needs_expected),
+            Expr::BlockExpr(ref block_expr) => {
+                block_expr.exprs.last().is_some_and(Checker::needs_expected)
+            }
+            Expr::IfExpr(ref if_expr) => {
+                Checker::needs_expected(&if_expr.then_branch)
+                    && if_expr
+                        .else_branch
+                        .as_ref()
+                        .is_some_and(|else_branch| Checker::needs_expected(else_branch))
+            }
+            Expr::TernaryExpr(ref ternary_expr) => {
+                Checker::needs_expected(&ternary_expr.then_expr)
+                    && Checker::needs_expected(&ternary_expr.else_expr)
+            }
+            Expr::MatchExpr(ref match_expr) => match_expr
+                .arms
+                .iter()
+                .all(|arm| Checker::needs_expected(&arm.body)),
+            _ => false,
+        }
+    }
+
+    /// The order in which to type-check `exprs`, which must all have the same
+    /// type: without `expected`, the first one whose type does not have to
+    /// come from the others goes first (see `needs_expected`).
+    fn check_order<'a>(
+        exprs: impl Iterator<Item = &'a Expr>,
+        expected: Option<&Type>,
+    ) -> Vec<usize> {
+        let needs_expected: Vec<bool> = exprs.map(Checker::needs_expected).collect();
+        let first = match expected {
+            Some(_) => 0,
+            None => needs_expected
+                .iter()
+                .position(|needs_expected| !needs_expected)
+                .unwrap_or(0),
+        };
+
+        let mut order: Vec<usize> = (0..needs_expected.len()).collect();
+        order.sort_by_key(|&idx| idx != first);
+        order
+    }
+
+    /// Type-check two branches, which must have the same type, that they are
+    /// expected to have if given, or else the type of the one that is
+    /// type-checked first (see `check_order`). Returns the type of each.
+    fn check_branches(
+        &mut self,
+        first: &mut Expr,
+        second: &mut Expr,
+        expected: Option<&Type>,
+    ) -> ExprResult<(Option<Type>, Option<Type>)> {
+        if Checker::check_order([&*first, &*second].into_iter(), expected)[0] == 1 {
+            let second_typ = self.visit_expr(second)?;
+            let first_typ = self.visit_expr_with(first, second_typ.as_ref())?;
+            return Ok((first_typ, second_typ));
+        }
+
+        let first_typ = self.visit_expr_with(first, expected)?;
+        let second_typ = self.visit_expr_with(second, expected.or(first_typ.as_ref()))?;
+        Ok((first_typ, second_typ))
+    }
+
+    /// Type-check a block, whose last expr is expected to have the type
+    /// `expected`, if given.
+    fn check_block(
+        &mut self,
+        block_expr: &mut BlockExpr,
+        expected: Option<&Type>,
+    ) -> ExprResult<Option<Type>> {
+        self.id_table.open_scope();
+
+        let mut typ = Ok(Some(Type::UnitType));
+        let count = block_expr.exprs.len();
+        for (idx, expr) in block_expr.exprs.iter_mut().enumerate() {
+            typ = match expected {
+                Some(expected) if idx + 1 == count => self.visit_expr_expecting(expr, expected),
+                _ => self.visit_expr(expr),
+            };
+            if typ.is_err() {
+                break;
+            }
+        }
+
+        self.id_table.close_scope();
+
+        block_expr.typ = typ?;
+        Ok(block_expr.typ.clone())
+    }
+
+    /// Type-check an if, whose branches are expected to have the type
+    /// `expected`, if given, see `visit_if_expr`.
+    fn check_if(
+        &mut self,
+        if_expr: &mut IfExpr,
+        expected: Option<&Type>,
+    ) -> ExprResult<Option<Type>> {
+        self.check_condition(&mut if_expr.cond, "an if")?;
+
+        if_expr.typ = match if_expr.else_branch {
+            Some(ref mut else_branch) => {
+                let (then_typ, else_typ) =
+                    self.check_branches(&mut if_expr.then_branch, else_branch, expected)?;
+                if then_typ != else_typ {
+                    return Err(ExprError::with_span(
+                        ErrorCode::BranchTypeMismatch,
+                        format!(
+                            "the else branch has type {}, but the if branch has type {}",
+                            Checker::type_name(&else_typ),
+                            Checker::type_name(&then_typ)
+                        ),
+                        else_branch.span().clone(),
+                    )
+                    .with_label(
+                        if_expr.then_branch.span().clone(),
+                        format!("expected {} due to this", Checker::type_name(&then_typ)),
+                    ));
+                }
+                then_typ
+            }
+            None => {
+                self.visit_expr(&mut if_expr.then_branch)?;
+                Some(Type::UnitType)
+            }
+        };
+
+        Ok(if_expr.typ.clone())
+    }
+
+    /// Type-check a ?:, whose arms are expected to have the type `expected`,
+    /// if given, see `visit_ternary_expr`.
+    fn check_ternary(
+        &mut self,
+        ternary_expr: &mut TernaryExpr,
+        expected: Option<&Type>,
+    ) -> ExprResult<Option<Type>> {
+        self.check_condition(&mut ternary_expr.cond, "a ?:")?;
+
+        let (then_typ, else_typ) = self.check_branches(
+            &mut ternary_expr.then_expr,
+            &mut ternary_expr.else_expr,
+            expected,
+        )?;
+        if then_typ != else_typ {
+            return Err(ExprError::with_span(
+                ErrorCode::BranchTypeMismatch,
+                format!(
+                    "the arm after : has type {}, but the arm after ? has type {}",
+                    Checker::type_name(&else_typ),
+                    Checker::type_name(&then_typ)
+                ),
+                ternary_expr.else_expr.span().clone(),
+            )
+            .with_label(
+                ternary_expr.then_expr.span().clone(),
+                format!("expected {} due to this", Checker::type_name(&then_typ)),
+            ));
+        }
+
+        ternary_expr.typ = then_typ;
+        Ok(ternary_expr.typ.clone())
+    }
+
+    /// Type-check a match, whose arms are expected to have the type
+    /// `expected`, if given, see `visit_match_expr`.
+    fn check_match(
+        &mut self,
+        match_expr: &mut MatchExpr,
+        expected: Option<&Type>,
+    ) -> ExprResult<Option<Type>> {
+        let typ = self.visit_expr(&mut match_expr.expr)?.ok_or_else(|| {
+            ExprError::with_span(
+                ErrorCode::UnknownOperandType,
+                "could not determine type of the matched expression".to_owned(),
+                match_expr.expr.span().clone(),
+            )
+        })?;
+        let typs = [typ.clone()];
+
+        let mut rows = Vec::new();
+        let mut arm_bindings = Vec::new();
+        for arm in &mut match_expr.arms {
+            let mut bindings = Vec::new();
+            self.check_pattern(&mut arm.pattern, &typ, &mut bindings)?;
+
+            let row = vec![Pat::from(&arm.pattern)];
+            if !exhaustiveness::is_useful(&rows, &row, &typs, &|id| {
+                self.get_enum_by_id(id).cloned()
+            }) {
+                return Err(ExprError::with_span(
+                    ErrorCode::UnreachablePattern,
+                    "this arm is never taken, since the arms before it match every value that it matches"
+                        .to_owned(),
+                    arm.pattern.span().clone(),
+                ));
+            }
+            rows.push(row);
+            arm_bindings.push(bindings);
+        }
+
+        let mut first_body: Option<(Option<Type>, Span)> = None;
+        let order = Checker::check_order(match_expr.arms.iter().map(|arm| &arm.body), expected);
+        for idx in order {
+            let arm = &mut match_expr.arms[idx];
+            self.id_table.open_scope();
+            for id in mem::take(&mut arm_bindings[idx]) {
+                self.id_table
+                    .save_attr(&id.spelling.clone(), DeclOrId::Id(id));
+            }
+            let expected = match first_body {
+                Some((Some(ref first_typ), _)) => Some(first_typ),
+                _ => expected,
+            };
+            let body_typ = self.visit_expr_with(&mut arm.body, expected);
+            self.id_table.close_scope();
+            let body_typ = body_typ?;
+
+            match first_body {
+                Some((ref first_typ, ref first_span)) if *first_typ != body_typ => {
+                    return Err(ExprError::with_span(
+                        ErrorCode::BranchTypeMismatch,
+                        format!(
+                            "this arm has type {}, but the first arm has type {}",
+                            Checker::type_name(&body_typ),
+                            Checker::type_name(first_typ)
+                        ),
+                        arm.body.span().clone(),
+                    )
+                    .with_label(
+                        first_span.clone(),
+                        format!("expected {} due to this", Checker::type_name(first_typ)),
+                    ));
+                }
+                Some(_) => {}
+                None => first_body = Some((body_typ, arm.body.span().clone())),
+            }
+        }
+
+        if let Some(witness) =
+            exhaustiveness::missing(&rows, &typs, &|id| self.get_enum_by_id(id).cloned())
+        {
+            let err = match witness[0].as_str() {
+                "_" => ExprError::with_span(
+                    ErrorCode::NonExhaustiveMatch,
+                    format!("the arms of the match do not cover every {}", typ),
+                    match_expr.expr.span().clone(),
+                )
+                .with_help("add a _ => ... arm last".to_owned()),
+                missing => ExprError::with_span(
+                    ErrorCode::NonExhaustiveMatch,
+                    format!("the arms of the match do not cover {}", missing),
+                    match_expr.expr.span().clone(),
+                )
+                .with_help(format!(
+                    "add an arm for it, e.g., {} => ..., or a _ => ... arm last",
+                    missing
+                )),
+            };
+            return Err(err);
+        }
+
+        match_expr.typ = first_body.and_then(|(typ, _)| typ);
+        Ok(match_expr.typ.clone())
+    }
+
+    /// Type-check a call, which is expected to have the type `expected`, if
+    /// given, see `visit_call_expr`.
+    fn check_call(
+        &mut self,
+        call_expr: &mut CallExpr,
+        expected: Option<&Type>,
+    ) -> ExprResult<Option<Type>> {
+        let not_callable = |callee: &Expr| {
+            ExprError::with_span(
+                ErrorCode::NotCallable,
+                "only functions can be called".to_owned(),
+                callee.span().clone(),
+            )
+        };
+
+        let mut arg_typs = Vec::new();
+        let func_decl = match *call_expr.callee {
+            Expr::VnameExpr(ref vname) => match self.lookup(&vname.id.spelling, &vname.span)? {
+                Some(DeclOrId::Decl(Decl::FunctionDecl(func_decl))) => {
+                    match call_expr.args.first_mut() {
+                        Some(arg)
+                            if STDENV
+                                .contains_key(format!("{}_list", vname.id.spelling).as_str()) =>
+                        {
+                            let arg_typ = self.visit_expr(arg)?;
+                            let overload = arg_typ.as_ref().and_then(|arg_typ| {
+                                Checker::get_list_overload(&vname.id.spelling, &func_decl, arg_typ)
+                            });
+                            arg_typs.push(arg_typ);
+                            Some(overload.unwrap_or(func_decl))
+                        }
+                        _ => Some(func_decl),
+                    }
+                }
+                Some(DeclOrId::Decl(Decl::VariantDecl(variant_decl)))
+                    if !variant_decl.payload_typs.is_empty() =>
+                {
+                    Some(FunctionDecl::new(
+                        variant_decl.payload_typs,
+                        variant_decl.enum_typ,
+                    ))
+                }
+                Some(DeclOrId::Id(_)) => None,
+                Some(_) => return Err(not_callable(&call_expr.callee)),
+                None => {
+                    return Err(ExprError::with_span(
+                        ErrorCode::UndeclaredVariable,
+                        format!("cannot find function {:?}", vname.id.spelling),
+                        vname.span.clone(),
+                    )
+                    .with_help(
+                        "a function can only call itself and the functions declared before it"
+                            .to_owned(),
+                    ))
+                }
+            },
+            _ => None,
+        };
+
+        let func_decl = match func_decl {
+            Some(func_decl) => func_decl,
+            None => match self.visit_expr(&mut call_expr.callee)? {
+                Some(Type::FnType(param_typs, ret_typ)) => FunctionDecl::new(param_typs, *ret_typ),
+                _ => return Err(not_callable(&call_expr.callee)),
+            },
+        };
+
+        if call_expr.args.len() != func_decl.param_typs.len() {
+            return Err(ExprError::with_span(
+                ErrorCode::ArgumentCountMismatch,
+                format!(
+                    "this function takes {} argument(s), but {} were given",
+                    func_decl.param_typs.len(),
+                    call_expr.args.len()
+                ),
+                call_expr.span.clone(),
+            ));
+        }
+
+        // the type that the call is expected to have suggests the type of the
+        // args of a builtin, e.g., some(x), whose return type has any in it.
+        let mut hint = None;
+        if let Some(expected) = expected {
+            if !Checker::unify(&func_decl.ret_typ, expected, &mut hint) {
+                hint = None;
+            }
+        }
+
+        let mut any = None;
+        for (idx, (arg, param_typ)) in call_expr
+            .args
+            .iter_mut()
+            .zip(&func_decl.param_typs)
+            .enumerate()
+        {
+            let param_typ = Checker::substitute(param_typ, &any);
+            let arg_typ = match arg_typs.get(idx) {
+                Some(arg_typ) => arg_typ.clone(),
+                None => self.visit_expr_expecting(
+                    arg,
+                    &Checker::substitute(&param_typ, &any.clone().or(hint.clone())),
+                )?,
+            };
+            let arg_typ = arg_typ.ok_or_else(|| {
+                ExprError::with_span(
+                    ErrorCode::UnknownOperandType,
+                    "for call expr, arg type is unavailable".to_owned(),
+                    arg.span().clone(),
+                )
+            })?;
+
+            if !Checker::unify(&param_typ, &arg_typ, &mut any) {
+                let err = ExprError::with_span(
+                    ErrorCode::ArgumentTypeMismatch,
+                    format!(
+                        "argument has type {}, but the parameter has type {}",
+                        arg_typ, param_typ
+                    ),
+                    arg.span().clone(),
+                );
+                return Err(self.label_same_named_types(err, &param_typ, &arg_typ));
+            }
+        }
+
+        call_expr.typ = Some(Checker::substitute(&func_decl.ret_typ, &any));
+        Ok(call_expr.typ.clone())
+    }
+
+    /// Type-check a list, whose elements are expected to have the type
+    /// `expected`, if given, or else the type of the first element (that is
+    /// type-checked, see `check_order`). The type of an empty list can only
+    /// come from `expected`.
+    fn check_list(
+        &mut self,
+        list_expr: &mut ListExpr,
+        expected: Option<&Type>,
+    ) -> ExprResult<Option<Type>> {
+        let mut first: Option<(Type, Span)> = None;
+        for idx in Checker::check_order(list_expr.elems.iter(), expected) {
+            let elem = &mut list_expr.elems[idx];
+            let typ = match (expected, &first) {
+                (Some(expected), _) => self.visit_expr_expecting(elem, expected)?,
+                (None, Some((ref first_typ, _))) => self.visit_expr_expecting(elem, first_typ)?,
+                (None, None) => self.visit_expr(elem)?,
+            };
+            let typ = typ.ok_or_else(|| {
+                ExprError::with_span(
+                    ErrorCode::UnknownOperandType,
+                    "for list expr, elem type is unavailable".to_owned(),
+                    elem.span().clone(),
+                )
+            })?;
+
+            match first {
+                Some((ref first_typ, ref first_span)) if *first_typ != typ => {
+                    return Err(ExprError::with_span(
+                        ErrorCode::ListElementTypeMismatch,
+                        format!(
+                            "this element has type {}, but the first element of the list has type {}",
+                            typ, first_typ
+                        ),
+                        elem.span().clone(),
+                    )
+                    .with_label(
+                        first_span.clone(),
+                        format!("expected {} due to this", first_typ),
+                    ))
+                }
+                Some(_) => {}
+                None => first = Some((typ, elem.span().clone())),
+            }
+        }
+
+        let elem_typ = match (first, expected) {
+            (Some((typ, _)), _) => typ,
+            (None, Some(expected)) => expected.clone(),
+            (None, None) => {
+                return Err(ExprError::with_span(
+                    ErrorCode::CannotInferListType,
+                    "cannot infer the element type of an empty list".to_owned(),
+                    list_expr.span.clone(),
+                )
+                .with_help("give the variable a type, e.g., let xs: List<int> = []".to_owned()))
+            }
+        };
+
+        list_expr.typ = Some(Type::ListType(Box::new(elem_typ)));
+        Ok(list_expr.typ.clone())
+    }
+
+    /// Type-check a tuple, whose elements are expected to have the types
+    /// `expected`, if given.
+    fn check_tuple(
+        &mut self,
+        tuple_expr: &mut TupleExpr,
+        expected: Option<&[Type]>,
+    ) -> ExprResult<Option<Type>> {
+        let mut elem_typs = Vec::new();
+        for (idx, elem) in tuple_expr.elems.iter_mut().enumerate() {
+            let typ = match expected {
+                Some(expected) => self.visit_expr_expecting(elem, &expected[idx])?,
+                None => self.visit_expr(elem)?,
+            };
+            elem_typs.push(typ.ok_or_else(|| {
+                ExprError::with_span(
+                    ErrorCode::UnknownOperandType,
+                    "for tuple expr, elem type is unavailable".to_owned(),
+                    elem.span().clone(),
+                )
+            })?);
+        }
+
+        tuple_expr.typ = Some(Type::TupleType(elem_typs));
+        Ok(tuple_expr.typ.clone())
+    }
+
+    /// Validate that a value of type `typ` can be assigned to `target`, which
+    /// is a (type-checked) tuple of targets or one of its elements. Each
+    /// element of the value is assigned to the corresponding target.
+    fn check_destructure(&self, target: &Expr, typ: &Type) -> ExprResult<()> {
+        match *target {
+            Expr::TupleExpr(ref tuple_expr) => match *typ {
+                Type::TupleType(ref elem_typs) if elem_typs.len() == tuple_expr.elems.len() => {
+                    for (elem, elem_typ) in tuple_expr.elems.iter().zip(elem_typs) {
+                        self.check_destructure(elem, elem_typ)?;
+                    }
+                    Ok(())
+                }
+                _ => Err(ExprError::with_span(
+                    ErrorCode::DestructuringMismatch,
+                    format!(
+                        "a tuple of {} targets cannot be assigned a value of type {}",
+                        tuple_expr.elems.len(),
+                        typ
+                    ),
+                    tuple_expr.span.clone(),
+                )),
+            },
+            Expr::VnameExpr(_) | Expr::IndexExpr(_) | Expr::FieldExpr(_) => {
+                if let Expr::FieldExpr(ref field_expr) = *target {
+                    Checker::check_field_target(field_expr)?;
+                }
+                self.check_assignable(target)?;
+                if target.typ().as_ref() != Some(typ) {
+                    return Err(ExprError::with_span(
+                        ErrorCode::DestructuringMismatch,
+                        format!(
+                            "this has type {}, but it is assigned a value of type {}",
+                            Checker::type_name(&target.typ()),
+                            typ
+                        ),
+                        target.span().clone(),
+                    ));
+                }
+                Ok(())
+            }
+            _ => Err(ExprError::with_span(
+                ErrorCode::InvalidAssignmentTarget,
+                "the elements of a tuple that is assigned to must be variables, index exprs or fields"
+                    .to_owned(),
+                target.span().clone(),
+            )),
+        }
+    }
+
+    /// Type-check an assignment to a tuple of targets, which destructures the
+    /// value of the rhs.
+    fn check_tuple_assign(&mut self, ass_expr: &mut AssignExpr) -> ExprResult<Option<Type>> {
+        if ass_expr.op != BinaryOperator::Assign {
+            return Err(ExprError::with_span(
+                ErrorCode::InvalidAssignmentTarget,
+                format!("cannot apply {} to a tuple", ass_expr.op),
+                ass_expr.op_span.clone(),
+            )
+            .with_help("only = can assign to a tuple of targets".to_owned()));
+        }
+
+        let lhs_typ = self.visit_expr(&mut ass_expr.target)?;
+        let rhs_typ = match lhs_typ {
+            Some(ref lhs_typ) => self.visit_expr_expecting(&mut ass_expr.expr, lhs_typ)?,
+            None => self.visit_expr(&mut ass_expr.expr)?,
+        };
+        let rhs_typ = rhs_typ.ok_or_else(|| {
+            ExprError::with_span(
+                ErrorCode::UnknownOperandType,
+                "could not determine type of rhs of assignment expression".to_owned(),
+                ass_expr.expr.span().clone(),
+            )
+        })?;
+
+        self.check_destructure(&ass_expr.target, &rhs_typ)
+            .map_err(|err| match err.code() {
+                ErrorCode::DestructuringMismatch => err.with_label(
+                    ass_expr.expr.span().clone(),
+                    format!("this has type {}", rhs_typ),
+                ),
+                _ => err,
+            })?;
+
+        ass_expr.typ = Some(rhs_typ);
+        Ok(ass_expr.typ.clone())
+    }
+
+    /// Type-check a closure, taking the types of the parameters that are not
+    /// annotated from `expected`, if given.
+    fn check_lambda(
+        &mut self,
+        lambda_expr: &mut LambdaExpr,
+        expected: Option<&[Type]>,
+    ) -> ExprResult<Option<Type>> {
+        let expected = expected.filter(|typs| typs.len() == lambda_expr.params.len());
+
+        let mut param_typs = Vec::new();
+        for (idx, param) in lambda_expr.params.iter_mut().enumerate() {
+            let typ = match (&mut param.annotation, expected) {
+                (Some((ref mut typ, ref typ_span)), _) => {
+                    self.resolve_type(typ, typ_span)?;
+                    typ.clone()
+                }
+                (None, Some(typs)) => typs[idx].clone(),
+                (None, None) => {
+                    return Err(ExprError::with_span(
+                        ErrorCode::CannotInferParamType,
+                        format!(
+                            "cannot infer the type of parameter {:?}",
+                            param.vname.id.spelling
+                        ),
+                        param.vname.span.clone(),
+                    )
+                    .with_help(format!(
+                        "give it a type, e.g., {}: int",
+                        param.vname.id.spelling
+                    )))
+                }
+            };
+            param.vname.typ = Some(typ.clone());
+            param_typs.push(typ);
+        }
+
+        let params = lambda_expr
+            .params
+            .iter_mut()
+            .map(|param| &mut param.vname)
+            .collect();
+        let (body_typ, captures) = self.check_function_body(params, &mut lambda_expr.body, None)?;
+        let body_typ = body_typ.ok_or_else(|| {
+            ExprError::with_span(
+                ErrorCode::UnknownOperandType,
+                "could not determine type of the body of the closure".to_owned(),
+                lambda_expr.body.span().clone(),
+            )
+        })?;
+
+        lambda_expr.captures = captures;
+        lambda_expr.typ = Some(Type::FnType(param_typs, Box::new(body_typ)));
+        Ok(lambda_expr.typ.clone())
+    }
+
+    /// Validate that the target of an assignment is not a variant of an enum,
+    /// nor a variable captured by the innermost closure, which only has a
+    /// copy of its value.
+    fn check_assignable(&self, target: &Expr) -> ExprResult<()> {
+        let vname = match *target {
+            Expr::VnameExpr(ref vname) => vname,
+            _ => return Ok(()),
+        };
+
+        if let Some(DeclOrId::Decl(Decl::VariantDecl(_))) =
+            self.id_table.get_attr(&vname.id.spelling)
+        {
+            return Err(ExprError::with_span(
+                ErrorCode::InvalidAssignmentTarget,
+                format!(
+                    "cannot assign to {:?}, which is a variant of an enum",
+                    vname.id.spelling
+                ),
+                vname.span.clone(),
+            ));
+        }
+
+        let function = match self.functions.last() {
+            Some(function) => function,
+            None => return Ok(()),
+        };
+
+        match self.id_table.lookup(&vname.id.spelling) {
+            Some((level, DeclOrId::Id(_))) if level < function.level => Err(ExprError::with_span(
+                ErrorCode::AssignToCapturedVariable,
+                format!(
+                    "cannot assign to {:?}, which is captured by the closure",
+                    vname.id.spelling
+                ),
+                vname.span.clone(),
+            )
+            .with_note(
+                "closures capture the values of variables when they are created".to_owned(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check a pattern against the type `typ` of the values that it is
+    /// matched with, collecting the names that it binds (with their types).
+    /// A name that is a variant of an enum is turned into a pattern for that
+    /// variant.
+    fn check_pattern(
+        &self,
+        pattern: &mut Pattern,
+        typ: &Type,
+        bindings: &mut Vec<Identifier>,
+    ) -> ExprResult<()> {
+        if let Pattern::Binding(ref vname) = *pattern {
+            if let Some(DeclOrId::Decl(Decl::VariantDecl(_))) =
+                self.id_table.get_attr(&vname.id.spelling)
+            {
+                *pattern = Pattern::Variant(VariantPattern::new(
+                    vname.id.clone(),
+                    Vec::new(),
+                    vname.span.clone(),
+                ));
+            }
+        }
+
+        match *pattern {
+            Pattern::Wildcard(_) => Ok(()),
+            Pattern::Binding(ref mut vname) => {
+                if let Some(prev) = bindings
+                    .iter()
+                    .find(|prev| prev.spelling == vname.id.spelling)
+                {
+                    return Err(ExprError::with_span(
+                        ErrorCode::DuplicateBinding,
+                        format!(
+                            "{:?} is bound more than once in this pattern",
+                            vname.id.spelling
+                        ),
+                        vname.span.clone(),
+                    )
+                    .with_label(prev.span.clone(), "first bound here".to_owned()));
+                }
+
+                vname.typ = Some(typ.clone());
+                vname.id.typ = Some(typ.clone());
+                bindings.push(vname.id.clone());
+                Ok(())
+            }
+            Pattern::Bool(_) if *typ == Type::BoolType => Ok(()),
+            Pattern::Int(_) if *typ == Type::IntType => Ok(()),
+            Pattern::Str(_) if *typ == Type::StringType => Ok(()),
+            Pattern::Variant(ref mut variant) => {
+                let payload_typs = self
+                    .get_enum(typ)
+                    .and_then(|enum_decl| enum_decl.payload_typs(&variant.name.spelling))
+                    .cloned()
+                    .ok_or_else(|| {
+                        ExprError::with_span(
+                            ErrorCode::PatternTypeMismatch,
+                            format!(
+                                "{} is not a variant of {}, the type of the matched value",
+                                variant.name.spelling, typ
+                            ),
+                            variant.name.span.clone(),
+                        )
+                    })?;
+
+                if payload_typs.len() != variant.args.len() {
+                    return Err(ExprError::with_span(
+                        ErrorCode::PatternTypeMismatch,
+                        format!(
+                            "variant {} has {} value(s) in its payload, but the pattern has {}",
+                            variant.name.spelling,
+                            payload_typs.len(),
+                            variant.args.len()
+                        ),
+                        variant.span.clone(),
+                    ));
+                }
+
+                for (arg, arg_typ) in variant.args.iter_mut().zip(&payload_typs) {
+                    self.check_pattern(arg, arg_typ, bindings)?;
+                }
+                Ok(())
+            }
+            ref pattern => Err(ExprError::with_span(
+                ErrorCode::PatternTypeMismatch,
+                format!("this pattern cannot match a value of type {}", typ),
+                pattern.span().clone(),
+            )),
+        }
+    }
+
+    /// Type-check `opt ?? default`, given the type of the option: the default
+    /// must have the type of the value of the option, which is the type of
+    /// the expr.
+    fn check_coalesce(
+        &mut self,
+        bin_expr: &mut BinaryExpr,
+        opt_typ: Type,
+    ) -> ExprResult<Option<Type>> {
+        let elem_typ = match opt_typ {
+            Type::OptionType(elem_typ) => *elem_typ,
+            typ => {
+                return Err(ExprError::with_span(
+                    ErrorCode::OperandTypeMismatch,
+                    format!("the lhs of ?? has type {}, but it must be an option", typ),
+                    bin_expr.lhs.span().clone(),
+                )
+                .with_label(
+                    bin_expr.op_span.clone(),
+                    "expected an option due to this operator".to_owned(),
+                ))
+            }
+        };
+
+        let default_typ = self.visit_expr_expecting(&mut bin_expr.rhs, &elem_typ)?;
+        if default_typ.as_ref() != Some(&elem_typ) {
+            return Err(ExprError::with_span(
+                ErrorCode::OperandTypeMismatch,
+                format!(
+                    "the default has type {}, but the option is of {}",
+                    Checker::type_name(&default_typ),
+                    elem_typ
+                ),
+                bin_expr.rhs.span().clone(),
+            )
+            .with_label(
+                bin_expr.lhs.span().clone(),
+                format!(
+                    "this has type {}",
+                    Type::OptionType(Box::new(elem_typ.clone()))
+                ),
+            ));
+        }
+
+        bin_expr.typ = Some(elem_typ);
+        Ok(bin_expr.typ.clone())
+    }
+
+    /// Type-check the condition of an if or a while, which must be a bool.
+    fn check_condition(&mut self, cond: &mut Expr, what: &str) -> ExprResult<()> {
+        let cond_typ = self.visit_expr(cond)?;
+        if cond_typ != Some(Type::BoolType) {
+            return Err(ExprError::with_span(
+                ErrorCode::NonBoolCondition,
+                format!(
+                    "the condition of {} must be a bool, but it has type {}",
+                    what,
+                    Checker::type_name(&cond_typ)
+                ),
+                cond.span().clone(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Type-check the body of a loop, in which break and continue are valid.
+    fn check_loop_body(&mut self, body: &mut Expr) -> ExprResult<Option<Type>> {
+        self.loop_depth += 1;
+        let typ = self.visit_expr(body);
+        self.loop_depth -= 1;
+        typ
+    }
+
+    fn unary_op_name(op: &UnaryOperator) -> &'static str {
         match *op {
-            UnaryOperator::BitwiseNot => self.id_table.get_attr("bitwise_not").unwrap(),
-            UnaryOperator::LogicalNot => self.id_table.get_attr("logical_not").unwrap(),
-            UnaryOperator::UnaryMinus => self.id_table.get_attr("unary_minus").unwrap(),
-            UnaryOperator::UnaryPlus => self.id_table.get_attr("unary_plus").unwrap(),
+            UnaryOperator::BitwiseNot => "bitwise_not",
+            UnaryOperator::LogicalNot => "logical_not",
+            UnaryOperator::PostfixDecrement | UnaryOperator::PrefixDecrement => "decrement",
+            UnaryOperator::PostfixIncrement | UnaryOperator::PrefixIncrement => "increment",
+            UnaryOperator::UnaryMinus => "unary_minus",
+            UnaryOperator::UnaryPlus => "unary_plus",
         }
     }
 
-    fn get_bin_op_spec(&self, op: &BinaryOperator) -> &DeclOrId {
+    fn bin_op_name(op: &BinaryOperator) -> &'static str {
         match *op {
-            BinaryOperator::Add => self.id_table.get_attr("add").unwrap(),
-            BinaryOperator::AddAssign => self.id_table.get_attr("add_assign").unwrap(),
-            BinaryOperator::Assign => self.id_table.get_attr("assign").unwrap(),
-            BinaryOperator::BitwiseAnd => self.id_table.get_attr("bitwise_and").unwrap(),
-            BinaryOperator::BitwiseAndAssign => {
-                self.id_table.get_attr("bitwise_and_assign").unwrap()
-            }
-            BinaryOperator::BitwiseOr => self.id_table.get_attr("bitwise_or").unwrap(),
-            BinaryOperator::BitwiseOrAssign => self.id_table.get_attr("bitwise_or_assign").unwrap(),
-            BinaryOperator::BitwiseXor => self.id_table.get_attr("bitwise_xor").unwrap(),
-            BinaryOperator::BitwiseXorAssign => {
-                self.id_table.get_attr("bitwise_xor_assign").unwrap()
-            }
-            BinaryOperator::Div => self.id_table.get_attr("div").unwrap(),
-            BinaryOperator::DivAssign => self.id_table.get_attr("div_assign").unwrap(),
-            BinaryOperator::Equal => self.id_table.get_attr("equal").unwrap(),
-            BinaryOperator::GreaterThan => self.id_table.get_attr("greater_than").unwrap(),
-            BinaryOperator::GreaterThanOrEqual => {
-                self.id_table.get_attr("greater_than_or_equal").unwrap()
-            }
-            BinaryOperator::LeftShift => self.id_table.get_attr("left_shift").unwrap(),
-            BinaryOperator::LeftShiftAssign => self.id_table.get_attr("left_shift_assign").unwrap(),
-            BinaryOperator::LessThan => self.id_table.get_attr("less_than").unwrap(),
-            BinaryOperator::LessThanOrEqual => {
-                self.id_table.get_attr("less_than_or_equal").unwrap()
-            }
-            BinaryOperator::LogicalAnd => self.id_table.get_attr("logical_and").unwrap(),
-            BinaryOperator::LogicalAndAssign => {
-                self.id_table.get_attr("logical_and_assign").unwrap()
-            }
-            BinaryOperator::LogicalOr => self.id_table.get_attr("logical_or").unwrap(),
-            BinaryOperator::LogicalOrAssign => self.id_table.get_attr("logical_or_assign").unwrap(),
-            BinaryOperator::Mod => self.id_table.get_attr("mod").unwrap(),
-            BinaryOperator::ModAssign => self.id_table.get_attr("mod_assign").unwrap(),
-            BinaryOperator::Mul => self.id_table.get_attr("mul").unwrap(),
-            BinaryOperator::MulAssign => self.id_table.get_attr("mul_assign").unwrap(),
-            BinaryOperator::NotEqual => self.id_table.get_attr("not_equal").unwrap(),
-            BinaryOperator::RightShift => self.id_table.get_attr("right_shift").unwrap(),
-            BinaryOperator::RightShiftAssign => {
-                self.id_table.get_attr("right_shift_assign").unwrap()
-            }
-            BinaryOperator::Sub => self.id_table.get_attr("sub").unwrap(),
-            BinaryOperator::SubAssign => self.id_table.get_attr("sub_assign").unwrap(),
+            BinaryOperator::Add => "add",
+            BinaryOperator::AddAssign => "add_assign",
+            BinaryOperator::Assign => "assign",
+            BinaryOperator::BitwiseAnd => "bitwise_and",
+            BinaryOperator::BitwiseAndAssign => "bitwise_and_assign",
+            BinaryOperator::BitwiseOr => "bitwise_or",
+            BinaryOperator::BitwiseOrAssign => "bitwise_or_assign",
+            BinaryOperator::BitwiseXor => "bitwise_xor",
+            BinaryOperator::BitwiseXorAssign => "bitwise_xor_assign",
+            BinaryOperator::Div => "div",
+            BinaryOperator::DivAssign => "div_assign",
+            BinaryOperator::Equal => "equal",
+            BinaryOperator::GreaterThan => "greater_than",
+            BinaryOperator::GreaterThanOrEqual => "greater_than_or_equal",
+            BinaryOperator::LeftShift => "left_shift",
+            BinaryOperator::LeftShiftAssign => "left_shift_assign",
+            BinaryOperator::LessThan => "less_than",
+            BinaryOperator::LessThanOrEqual => "less_than_or_equal",
+            BinaryOperator::Coalesce => "coalesce",
+            BinaryOperator::LogicalAnd => "logical_and",
+            BinaryOperator::LogicalAndAssign => "logical_and_assign",
+            BinaryOperator::LogicalOr => "logical_or",
+            BinaryOperator::LogicalOrAssign => "logical_or_assign",
+            BinaryOperator::Mod => "mod",
+            BinaryOperator::ModAssign => "mod_assign",
+            BinaryOperator::Mul => "mul",
+            BinaryOperator::MulAssign => "mul_assign",
+            BinaryOperator::NotEqual => "not_equal",
+            BinaryOperator::RightShift => "right_shift",
+            BinaryOperator::RightShiftAssign => "right_shift_assign",
+            BinaryOperator::Sub => "sub",
+            BinaryOperator::SubAssign => "sub_assign",
+        }
+    }
+
+    /// Get the spec for an operator, given the type of its (first) operand.
+    /// Operators are overloaded by declaring them again with the name of the
+    /// operand type as a suffix, e.g., `add_float`, which takes precedence
+    /// over the plain declaration for operands of that type.
+    fn get_op_spec(&self, name: &str, operand_typ: &Type) -> &DeclOrId {
+        self.op_table
+            .get_attr(&format!("{}_{}", name, operand_typ))
+            .unwrap_or_else(|| self.op_table.get_attr(name).unwrap())
+    }
+
+    fn get_unary_op_spec(&self, op: &UnaryOperator, elem_typ: &Type) -> &DeclOrId {
+        self.get_op_spec(Checker::unary_op_name(op), elem_typ)
+    }
+
+    fn get_bin_op_spec(&self, op: &BinaryOperator, lhs_typ: &Type) -> &DeclOrId {
+        self.get_op_spec(Checker::bin_op_name(op), lhs_typ)
+    }
+
+    /// Get the overload of the builtin function `name` for lists, if `decl`
+    /// is that builtin (rather than a function declared in the program) and
+    /// its first arg is a list. See `len_list` in the std env.
+    fn get_list_overload(name: &str, decl: &FunctionDecl, arg_typ: &Type) -> Option<FunctionDecl> {
+        if !matches!(*arg_typ, Type::ListType(_)) {
+            return None;
+        }
+
+        match (
+            STDENV.get(name),
+            STDENV.get(format!("{}_list", name).as_str()),
+        ) {
+            (Some(Decl::FunctionDecl(builtin)), Some(Decl::FunctionDecl(overload)))
+                if builtin == decl =>
+            {
+                Some(overload.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Match the type of an arg against the type of a parameter of a builtin
+    /// function, in which any stands for a single type that is bound by the
+    /// first arg that it matches.
+    fn unify(param_typ: &Type, arg_typ: &Type, any: &mut Option<Type>) -> bool {
+        match (param_typ, arg_typ) {
+            (Type::AnyType, _) => match *any {
+                Some(ref typ) => typ == arg_typ,
+                None => {
+                    *any = Some(arg_typ.clone());
+                    true
+                }
+            },
+            (Type::ListType(ref param_elem), Type::ListType(ref arg_elem))
+            | (Type::OptionType(ref param_elem), Type::OptionType(ref arg_elem)) => {
+                Checker::unify(param_elem, arg_elem, any)
+            }
+            _ => param_typ == arg_typ,
+        }
+    }
+
+    /// Replace any in `typ` with the type that it is bound to, if any.
+    fn substitute(typ: &Type, any: &Option<Type>) -> Type {
+        match (typ, any) {
+            (Type::AnyType, Some(ref any)) => any.clone(),
+            (Type::ListType(ref elem_typ), _) => {
+                Type::ListType(Box::new(Checker::substitute(elem_typ, any)))
+            }
+            (Type::OptionType(ref elem_typ), _) => {
+                Type::OptionType(Box::new(Checker::substitute(elem_typ, any)))
+            }
+            _ => typ.clone(),
         }
     }
 }
 
 impl VisitorMut for Checker {
-    type Result = Option<Type>;
+    type Result = ExprResult<Option<Type>>;
 
     /// Type-check ast:
     /// - type-check all the expts in the ast.
@@ -88,7 +1394,7 @@ impl VisitorMut for Checker {
             self.visit_expr(expr)?;
         }
 
-        None
+        Ok(None)
     }
 
     /// Type-check expr:
@@ -96,9 +1402,32 @@ impl VisitorMut for Checker {
     fn visit_expr(&mut self, expr: &mut Expr) -> Self::Result {
         match expr {
             Expr::PrintExpr(ref mut print_expr) => self.visit_print_expr(print_expr),
+            Expr::BlockExpr(ref mut block_expr) => self.visit_block_expr(block_expr),
+            Expr::IfExpr(ref mut if_expr) => self.visit_if_expr(if_expr),
+            Expr::WhileExpr(ref mut while_expr) => self.visit_while_expr(while_expr),
+            Expr::ForExpr(ref mut for_expr) => self.visit_for_expr(for_expr),
+            Expr::FnExpr(ref mut fn_expr) => self.visit_fn_expr(fn_expr),
+            Expr::LambdaExpr(ref mut lambda_expr) => self.visit_lambda_expr(lambda_expr),
+            Expr::BreakExpr(ref mut break_expr) => self.visit_break_expr(break_expr),
+            Expr::ContinueExpr(ref mut continue_expr) => self.visit_continue_expr(continue_expr),
+            Expr::LetExpr(ref mut let_expr) => self.visit_let_expr(let_expr),
             Expr::VnameExpr(ref mut vname_expr) => self.visit_vname_expr(vname_expr),
             Expr::IntegerExpr(ref mut int_expr) => self.visit_integer_expr(int_expr),
             Expr::BoolExpr(ref mut bool_expr) => self.visit_bool_expr(bool_expr),
+            Expr::StringExpr(ref mut str_expr) => self.visit_string_expr(str_expr),
+            Expr::TernaryExpr(ref mut ternary_expr) => self.visit_ternary_expr(ternary_expr),
+            Expr::CallExpr(ref mut call_expr) => self.visit_call_expr(call_expr),
+            Expr::ListExpr(ref mut list_expr) => self.visit_list_expr(list_expr),
+            Expr::IndexExpr(ref mut index_expr) => self.visit_index_expr(index_expr),
+            Expr::TupleExpr(ref mut tuple_expr) => self.visit_tuple_expr(tuple_expr),
+            Expr::FieldExpr(ref mut field_expr) => self.visit_field_expr(field_expr),
+            Expr::StructExpr(ref mut struct_expr) => self.visit_struct_expr(struct_expr),
+            Expr::RecordExpr(ref mut record_expr) => self.visit_record_expr(record_expr),
+            Expr::EnumExpr(ref mut enum_expr) => self.visit_enum_expr(enum_expr),
+            Expr::MatchExpr(ref mut match_expr) => self.visit_match_expr(match_expr),
+            Expr::NoneExpr(ref mut none_expr) => self.visit_none_expr(none_expr),
+            Expr::CastExpr(ref mut cast_expr) => self.visit_cast_expr(cast_expr),
+            Expr::FloatExpr(ref mut float_expr) => self.visit_float_expr(float_expr),
             Expr::UnaryExpr(ref mut un_expr) => self.visit_unary_expr(un_expr),
             Expr::AssignExpr(ref mut ass_expr) => self.visit_assign_expr(ass_expr),
             Expr::BinaryExpr(ref mut bin_expr) => self.visit_binary_expr(bin_expr),
@@ -107,209 +1436,1002 @@ impl VisitorMut for Checker {
 
     /// Type-check print expr:
     /// - type-check the expr
-    fn visit_print_expr(&mut self, print_expr: &mut Expr) -> Self::Result {
-        self.visit_expr(print_expr)
+    /// - print has no value of its own.
+    fn visit_print_expr(&mut self, print_expr: &mut PrintExpr) -> Self::Result {
+        self.visit_expr(&mut print_expr.expr)?;
+        Ok(Some(Type::UnitType))
+    }
+
+    /// Type-check block expr:
+    /// - open a new scope for the declarations in the block
+    /// - type-check all the exprs in the block, the last one giving the type
+    ///   of the block
+    /// - close the scope, whether type-checking succeeded or not.
+    fn visit_block_expr(&mut self, block_expr: &mut BlockExpr) -> Self::Result {
+        self.check_block(block_expr, None)
+    }
+
+    /// Type-check if expr:
+    /// - validate that the condition is a bool
+    /// - type-check both branches, which must have the same type (see
+    ///   `check_branches`)
+    /// - an if without an else has no value, whatever the type of its branch.
+    fn visit_if_expr(&mut self, if_expr: &mut IfExpr) -> Self::Result {
+        self.check_if(if_expr, None)
+    }
+
+    /// Type-check ternary expr:
+    /// - validate that the condition is a bool
+    /// - type-check both arms, which must have the same type (see
+    ///   `check_branches`).
+    fn visit_ternary_expr(&mut self, ternary_expr: &mut TernaryExpr) -> Self::Result {
+        self.check_ternary(ternary_expr, None)
+    }
+
+    /// Type-check while expr:
+    /// - validate that the condition is a bool
+    /// - type-check the body as the body of a loop
+    /// - loops have no value.
+    fn visit_while_expr(&mut self, while_expr: &mut WhileExpr) -> Self::Result {
+        self.check_condition(&mut while_expr.cond, "a while")?;
+        self.check_loop_body(&mut while_expr.body)?;
+        Ok(Some(Type::UnitType))
+    }
+
+    /// Type-check for expr:
+    /// - validate that both bounds of the range are ints
+    /// - declare the loop variable as an int, in a scope of its own
+    /// - type-check the body as the body of a loop
+    /// - close the scope, whether type-checking succeeded or not.
+    fn visit_for_expr(&mut self, for_expr: &mut ForExpr) -> Self::Result {
+        for bound in [&mut for_expr.start, &mut for_expr.end] {
+            let bound_typ = self.visit_expr(bound)?;
+            if bound_typ != Some(Type::IntType) {
+                return Err(ExprError::with_span(
+                    ErrorCode::NonIntRangeBound,
+                    format!(
+                        "the bounds of a range must be ints, but this has type {}",
+                        Checker::type_name(&bound_typ)
+                    ),
+                    bound.span().clone(),
+                ));
+            }
+        }
+
+        self.id_table.open_scope();
+
+        let vname = &mut for_expr.vname;
+        vname.typ = Some(Type::IntType);
+        vname.id.typ = Some(Type::IntType);
+        self.id_table
+            .save_attr(&vname.id.spelling, DeclOrId::Id(vname.id.clone()));
+        let typ = self.check_loop_body(&mut for_expr.body);
+
+        self.id_table.close_scope();
+
+        typ?;
+        Ok(Some(Type::UnitType))
+    }
+
+    /// Type-check fn expr:
+    /// - declare the function in the current scope, so that it can be called
+    ///   from its own body
+    /// - declare the parameters in a new scope
+    /// - type-check the body, outside of any loop, and record the functions
+    ///   that it captures
+    /// - validate the type of the body against the return type
+    /// - close the scope, whether type-checking succeeded or not.
+    fn visit_fn_expr(&mut self, fn_expr: &mut FnExpr) -> Self::Result {
+        for param in &mut fn_expr.params {
+            self.resolve_type(&mut param.typ, &param.typ_span)?;
+        }
+        if let Some(ref ret_span) = fn_expr.ret_span {
+            self.resolve_type(&mut fn_expr.ret_typ, ret_span)?;
+        }
+
+        // like types, functions can be shadowed in an inner scope, but not
+        // declared twice in the same one.
+        let name = &fn_expr.vname.id.spelling;
+        if let Some((level, _)) = self.id_table.lookup(&Checker::fn_key(name)) {
+            if level == self.id_table.level {
+                return Err(ExprError::with_span(
+                    ErrorCode::DuplicateFunction,
+                    format!("function {:?} is already declared in this scope", name),
+                    fn_expr.vname.span.clone(),
+                )
+                .with_help("give it another name".to_owned()));
+            }
+        }
+
+        let param_typs = fn_expr
+            .params
+            .iter()
+            .map(|param| param.typ.clone())
+            .collect();
+        let decl = DeclOrId::Decl(Decl::FunctionDecl(FunctionDecl::new(
+            param_typs,
+            fn_expr.ret_typ.clone(),
+        )));
+        self.id_table
+            .save_attr(&Checker::fn_key(name), decl.clone());
+        self.id_table.save_attr(name, decl);
+
+        let params = fn_expr
+            .params
+            .iter_mut()
+            .map(|param| {
+                param.vname.typ = Some(param.typ.clone());
+                &mut param.vname
+            })
+            .collect();
+        let (body_typ, captures) =
+            self.check_function_body(params, &mut fn_expr.body, Some(&fn_expr.ret_typ))?;
+        fn_expr.captures = captures;
+
+        if body_typ.as_ref() != Some(&fn_expr.ret_typ) {
+            let body_span = match *fn_expr.body {
+                Expr::BlockExpr(ref block_expr) if !block_expr.exprs.is_empty() => {
+                    block_expr.exprs.last().unwrap().span().clone()
+                }
+                ref body => body.span().clone(),
+            };
+
+            let err = ExprError::with_span(
+                ErrorCode::ReturnTypeMismatch,
+                format!(
+                    "function {:?} returns {}, but its body has type {}",
+                    fn_expr.vname.id.spelling,
+                    fn_expr.ret_typ,
+                    Checker::type_name(&body_typ)
+                ),
+                body_span,
+            );
+
+            return Err(match fn_expr.ret_span {
+                Some(ref ret_span) => err.with_label(
+                    ret_span.clone(),
+                    format!("expected {} due to this", fn_expr.ret_typ),
+                ),
+                None => err.with_help(format!(
+                    "add a return type, e.g., -> {}",
+                    Checker::type_name(&body_typ)
+                )),
+            });
+        }
+
+        Ok(Some(Type::UnitType))
+    }
+
+    /// Type-check lambda expr:
+    /// - declare the parameters, which must be annotated here, in a new scope
+    /// - type-check the body, outside of any loop, and record the variables
+    ///   and functions that it captures
+    /// - the type of the closure is a function type with the type of the
+    ///   body as its return type.
+    fn visit_lambda_expr(&mut self, lambda_expr: &mut LambdaExpr) -> Self::Result {
+        self.check_lambda(lambda_expr, None)
+    }
+
+    /// Type-check break expr:
+    /// - validate that it is inside a loop.
+    fn visit_break_expr(&mut self, break_expr: &mut BreakExpr) -> Self::Result {
+        if self.loop_depth == 0 {
+            return Err(ExprError::with_span(
+                ErrorCode::BreakOutsideLoop,
+                "break outside of a loop".to_owned(),
+                break_expr.span.clone(),
+            ));
+        }
+
+        Ok(Some(Type::UnitType))
+    }
+
+    /// Type-check continue expr:
+    /// - validate that it is inside a loop.
+    fn visit_continue_expr(&mut self, continue_expr: &mut ContinueExpr) -> Self::Result {
+        if self.loop_depth == 0 {
+            return Err(ExprError::with_span(
+                ErrorCode::BreakOutsideLoop,
+                "continue outside of a loop".to_owned(),
+                continue_expr.span.clone(),
+            ));
+        }
+
+        Ok(Some(Type::UnitType))
     }
 
     /// Type-check vname expr;
-    /// - simply visit the identifier and return its type.
+    /// - visit the identifier and return its type
+    /// - validate that the variable has been declared.
     fn visit_vname_expr(&mut self, vname_expr: &mut VnameExpr) -> Self::Result {
-        vname_expr.typ = self.visit_identifier(&mut vname_expr.id);
-        vname_expr.typ.clone()
+        vname_expr.typ = self.visit_identifier(&mut vname_expr.id)?;
+
+        if let Some(DeclOrId::Decl(Decl::VariantDecl(ref variant_decl))) =
+            self.id_table.get_attr(&vname_expr.id.spelling)
+        {
+            if !variant_decl.payload_typs.is_empty() {
+                let names = (0..variant_decl.payload_typs.len())
+                    .map(|idx| ((b'a' + (idx % 26) as u8) as char).to_string())
+                    .collect::<Vec<_>>();
+                return Err(ExprError::with_span(
+                    ErrorCode::FunctionUsedAsValue,
+                    format!(
+                        "variant {:?} has a payload, so it can only be called, not used as a value",
+                        vname_expr.id.spelling
+                    ),
+                    vname_expr.span.clone(),
+                )
+                .with_help(format!(
+                    "give it its payload, e.g., {}({})",
+                    vname_expr.id.spelling,
+                    names.join(", ")
+                )));
+            }
+
+            vname_expr.typ = Some(variant_decl.enum_typ.clone());
+            vname_expr.id.typ = vname_expr.typ.clone();
+            return Ok(vname_expr.typ.clone());
+        }
+
+        if let (None, Some(DeclOrId::Decl(Decl::FunctionDecl(ref func_decl)))) = (
+            &vname_expr.typ,
+            self.id_table.get_attr(&vname_expr.id.spelling),
+        ) {
+            let names = (0..func_decl.param_typs.len())
+                .map(|idx| ((b'a' + (idx % 26) as u8) as char).to_string())
+                .collect::<Vec<_>>();
+            let params = names
+                .iter()
+                .zip(&func_decl.param_typs)
+                .map(|(name, typ)| format!("{}: {}", name, typ))
+                .collect::<Vec<_>>();
+            return Err(ExprError::with_span(
+                ErrorCode::FunctionUsedAsValue,
+                format!(
+                    "function {:?} can only be called, not used as a value",
+                    vname_expr.id.spelling
+                ),
+                vname_expr.span.clone(),
+            )
+            .with_help(format!(
+                "wrap it in a closure, e.g., |{}| {}({})",
+                params.join(", "),
+                vname_expr.id.spelling,
+                names.join(", ")
+            )));
+        }
+
+        if vname_expr.typ.is_none() {
+            return Err(ExprError::with_span(
+                ErrorCode::UndeclaredVariable,
+                format!("cannot find variable {:?}", vname_expr.id.spelling),
+                vname_expr.span.clone(),
+            )
+            .with_help(format!(
+                "declare it first, e.g., let {} = ...",
+                vname_expr.id.spelling
+            )));
+        }
+
+        Ok(vname_expr.typ.clone())
+    }
+
+    /// Type-check let expr:
+    /// - type-check the expr, inferring the types of the parameters of a
+    ///   closure from the annotation, if any
+    /// - validate the type against the annotation, if any
+    /// - declare the variable with the type in the current scope.
+    fn visit_let_expr(&mut self, let_expr: &mut LetExpr) -> Self::Result {
+        let typ = match let_expr.annotation {
+            Some((ref mut annotation, ref annotation_span)) => {
+                self.resolve_type(annotation, annotation_span)?;
+                self.visit_expr_expecting(&mut let_expr.expr, annotation)?
+            }
+            None => self.visit_expr(&mut let_expr.expr)?,
+        };
+        let typ = typ.ok_or_else(|| {
+            ExprError::with_span(
+                ErrorCode::UnknownOperandType,
+                "could not determine type of the initial value in let expression".to_owned(),
+                let_expr.expr.span().clone(),
+            )
+        })?;
+
+        if let Some((ref annotation, ref annotation_span)) = let_expr.annotation {
+            if *annotation != typ {
+                let err = ExprError::with_span(
+                    ErrorCode::AnnotationMismatch,
+                    format!(
+                        "variable {:?} is annotated as {}, but its initial value has type {}",
+                        let_expr.vname.id.spelling, annotation, typ
+                    ),
+                    let_expr.expr.span().clone(),
+                )
+                .with_label(
+                    annotation_span.clone(),
+                    format!("expected {} due to this", annotation),
+                );
+                return Err(self.label_same_named_types(err, annotation, &typ));
+            }
+        }
+
+        let vname = &mut let_expr.vname;
+        vname.typ = Some(typ.clone());
+        vname.id.typ = Some(typ.clone());
+        self.id_table
+            .save_attr(&vname.id.spelling, DeclOrId::Id(vname.id.clone()));
+
+        let_expr.typ = Some(typ);
+        Ok(let_expr.typ.clone())
     }
 
     /// Type-check integer expr:
-    fn visit_integer_expr(&mut self, _int_expr: &mut i32) -> Self::Result {
-        Some(Type::IntType)
+    fn visit_integer_expr(&mut self, _int_expr: &mut IntegerExpr) -> Self::Result {
+        Ok(Some(Type::IntType))
+    }
+
+    /// Type-check float expr:
+    fn visit_float_expr(&mut self, _float_expr: &mut FloatExpr) -> Self::Result {
+        Ok(Some(Type::FloatType))
     }
 
     /// Type-check bool expr:
-    fn visit_bool_expr(&mut self, _bool_expr: &mut bool) -> Self::Result {
-        Some(Type::BoolType)
+    fn visit_bool_expr(&mut self, _bool_expr: &mut BoolExpr) -> Self::Result {
+        Ok(Some(Type::BoolType))
+    }
+
+    /// Type-check string expr:
+    fn visit_string_expr(&mut self, _str_expr: &mut StringExpr) -> Self::Result {
+        Ok(Some(Type::StringType))
+    }
+
+    /// Type-check call expr:
+    /// - get the spec for the function from the id table (a variant with a
+    ///   payload is a function from the payload to the enum), or else
+    ///   type-check the callee, which must have a function type
+    /// - type-check the args, inferring the types of the parameters of
+    ///   closures from the spec
+    /// - for a builtin that is overloaded for lists, type-check the first arg
+    ///   to choose the overload
+    /// - validate the number and the types of the args against the spec,
+    ///   binding any to the type of the first arg that it matches
+    /// - set the spec return type as the type of the expr
+    fn visit_call_expr(&mut self, call_expr: &mut CallExpr) -> Self::Result {
+        self.check_call(call_expr, None)
+    }
+
+    /// Type-check list expr:
+    /// - type-check the elements, which must all have the same type
+    /// - the type of an empty list cannot be inferred here, see
+    ///   `visit_expr_expecting`.
+    fn visit_list_expr(&mut self, list_expr: &mut ListExpr) -> Self::Result {
+        self.check_list(list_expr, None)
+    }
+
+    /// Type-check index expr:
+    /// - validate that the indexed expr is a list
+    /// - validate that the index is an int
+    /// - the type of the expr is the element type of the list.
+    fn visit_index_expr(&mut self, index_expr: &mut IndexExpr) -> Self::Result {
+        let elem_typ = match self.visit_expr(&mut index_expr.list)? {
+            Some(Type::ListType(elem_typ)) => *elem_typ,
+            list_typ => {
+                return Err(ExprError::with_span(
+                    ErrorCode::NotIndexable,
+                    format!(
+                        "cannot index into a value of type {}",
+                        Checker::type_name(&list_typ)
+                    ),
+                    index_expr.list.span().clone(),
+                )
+                .with_help("only lists can be indexed".to_owned()))
+            }
+        };
+
+        let index_typ = self.visit_expr(&mut index_expr.index)?;
+        if index_typ != Some(Type::IntType) {
+            return Err(ExprError::with_span(
+                ErrorCode::NonIntIndex,
+                format!(
+                    "the index of a list must be an int, but it has type {}",
+                    Checker::type_name(&index_typ)
+                ),
+                index_expr.index.span().clone(),
+            ));
+        }
+
+        index_expr.typ = Some(elem_typ);
+        Ok(index_expr.typ.clone())
+    }
+
+    /// Type-check tuple expr:
+    /// - type-check the elements, whose types make up the type of the tuple.
+    fn visit_tuple_expr(&mut self, tuple_expr: &mut TupleExpr) -> Self::Result {
+        self.check_tuple(tuple_expr, None)
+    }
+
+    /// Type-check field expr:
+    /// - type-check the expr, which must be a tuple or a record
+    /// - validate that the tuple has an element at the index of the field, or
+    ///   that the struct of the record has the field, whose type is the type
+    ///   of the field expr.
+    fn visit_field_expr(&mut self, field_expr: &mut FieldExpr) -> Self::Result {
+        let expr_typ = self.visit_expr(&mut field_expr.expr)?;
+
+        let elem_typ = match (&expr_typ, field_expr.field.parse::<usize>()) {
+            (Some(Type::TupleType(ref elem_typs)), Ok(idx)) => elem_typs.get(idx).cloned(),
+            (Some(ref typ @ Type::NamedType(..)), _) => self
+                .get_struct(typ)
+                .and_then(|struct_decl| struct_decl.field_typ(&field_expr.field))
+                .cloned(),
+            _ => None,
+        };
+
+        field_expr.typ = Some(elem_typ.ok_or_else(|| {
+            ExprError::with_span(
+                ErrorCode::UnknownField,
+                format!(
+                    "no field {} on type {}",
+                    field_expr.field,
+                    Checker::type_name(&expr_typ)
+                ),
+                field_expr.field_span.clone(),
+            )
+            .with_label(
+                field_expr.expr.span().clone(),
+                format!("this has type {}", Checker::type_name(&expr_typ)),
+            )
+        })?);
+        Ok(field_expr.typ.clone())
+    }
+
+    /// Type-check struct expr:
+    /// - validate that the fields have different names
+    /// - declare the struct in the current scope, where no other type of the
+    ///   same name is declared, so that its fields can refer to it
+    /// - validate that the types of the fields have been declared
+    /// - the declaration has no value.
+    fn visit_struct_expr(&mut self, struct_expr: &mut StructExpr) -> Self::Result {
+        for (idx, field) in struct_expr.fields.iter().enumerate() {
+            if let Some(prev) = struct_expr.fields[..idx]
+                .iter()
+                .find(|prev| prev.name.spelling == field.name.spelling)
+            {
+                return Err(ExprError::with_span(
+                    ErrorCode::DuplicateField,
+                    format!(
+                        "field {:?} is declared more than once in struct {}",
+                        field.name.spelling, struct_expr.name.spelling
+                    ),
+                    field.name.span.clone(),
+                )
+                .with_label(prev.name.span.clone(), "first declared here".to_owned()));
+            }
+        }
+
+        let fields = |struct_expr: &StructExpr| {
+            struct_expr
+                .fields
+                .iter()
+                .map(|field| (field.name.spelling.clone(), field.typ.clone()))
+                .collect()
+        };
+        let unresolved = fields(struct_expr);
+        let id = self.declare_type(&struct_expr.name, |id| {
+            Decl::StructDecl(StructDecl::new(id, unresolved))
+        })?;
+
+        for field in &mut struct_expr.fields {
+            self.resolve_type(&mut field.typ, &field.typ_span)?;
+        }
+        self.types[id] = Decl::StructDecl(StructDecl::new(id, fields(struct_expr)));
+        self.update_type(&struct_expr.name.spelling, id);
+
+        Ok(Some(Type::UnitType))
+    }
+
+    /// Type-check record expr:
+    /// - get the declaration of the struct from the id table
+    /// - type-check the value of each field, which must be a field of the
+    ///   struct given only once, against the type of the field
+    /// - validate that every field of the struct has been given a value
+    /// - the type of the expr is the struct type.
+    fn visit_record_expr(&mut self, record_expr: &mut RecordExpr) -> Self::Result {
+        let name = &record_expr.name.spelling;
+        let typ = Type::NamedType(name.clone(), self.type_id(name));
+        let struct_decl = self.get_struct(&typ).cloned().ok_or_else(|| {
+            ExprError::with_span(
+                ErrorCode::UnknownType,
+                format!("cannot find struct {:?}", name),
+                record_expr.name.span.clone(),
+            )
+            .with_help(format!("declare it first, e.g., struct {} {{ ... }}", name))
+        })?;
+
+        for idx in 0..record_expr.fields.len() {
+            let (field, rest) = record_expr.fields[..=idx].split_last_mut().unwrap();
+            let (ref field_id, ref mut expr) = *field;
+
+            if let Some((prev_id, _)) = rest
+                .iter()
+                .find(|(prev_id, _)| prev_id.spelling == field_id.spelling)
+            {
+                return Err(ExprError::with_span(
+                    ErrorCode::DuplicateField,
+                    format!("field {:?} is given more than once", field_id.spelling),
+                    field_id.span.clone(),
+                )
+                .with_label(prev_id.span.clone(), "first given here".to_owned()));
+            }
+
+            let field_typ = struct_decl.field_typ(&field_id.spelling).ok_or_else(|| {
+                ExprError::with_span(
+                    ErrorCode::UnknownField,
+                    format!("struct {} has no field {:?}", name, field_id.spelling),
+                    field_id.span.clone(),
+                )
+            })?;
+
+            let typ = self.visit_expr_expecting(expr, field_typ)?;
+            if typ.as_ref() != Some(field_typ) {
+                return Err(ExprError::with_span(
+                    ErrorCode::FieldTypeMismatch,
+                    format!(
+                        "field {:?} has type {}, but the value has type {}",
+                        field_id.spelling,
+                        field_typ,
+                        Checker::type_name(&typ)
+                    ),
+                    expr.span().clone(),
+                ));
+            }
+        }
+
+        let missing = struct_decl
+            .fields
+            .iter()
+            .filter(|(decl_field, _)| {
+                !record_expr
+                    .fields
+                    .iter()
+                    .any(|(field_id, _)| field_id.spelling == *decl_field)
+            })
+            .map(|(decl_field, _)| format!("{:?}", decl_field))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(ExprError::with_span(
+                ErrorCode::MissingField,
+                format!(
+                    "missing field(s) {} in record of struct {}",
+                    missing.join(", "),
+                    name
+                ),
+                record_expr.span.clone(),
+            ));
+        }
+
+        record_expr.decl_fields = struct_decl
+            .fields
+            .iter()
+            .map(|(decl_field, _)| decl_field.clone())
+            .collect();
+        record_expr.typ = Some(typ);
+        Ok(record_expr.typ.clone())
+    }
+
+    /// Type-check enum expr:
+    /// - validate that the variants have different names
+    /// - declare the enum in the current scope, where no other type of the
+    ///   same name is declared, so that the payloads of its variants can
+    ///   refer to it
+    /// - validate that the types of the payloads have been declared
+    /// - declare each variant under its own name.
+    fn visit_enum_expr(&mut self, enum_expr: &mut EnumExpr) -> Self::Result {
+        for (idx, variant) in enum_expr.variants.iter().enumerate() {
+            if let Some(prev) = enum_expr.variants[..idx]
+                .iter()
+                .find(|prev| prev.name.spelling == variant.name.spelling)
+            {
+                return Err(ExprError::with_span(
+                    ErrorCode::DuplicateVariant,
+                    format!(
+                        "variant {:?} is declared more than once in enum {}",
+                        variant.name.spelling, enum_expr.name.spelling
+                    ),
+                    variant.name.span.clone(),
+                )
+                .with_label(prev.name.span.clone(), "first declared here".to_owned()));
+            }
+        }
+
+        let variants = |enum_expr: &EnumExpr| {
+            enum_expr
+                .variants
+                .iter()
+                .map(|variant| {
+                    let payload_typs = variant.payload.iter().map(|(typ, _)| typ.clone());
+                    (variant.name.spelling.clone(), payload_typs.collect())
+                })
+                .collect::<Vec<(String, Vec<Type>)>>()
+        };
+        let unresolved = variants(enum_expr);
+        let id = self.declare_type(&enum_expr.name, |id| {
+            Decl::EnumDecl(EnumDecl::new(id, unresolved))
+        })?;
+
+        // the variants are declared even if a payload type is unknown, so
+        // that the uses of the enum are not reported as well.
+        let mut resolved = Ok(());
+        for variant in &mut enum_expr.variants {
+            for (typ, typ_span) in &mut variant.payload {
+                if resolved.is_ok() {
+                    resolved = self.resolve_type(typ, typ_span);
+                }
+            }
+        }
+
+        let name = &enum_expr.name.spelling;
+        let variants = variants(enum_expr);
+        self.types[id] = Decl::EnumDecl(EnumDecl::new(id, variants.clone()));
+        self.update_type(name, id);
+        for (variant, payload_typs) in variants {
+            self.id_table.save_attr(
+                &variant,
+                DeclOrId::Decl(Decl::VariantDecl(VariantDecl::new(
+                    Type::NamedType(name.clone(), Some(id)),
+                    payload_typs,
+                ))),
+            );
+        }
+
+        resolved?;
+        Ok(Some(Type::UnitType))
+    }
+
+    /// Type-check match expr:
+    /// - type-check the matched expr
+    /// - check the pattern of each arm against its type, and validate that
+    ///   the arm can be taken, i.e., that the pattern matches a value that
+    ///   the arms before it do not
+    /// - type-check the body of each arm, with the names that its pattern
+    ///   binds declared in a scope of their own
+    /// - the bodies must all have the same type (that the bodies after the
+    ///   first one type-checked are expected to have, see `check_order`),
+    ///   which is the type of the expr
+    /// - validate that the arms cover every value of the matched expr.
+    fn visit_match_expr(&mut self, match_expr: &mut MatchExpr) -> Self::Result {
+        self.check_match(match_expr, None)
+    }
+
+    /// Type-check none expr:
+    /// - the type of none cannot be inferred here, see
+    ///   `visit_expr_expecting`.
+    fn visit_none_expr(&mut self, none_expr: &mut NoneExpr) -> Self::Result {
+        Err(ExprError::with_span(
+            ErrorCode::CannotInferOptionType,
+            "cannot infer the type of none".to_owned(),
+            none_expr.span.clone(),
+        )
+        .with_help("give the variable a type, e.g., let x: Option<int> = none".to_owned()))
+    }
+
+    /// Type-check cast expr:
+    /// - type-check the expr
+    /// - validate that both the expr type and the target type are numeric.
+    fn visit_cast_expr(&mut self, cast_expr: &mut CastExpr) -> Self::Result {
+        let expr_typ = self.visit_expr(&mut cast_expr.expr)?.ok_or_else(|| {
+            ExprError::with_span(
+                ErrorCode::UnknownOperandType,
+                "for cast expr, expr type is unavailable".to_owned(),
+                cast_expr.expr.span().clone(),
+            )
+        })?;
+
+        let is_numeric = |typ: &Type| matches!(*typ, Type::IntType | Type::FloatType);
+        if !is_numeric(&expr_typ) || !is_numeric(&cast_expr.target) {
+            return Err(ExprError::with_span(
+                ErrorCode::InvalidCast,
+                format!("cannot convert {} to {}", expr_typ, cast_expr.target),
+                cast_expr.span.clone(),
+            )
+            .with_help("only int and float can be converted to each other".to_owned()));
+        }
+
+        cast_expr.typ = Some(cast_expr.target.clone());
+        Ok(cast_expr.typ.clone())
     }
 
     /// Type-check an identifier:
     /// - for now, simply return its type, if any.
     fn visit_identifier(&mut self, id: &mut Identifier) -> Self::Result {
-        if let Some(DeclOrId::Id(ref id_decl)) = self.id_table.get_attr(&id.spelling) {
+        if let Some(DeclOrId::Id(ref id_decl)) = self.lookup(&id.spelling, &id.span)? {
             id.typ = id_decl.typ.clone();
-            id.typ.clone()
+            Ok(id.typ.clone())
         } else {
-            None
+            Ok(None)
         }
     }
 
     /// Type-check unary expr:
     /// - get the spec for the operator from the id table
     /// - get the type of the unary expr post typechecking
-    /// - validate that the types are the same.
+    /// - validate that the types are the same
+    /// - for ++ and --, validate that the elem is a variable that can be
+    ///   assigned to.
     fn visit_unary_expr(&mut self, un_expr: &mut UnaryExpr) -> Self::Result {
-        let elem_typ = self.visit_expr(&mut un_expr.elem);
-        if elem_typ.is_none() {
-            report_error(
-                ExprError::new(
-                    ExprErrorKind::CheckerError,
-                    format!("for unary expr, elem type is unavailable"),
-                ),
-                None,
-            );
+        let elem_typ = self.visit_expr(&mut un_expr.elem)?.ok_or_else(|| {
+            ExprError::with_span(
+                ErrorCode::UnknownOperandType,
+                "for unary expr, elem type is unavailable".to_owned(),
+                un_expr.elem.span().clone(),
+            )
+        })?;
+
+        if un_expr.op.is_assignment() {
+            if !matches!(*un_expr.elem, Expr::VnameExpr(_)) {
+                return Err(ExprError::with_span(
+                    ErrorCode::InvalidAssignmentTarget,
+                    format!("the operand of {} must be a variable", un_expr.op),
+                    un_expr.elem.span().clone(),
+                ));
+            }
+            self.check_assignable(&un_expr.elem)?;
         }
 
-        let elem_typ = elem_typ.unwrap();
-        let op_spec = self.get_unary_op_spec(&un_expr.op);
+        let op_spec = self.get_unary_op_spec(&un_expr.op, &elem_typ);
 
         if let DeclOrId::Decl(Decl::OperatorDecl(OperatorDecl::UnaryOperatorDecl(ref op_decl))) =
             op_spec
         {
             if op_decl.elem_typ != elem_typ {
-                report_error(ExprError::new(ExprErrorKind::CheckerError, format!("unary expression elem type ({:?}) does not match the expected elem type ({:?}) for operator", 
-                        elem_typ, op_decl.elem_typ)), None);
+                return Err(ExprError::with_span(
+                    ErrorCode::UnaryOperandMismatch,
+                    format!(
+                        "the operand of {} has type {}, but the operator expects {}",
+                        un_expr.op, elem_typ, op_decl.elem_typ
+                    ),
+                    un_expr.elem.span().clone(),
+                ));
             }
 
             un_expr.typ = Some(op_decl.ret_typ.clone());
-            un_expr.typ.clone()
+            Ok(un_expr.typ.clone())
         } else {
-            report_error(
-                ExprError::new(
-                    ExprErrorKind::CheckerError,
-                    format!(
-                        "expected a unary operator in unary expression, but found {:?}",
-                        op_spec
-                    ),
-                ),
-                None,
-            );
-            None
+            Err(ExprError::with_span(
+                ErrorCode::UndeclaredOperator,
+                format!("operator {} is not declared", un_expr.op),
+                un_expr.span.clone(),
+            ))
         }
     }
 
     /// Type-check assignment expr:
-    /// - type-check vname and get id.
+    /// - a tuple of targets is destructured, see `check_tuple_assign`
+    /// - type-check the target, a vname or an index expr, and get its type.
     /// - type-check rhs expr and get type.
     /// - get spec for assignment operation.
     /// - validate types
     /// - decorate identifier with type (if not set), and if already set,
-    /// verify that the type has not changed.
+    ///   verify that the type has not changed.
     fn visit_assign_expr(&mut self, ass_expr: &mut AssignExpr) -> Self::Result {
-        let lhs_typ = self.visit_expr(&mut ass_expr.vname);
-        let rhs_typ = self.visit_expr(&mut ass_expr.expr);
+        if let Expr::TupleExpr(_) = *ass_expr.target {
+            return self.check_tuple_assign(ass_expr);
+        }
+
+        let lhs_typ = self.visit_expr(&mut ass_expr.target)?;
+        self.check_assignable(&ass_expr.target)?;
+        let rhs_typ = match lhs_typ {
+            Some(ref lhs_typ) => self.visit_expr_expecting(&mut ass_expr.expr, lhs_typ)?,
+            None => self.visit_expr(&mut ass_expr.expr)?,
+        };
 
         if rhs_typ.is_none() {
-            report_error(
-                ExprError::new(
-                    ExprErrorKind::CheckerError,
-                    format!("could not determine type of rhs of assignment expression"),
-                ),
-                None,
-            );
+            return Err(ExprError::with_span(
+                ErrorCode::UnknownOperandType,
+                "could not determine type of rhs of assignment expression".to_owned(),
+                ass_expr.expr.span().clone(),
+            ));
         }
 
-        let op_spec = self.get_bin_op_spec(&ass_expr.op);
-        if let DeclOrId::Decl(Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(ref _op_decl))) =
+        let var_typ = lhs_typ.clone().or_else(|| rhs_typ.clone()).unwrap();
+        let op_spec = self.get_bin_op_spec(&ass_expr.op, &var_typ);
+        if let DeclOrId::Decl(Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(ref op_decl))) =
             op_spec
         {
-            if let Expr::VnameExpr(ref mut vname) = *ass_expr.vname {
-                if vname.typ.is_none() {
-                    vname.typ = rhs_typ.clone();
-                    vname.id.typ = rhs_typ.clone();
-                    self.id_table
-                        .save_attr(&vname.id.spelling, DeclOrId::Id(vname.id.clone()));
-                } else if lhs_typ != rhs_typ {
-                    report_error(
-                        ExprError::new(
-                            ExprErrorKind::CheckerError,
+            // compound assignments apply the operator to the variable, so
+            // the variable must have a type that the operator accepts.
+            if ass_expr.op != BinaryOperator::Assign && var_typ != op_decl.lhs_typ {
+                return Err(ExprError::with_span(
+                    ErrorCode::OperandTypeMismatch,
+                    format!(
+                        "the variable has type {}, but the operator {} expects {}",
+                        var_typ, ass_expr.op, op_decl.lhs_typ
+                    ),
+                    ass_expr.target.span().clone(),
+                )
+                .with_label(
+                    ass_expr.op_span.clone(),
+                    format!("expected {} due to this operator", op_decl.lhs_typ),
+                ));
+            }
+
+            match *ass_expr.target {
+                Expr::VnameExpr(ref mut vname) => {
+                    if lhs_typ != rhs_typ {
+                        let mut err = ExprError::with_span(
+                            ErrorCode::VariableTypeChanged,
                             format!(
-                                "id {:?} has inferred type {:?}, but rhs has type {:?}",
-                                vname.id.spelling, vname.id.typ, rhs_typ
+                                "variable {:?} was declared as {}, but the value has type {}",
+                                vname.id.spelling,
+                                Checker::type_name(&lhs_typ),
+                                Checker::type_name(&rhs_typ)
                             ),
-                        ),
-                        None,
-                    );
-                    return None;
+                            ass_expr.expr.span().clone(),
+                        )
+                        .with_note("the type of a variable cannot change once declared".to_owned());
+
+                        if let Some(DeclOrId::Id(ref decl_id)) =
+                            self.id_table.get_attr(&vname.id.spelling)
+                        {
+                            err = err.with_label(
+                                decl_id.span.clone(),
+                                format!("declared as {} here", Checker::type_name(&lhs_typ)),
+                            );
+                        }
+                        if let (Some(ref lhs_typ), Some(ref rhs_typ)) = (&lhs_typ, &rhs_typ) {
+                            err = self.label_same_named_types(err, lhs_typ, rhs_typ);
+                        }
+
+                        return Err(err);
+                    }
                 }
-                ass_expr.typ = rhs_typ.clone();
-                ass_expr.typ.clone()
-            } else {
-                report_error(
-                    ExprError::new(
-                        ExprErrorKind::CheckerError,
-                        format!("the lhs of an assignment expression must be a vname"),
-                    ),
-                    None,
-                );
-                None
+                Expr::FieldExpr(ref field_expr) => {
+                    Checker::check_field_target(field_expr)?;
+                    if lhs_typ != rhs_typ {
+                        return Err(ExprError::with_span(
+                            ErrorCode::FieldTypeMismatch,
+                            format!(
+                                "field {:?} has type {}, but the value has type {}",
+                                field_expr.field,
+                                Checker::type_name(&lhs_typ),
+                                Checker::type_name(&rhs_typ)
+                            ),
+                            ass_expr.expr.span().clone(),
+                        )
+                        .with_label(
+                            field_expr.span.clone(),
+                            format!("this has type {}", Checker::type_name(&lhs_typ)),
+                        ));
+                    }
+                }
+                Expr::IndexExpr(ref index_expr) => {
+                    if lhs_typ != rhs_typ {
+                        return Err(ExprError::with_span(
+                            ErrorCode::ListElementTypeMismatch,
+                            format!(
+                                "the elements of the list have type {}, but the value has type {}",
+                                Checker::type_name(&lhs_typ),
+                                Checker::type_name(&rhs_typ)
+                            ),
+                            ass_expr.expr.span().clone(),
+                        )
+                        .with_label(
+                            index_expr.list.span().clone(),
+                            format!(
+                                "this has type {}",
+                                Checker::type_name(&index_expr.list.typ())
+                            ),
+                        ));
+                    }
+                }
+                _ => return Err(ExprError::with_span(
+                    ErrorCode::InvalidAssignmentTarget,
+                    "the lhs of an assignment expression must be a vname, an index expr or a field"
+                        .to_owned(),
+                    ass_expr.target.span().clone(),
+                )),
             }
+            ass_expr.typ = rhs_typ.clone();
+            Ok(ass_expr.typ.clone())
         } else {
-            None
+            Ok(None)
         }
     }
 
     /// Type-check binary expr:
     /// - get the spec for the binary operator
     /// - type-check the lhs
-    /// - type-check the rhs
+    /// - type-check the rhs, which is expected to have the type of the lhs
+    ///   (and of the option for ??, see `check_coalesce`)
     /// - validate types against spec
     /// - set the spec return type as the type of the expr
     fn visit_binary_expr(&mut self, bin_expr: &mut BinaryExpr) -> Self::Result {
-        let lhs_typ = self.visit_expr(&mut bin_expr.lhs);
-        if lhs_typ.is_none() {
-            println!("{:?}", bin_expr.lhs);
-            report_error(
-                ExprError::new(
-                    ExprErrorKind::CheckerError,
-                    format!("for bin expr, lhs type is unavailable"),
-                ),
-                None,
-            );
-        }
-
-        let rhs_typ = self.visit_expr(&mut bin_expr.rhs);
+        let lhs_typ = self.visit_expr(&mut bin_expr.lhs)?.ok_or_else(|| {
+            ExprError::with_span(
+                ErrorCode::UnknownOperandType,
+                "for bin expr, lhs type is unavailable".to_owned(),
+                bin_expr.lhs.span().clone(),
+            )
+        })?;
 
-        if rhs_typ.is_none() {
-            report_error(
-                ExprError::new(
-                    ExprErrorKind::CheckerError,
-                    format!("for bin expr, rhs type is unavailable"),
-                ),
-                None,
-            );
+        if bin_expr.op == BinaryOperator::Coalesce {
+            return self.check_coalesce(bin_expr, lhs_typ);
         }
 
-        let lhs_typ = lhs_typ.unwrap();
-        let rhs_typ = rhs_typ.unwrap();
-        let op_spec = self.get_bin_op_spec(&bin_expr.op);
+        let rhs_typ = self
+            .visit_expr_expecting(&mut bin_expr.rhs, &lhs_typ)?
+            .ok_or_else(|| {
+                ExprError::with_span(
+                    ErrorCode::UnknownOperandType,
+                    "for bin expr, rhs type is unavailable".to_owned(),
+                    bin_expr.rhs.span().clone(),
+                )
+            })?;
+
+        let op_spec = self.get_bin_op_spec(&bin_expr.op, &lhs_typ);
 
         if let DeclOrId::Decl(Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(ref op_decl))) =
             op_spec
         {
             if op_decl.lhs_typ == Type::AnyType && op_decl.rhs_typ == Type::AnyType {
                 if lhs_typ != rhs_typ {
-                    report_error(ExprError::new(ExprErrorKind::CheckerError, format!("for bin expr, lhs type of lhs expr ({:?}) does not match the rhs expr type ({:?})", lhs_typ,
-            rhs_typ)), None);
+                    return Err(ExprError::with_span(
+                        ErrorCode::OperandTypesDiffer,
+                        format!(
+                            "the operands of {} have different types, {} and {}",
+                            bin_expr.op, lhs_typ, rhs_typ
+                        ),
+                        bin_expr.op_span.clone(),
+                    )
+                    .with_label(
+                        bin_expr.lhs.span().clone(),
+                        format!("this has type {}", lhs_typ),
+                    )
+                    .with_label(
+                        bin_expr.rhs.span().clone(),
+                        format!("this has type {}", rhs_typ),
+                    )
+                    .with_help(format!(
+                        "both operands of {} must have the same type",
+                        bin_expr.op
+                    )));
                 }
             } else if lhs_typ != op_decl.lhs_typ {
-                report_error(ExprError::new(ExprErrorKind::CheckerError, format!("for bin expr, lhs type of lhs expr ({:?}) does not match the spec's lhs type ({:?})", lhs_typ,
-            op_decl.lhs_typ)), None);
+                return Err(ExprError::with_span(
+                    ErrorCode::OperandTypeMismatch,
+                    format!(
+                        "the left operand of {} has type {}, but the operator expects {}",
+                        bin_expr.op, lhs_typ, op_decl.lhs_typ
+                    ),
+                    bin_expr.lhs.span().clone(),
+                )
+                .with_label(
+                    bin_expr.op_span.clone(),
+                    format!("expected {} due to this operator", op_decl.lhs_typ),
+                ));
             } else if rhs_typ != op_decl.rhs_typ {
-                report_error(ExprError::new(ExprErrorKind::CheckerError, format!("for bin expr, lhs type of rhs expr ({:?}) does not match the spec's rhs type ({:?})", rhs_typ,
-            op_decl.rhs_typ)), None);
+                return Err(ExprError::with_span(
+                    ErrorCode::OperandTypeMismatch,
+                    format!(
+                        "the right operand of {} has type {}, but the operator expects {}",
+                        bin_expr.op, rhs_typ, op_decl.rhs_typ
+                    ),
+                    bin_expr.rhs.span().clone(),
+                )
+                .with_label(
+                    bin_expr.op_span.clone(),
+                    format!("expected {} due to this operator", op_decl.rhs_typ),
+                ));
             }
 
             bin_expr.typ = Some(op_decl.ret_typ.clone());
-            bin_expr.typ.clone()
+            Ok(bin_expr.typ.clone())
         } else {
-            report_error(
-                ExprError::new(
-                    ExprErrorKind::CheckerError,
-                    format!(
-                        "expected a binary operator in binary expression, but found {:?}",
-                        op_spec
-                    ),
-                ),
-                None,
-            );
-
-            None
+            Err(ExprError::with_span(
+                ErrorCode::UndeclaredOperator,
+                format!("operator {} is not declared", bin_expr.op),
+                bin_expr.op_span.clone(),
+            ))
         }
     }
 }
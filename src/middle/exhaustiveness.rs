@@ -0,0 +1,204 @@
+//! The checks of the arms of a match, with the usefulness algorithm from
+//! "Warnings for pattern matching" (Maranget, 2007). A row of patterns is
+//! useful after some other rows if it matches a value that none of them
+//! match, so an arm is unreachable if its pattern is not useful after the
+//! patterns of the arms before it, and the arms are exhaustive if a wildcard
+//! is not useful after all of them.
+
+use crate::front::ast::{EnumDecl, Pattern, Type};
+use std::fmt;
+
+/// Looks up the declaration of an enum by id (see `Type::NamedType`).
+pub type EnumLookup<'a> = dyn Fn(usize) -> Option<EnumDecl> + 'a;
+
+/// A pattern, reduced to what matters for usefulness.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pat {
+    /// matches any value, as a wildcard or a binding does.
+    Any,
+    /// matches the values built with the constructor, whose fields match the
+    /// patterns.
+    Ctor(Ctor, Vec<Pat>),
+}
+
+/// What a value is built with: a literal, which has no fields, or a variant,
+/// whose fields are the values of its payload.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ctor {
+    Bool(bool),
+    Int(i32),
+    Str(String),
+    Variant(String),
+}
+
+impl From<&Pattern> for Pat {
+    fn from(pattern: &Pattern) -> Self {
+        match *pattern {
+            Pattern::Wildcard(_) | Pattern::Binding(_) => Pat::Any,
+            Pattern::Bool(ref bool_expr) => Pat::Ctor(Ctor::Bool(bool_expr.value), Vec::new()),
+            Pattern::Int(ref int_expr) => Pat::Ctor(Ctor::Int(int_expr.value), Vec::new()),
+            Pattern::Str(ref str_expr) => Pat::Ctor(Ctor::Str(str_expr.value.clone()), Vec::new()),
+            Pattern::Variant(ref variant) => Pat::Ctor(
+                Ctor::Variant(variant.name.spelling.clone()),
+                variant.args.iter().map(Pat::from).collect(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Ctor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Ctor::Bool(bval) => write!(f, "{}", bval),
+            Ctor::Int(ival) => write!(f, "{}", ival),
+            Ctor::Str(ref sval) => write!(f, "{:?}", sval),
+            Ctor::Variant(ref name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Whether `row` matches a value of the types `typs` that none of `rows`
+/// match.
+pub fn is_useful(rows: &[Vec<Pat>], row: &[Pat], typs: &[Type], enums: &EnumLookup) -> bool {
+    let (head, rest) = match row.split_first() {
+        Some(split) => split,
+        None => return rows.is_empty(),
+    };
+
+    match *head {
+        Pat::Ctor(ref ctor, ref args) => {
+            let row = [args.as_slice(), rest].concat();
+            let typs = [
+                field_typs(ctor, args.len(), &typs[0], enums),
+                typs[1..].to_vec(),
+            ]
+            .concat();
+            is_useful(&specialize(rows, ctor, args.len()), &row, &typs, enums)
+        }
+        Pat::Any => match signature(&typs[0], enums) {
+            Some(ctors) if is_complete(rows, &ctors) => ctors.into_iter().any(|(ctor, fields)| {
+                let row = [vec![Pat::Any; fields.len()].as_slice(), rest].concat();
+                let typs = [fields.as_slice(), &typs[1..]].concat();
+                is_useful(&specialize(rows, &ctor, fields.len()), &row, &typs, enums)
+            }),
+            _ => is_useful(&default_rows(rows), rest, &typs[1..], enums),
+        },
+    }
+}
+
+/// A value of the types `typs` that none of `rows` match, if there is one,
+/// shown as a pattern for each type.
+pub fn missing(rows: &[Vec<Pat>], typs: &[Type], enums: &EnumLookup) -> Option<Vec<String>> {
+    let (typ, rest_typs) = match typs.split_first() {
+        Some(split) => split,
+        None => {
+            return if rows.is_empty() {
+                Some(Vec::new())
+            } else {
+                None
+            }
+        }
+    };
+
+    let ctors = signature(typ, enums);
+    if let Some(ref ctors) = ctors {
+        if is_complete(rows, ctors) {
+            return ctors.iter().find_map(|(ctor, fields)| {
+                let typs = [fields.as_slice(), rest_typs].concat();
+                let mut witness = missing(&specialize(rows, ctor, fields.len()), &typs, enums)?;
+                let rest = witness.split_off(fields.len());
+                Some([vec![show(ctor, &witness)], rest].concat())
+            });
+        }
+    }
+
+    // any value that the rows do not start with a constructor for is missing
+    // if the rest of it is.
+    let rest = missing(&default_rows(rows), rest_typs, enums)?;
+    let head = ctors
+        .and_then(|ctors| {
+            ctors
+                .into_iter()
+                .find(|(ctor, _)| !heads(rows).contains(&ctor))
+                .map(|(ctor, fields)| show(&ctor, &vec!["_".to_owned(); fields.len()]))
+        })
+        .unwrap_or_else(|| "_".to_owned());
+    Some([vec![head], rest].concat())
+}
+
+/// The constructors of the values of `typ`, with the types of their fields,
+/// if there are finitely many of them (for bools and enums).
+fn signature(typ: &Type, enums: &EnumLookup) -> Option<Vec<(Ctor, Vec<Type>)>> {
+    match *typ {
+        Type::BoolType => Some(vec![
+            (Ctor::Bool(true), Vec::new()),
+            (Ctor::Bool(false), Vec::new()),
+        ]),
+        Type::NamedType(_, Some(id)) => enums(id).map(|enum_decl| {
+            enum_decl
+                .variants
+                .into_iter()
+                .map(|(variant, payload_typs)| (Ctor::Variant(variant), payload_typs))
+                .collect()
+        }),
+        _ => None,
+    }
+}
+
+/// The types of the `arity` fields of `ctor`, a constructor of `typ`.
+fn field_typs(ctor: &Ctor, arity: usize, typ: &Type, enums: &EnumLookup) -> Vec<Type> {
+    match (ctor, typ) {
+        (Ctor::Variant(ref variant), Type::NamedType(_, Some(id))) => enums(*id)
+            .and_then(|enum_decl| enum_decl.payload_typs(variant).cloned())
+            .unwrap_or_else(|| vec![Type::AnyType; arity]),
+        _ => vec![Type::AnyType; arity],
+    }
+}
+
+/// The constructors that the rows start with.
+fn heads(rows: &[Vec<Pat>]) -> Vec<&Ctor> {
+    rows.iter()
+        .filter_map(|row| match row.first() {
+            Some(Pat::Ctor(ref ctor, _)) => Some(ctor),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the rows start with every constructor of the signature.
+fn is_complete(rows: &[Vec<Pat>], ctors: &[(Ctor, Vec<Type>)]) -> bool {
+    let heads = heads(rows);
+    ctors.iter().all(|(ctor, _)| heads.contains(&ctor))
+}
+
+/// The rows for the values built with `ctor`: those that start with `ctor`
+/// or with a pattern that matches any value, with the first pattern replaced
+/// by the patterns of the `arity` fields.
+fn specialize(rows: &[Vec<Pat>], ctor: &Ctor, arity: usize) -> Vec<Vec<Pat>> {
+    rows.iter()
+        .filter_map(|row| match row[0] {
+            Pat::Any => Some([vec![Pat::Any; arity].as_slice(), &row[1..]].concat()),
+            Pat::Ctor(ref head, ref args) if head == ctor => {
+                Some([args.as_slice(), &row[1..]].concat())
+            }
+            Pat::Ctor(..) => None,
+        })
+        .collect()
+}
+
+/// The rows for the values built with a constructor that no row starts
+/// with: those that start with a pattern that matches any value, without it.
+fn default_rows(rows: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    rows.iter()
+        .filter(|row| row[0] == Pat::Any)
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+fn show(ctor: &Ctor, fields: &[String]) -> String {
+    if fields.is_empty() {
+        ctor.to_string()
+    } else {
+        format!("{}({})", ctor, fields.join(", "))
+    }
+}
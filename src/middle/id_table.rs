@@ -8,12 +8,18 @@ pub enum DeclOrId {
     Id(Identifier),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct IdentificationTable {
     pub level: isize,
     pub id_table: HashMap<isize, HashMap<String, DeclOrId>>,
 }
 
+impl Default for IdentificationTable {
+    fn default() -> Self {
+        IdentificationTable::new()
+    }
+}
+
 impl IdentificationTable {
     pub fn new() -> Self {
         let mut id_table = HashMap::new();
@@ -30,12 +36,18 @@ impl IdentificationTable {
     }
 
     pub fn get_attr(&self, search_id: &str) -> Option<&DeclOrId> {
+        self.lookup(search_id).map(|(_, attr)| attr)
+    }
+
+    /// Find the innermost declaration of `search_id`, along with the level
+    /// of the scope that it was declared in.
+    pub fn lookup(&self, search_id: &str) -> Option<(isize, &DeclOrId)> {
         let mut level = self.level;
 
         while level >= 0 {
             for (id, attr) in self.id_table.get(&level).unwrap().iter() {
                 if id == search_id {
-                    return Some(attr);
+                    return Some((level, attr));
                 }
             }
 
@@ -45,6 +57,27 @@ impl IdentificationTable {
         None
     }
 
+    /// All the identifiers visible from the current scope, sorted by name.
+    pub fn identifiers(&self) -> Vec<&Identifier> {
+        let mut ids: Vec<&Identifier> = Vec::new();
+        let mut level = self.level;
+
+        while level >= 0 {
+            for attr in self.id_table.get(&level).unwrap().values() {
+                if let DeclOrId::Id(ref id) = attr {
+                    if !ids.iter().any(|seen| seen.spelling == id.spelling) {
+                        ids.push(id);
+                    }
+                }
+            }
+
+            level -= 1;
+        }
+
+        ids.sort_by(|lhs, rhs| lhs.spelling.cmp(&rhs.spelling));
+        ids
+    }
+
     pub fn open_scope(&mut self) {
         self.level += 1;
         self.id_table.insert(self.level, HashMap::new());
@@ -61,15 +94,15 @@ impl fmt::Display for IdentificationTable {
         let mut level = self.level;
 
         while level >= 0 {
-            let _ = write!(f, "Entries for level {}", level);
+            writeln!(f, "Entries for level {}", level)?;
 
             if let Some(mapping) = self.id_table.get(&level) {
                 for (id, attr) in mapping.iter() {
-                    let _ = write!(f, "\t{:?} => {:?}\n", id, attr);
+                    writeln!(f, "\t{:?} => {:?}", id, attr)?;
                 }
             }
             level -= 1;
         }
-        write!(f, "\n")
+        writeln!(f)
     }
 }
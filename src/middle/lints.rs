@@ -0,0 +1,624 @@
+use super::visitor::VisitorMut;
+use crate::diagnostics::{codes::ErrorCode, Diagnostics};
+use crate::error::{ExprError, Severity};
+use crate::front::ast::*;
+use crate::front::source_file::Span;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem;
+
+/// The analyses run by the `Linter`. Each lint reports a warning with its own
+/// code, and can be allowed or denied by name.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedVariables,
+    DeadAssignments,
+    SelfAssignment,
+    ConstantConditions,
+    UnaryPlus,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 5] = [
+        Lint::UnusedVariables,
+        Lint::DeadAssignments,
+        Lint::SelfAssignment,
+        Lint::ConstantConditions,
+        Lint::UnaryPlus,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Lint::UnusedVariables => "unused_variables",
+            Lint::DeadAssignments => "dead_assignments",
+            Lint::SelfAssignment => "self_assignment",
+            Lint::ConstantConditions => "constant_conditions",
+            Lint::UnaryPlus => "unary_plus",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.iter().find(|lint| lint.name() == name).copied()
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match *self {
+            Lint::UnusedVariables => ErrorCode::UnusedVariable,
+            Lint::DeadAssignments => ErrorCode::DeadAssignment,
+            Lint::SelfAssignment => ErrorCode::SelfAssignment,
+            Lint::ConstantConditions => ErrorCode::ConstantCondition,
+            Lint::UnaryPlus => ErrorCode::UnaryPlus,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                LintLevel::Allow => "allow",
+                LintLevel::Warn => "warn",
+                LintLevel::Deny => "deny",
+            }
+        )
+    }
+}
+
+/// The level of every lint - lints warn unless set otherwise.
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels {
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintLevels {
+    pub fn new() -> Self {
+        LintLevels {
+            levels: HashMap::new(),
+        }
+    }
+
+    pub fn set(&mut self, lint: Lint, level: LintLevel) {
+        self.levels.insert(lint, level);
+    }
+
+    pub fn set_all(&mut self, level: LintLevel) {
+        for lint in Lint::ALL {
+            self.set(lint, level);
+        }
+    }
+
+    pub fn get(&self, lint: Lint) -> LintLevel {
+        self.levels.get(&lint).copied().unwrap_or(LintLevel::Warn)
+    }
+}
+
+/// Looks for likely mistakes in a program that type-checks. The lints are
+/// reported as warnings (or as errors, if denied) to `diagnostics`.
+///
+/// Variables are tracked in the order in which the program runs - the rhs of
+/// an assignment is visited before the variable that it is assigned to. Each
+/// declaration is a distinct variable, even if it shadows another one. The
+/// branches of an if are visited one after the other, but a value assigned
+/// before a branch may still be read on another path, so it is only reported
+/// as dead when it is overwritten outside the branch. The body of a loop is a
+/// branch too, and a value assigned in it may be read by the next iteration.
+pub struct Linter<'a> {
+    levels: &'a LintLevels,
+    reports: Vec<ExprError>,
+    /// the name and the declaration of each variable, indexed by variable.
+    vars: Vec<(String, Span)>,
+    /// the variables declared in each enclosing scope, innermost last.
+    scopes: Vec<HashMap<String, usize>>,
+    /// the variables that are read anywhere.
+    read: HashSet<usize>,
+    /// the latest assignment to each variable, if it has not been read yet,
+    /// along with the branches that it was made in.
+    unread: HashMap<usize, (Span, Vec<usize>)>,
+    /// the branches that are being visited, outermost first.
+    branches: Vec<usize>,
+    branch_count: usize,
+    /// the branch of each enclosing loop and the variables read in it,
+    /// innermost last.
+    loops: Vec<(usize, HashSet<usize>)>,
+}
+
+impl<'a> Linter<'a> {
+    pub fn new(levels: &'a LintLevels) -> Self {
+        Linter {
+            levels,
+            reports: Vec::new(),
+            vars: Vec::new(),
+            scopes: vec![HashMap::new()],
+            read: HashSet::new(),
+            unread: HashMap::new(),
+            branches: Vec::new(),
+            branch_count: 0,
+            loops: Vec::new(),
+        }
+    }
+
+    pub fn lint(&mut self, ast: &mut Ast, diagnostics: &mut Diagnostics) {
+        self.visit_ast(ast);
+
+        let unused = self
+            .vars
+            .iter()
+            .enumerate()
+            .filter(|(var, _)| !self.read.contains(var))
+            .map(|(_, (name, span))| (name.clone(), span.clone()))
+            .collect::<Vec<(String, Span)>>();
+
+        for (name, span) in unused {
+            self.report(
+                Lint::UnusedVariables,
+                ExprError::with_span(
+                    ErrorCode::UnusedVariable,
+                    format!("variable {:?} is assigned but never read", name),
+                    span,
+                ),
+            );
+        }
+
+        for err in self.reports.drain(..) {
+            diagnostics.push(err);
+        }
+    }
+
+    fn report(&mut self, lint: Lint, err: ExprError) {
+        let level = self.levels.get(lint);
+        let severity = match level {
+            LintLevel::Allow => return,
+            LintLevel::Warn => Severity::Warning,
+            LintLevel::Deny => Severity::Error,
+        };
+
+        self.reports
+            .push(err.with_severity(severity).with_note(format!(
+                "lint {} is set to {}",
+                lint.name(),
+                level
+            )));
+    }
+
+    fn lookup_var(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    fn read_var(&mut self, name: &str) {
+        if let Some(var) = self.lookup_var(name) {
+            self.read.insert(var);
+            self.unread.remove(&var);
+            if let Some((_, reads)) = self.loops.last_mut() {
+                reads.insert(var);
+            }
+        }
+    }
+
+    fn declare_var(&mut self, vname: &VnameExpr) {
+        let name = &vname.id.spelling;
+
+        self.vars.push((name.clone(), vname.span.clone()));
+        let var = self.vars.len() - 1;
+        self.scopes.last_mut().unwrap().insert(name.clone(), var);
+        self.unread
+            .insert(var, (vname.span.clone(), self.branches.clone()));
+    }
+
+    /// Declare the names that a pattern binds.
+    fn declare_pattern(&mut self, pattern: &Pattern) {
+        match *pattern {
+            Pattern::Binding(ref vname) => self.declare_var(vname),
+            Pattern::Variant(ref variant) => {
+                for arg in &variant.args {
+                    self.declare_pattern(arg);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn assign_var(&mut self, vname: &VnameExpr) {
+        let name = &vname.id.spelling;
+        let var = match self.lookup_var(name) {
+            Some(var) => var,
+            None => return,
+        };
+
+        let prev = self.unread.remove(&var);
+        if let Some((prev_span, _)) =
+            prev.filter(|(_, prev_branches)| prev_branches.starts_with(&self.branches))
+        {
+            self.report(
+                Lint::DeadAssignments,
+                ExprError::with_span(
+                    ErrorCode::DeadAssignment,
+                    format!("value assigned to {:?} is never read", name),
+                    prev_span,
+                )
+                .with_label(vname.span.clone(), "overwritten here".to_owned()),
+            );
+        }
+
+        self.unread
+            .insert(var, (vname.span.clone(), self.branches.clone()));
+    }
+
+    /// Assign to the target of an assignment: a variable, the elements of a
+    /// tuple of targets, an index expr (which reads the list and the index)
+    /// or a field expr (which reads the record).
+    fn assign_target(&mut self, target: &mut Expr) {
+        match *target {
+            Expr::VnameExpr(ref vname) => self.assign_var(vname),
+            Expr::TupleExpr(ref mut tuple_expr) => {
+                for elem in &mut tuple_expr.elems {
+                    self.assign_target(elem);
+                }
+            }
+            ref mut target => self.visit_expr(target),
+        }
+    }
+
+    fn visit_branch(&mut self, branch: &mut Expr) {
+        self.branch_count += 1;
+        self.branches.push(self.branch_count);
+        self.visit_expr(branch);
+        self.branches.pop();
+    }
+
+    fn visit_loop(&mut self, cond: Option<&mut Expr>, body: &mut Expr) {
+        self.branch_count += 1;
+        let branch = self.branch_count;
+        self.branches.push(branch);
+        self.loops.push((branch, HashSet::new()));
+
+        if let Some(cond) = cond {
+            self.visit_expr(cond);
+        }
+        self.visit_expr(body);
+
+        self.branches.pop();
+        let (_, reads) = self.loops.pop().unwrap();
+
+        // a value assigned in the loop is not dead if the loop reads it again.
+        self.unread
+            .retain(|var, (_, branches)| !(reads.contains(var) && branches.contains(&branch)));
+        if let Some((_, outer_reads)) = self.loops.last_mut() {
+            outer_reads.extend(reads);
+        }
+    }
+
+    /// Leave the innermost loop early - the values assigned in it so far may
+    /// be read after the loop, or by its next iteration.
+    fn leave_loop(&mut self) {
+        if let Some(&(branch, _)) = self.loops.last() {
+            self.unread
+                .retain(|_, (_, branches)| !branches.contains(&branch));
+        }
+    }
+}
+
+/// Check if two expressions always evaluate to the same value, i.e., if they
+/// are the same and have no side effects.
+fn same_expr(lhs: &Expr, rhs: &Expr) -> bool {
+    match (lhs, rhs) {
+        (Expr::BoolExpr(lhs), Expr::BoolExpr(rhs)) => lhs.value == rhs.value,
+        (Expr::IntegerExpr(lhs), Expr::IntegerExpr(rhs)) => lhs.value == rhs.value,
+        (Expr::StringExpr(lhs), Expr::StringExpr(rhs)) => lhs.value == rhs.value,
+        (Expr::CastExpr(lhs), Expr::CastExpr(rhs)) => {
+            lhs.target == rhs.target && same_expr(&lhs.expr, &rhs.expr)
+        }
+        (Expr::VnameExpr(lhs), Expr::VnameExpr(rhs)) => lhs.id.spelling == rhs.id.spelling,
+        (Expr::IndexExpr(lhs), Expr::IndexExpr(rhs)) => {
+            same_expr(&lhs.list, &rhs.list) && same_expr(&lhs.index, &rhs.index)
+        }
+        // ++ and -- change the variable, so they differ each time.
+        (Expr::UnaryExpr(lhs), Expr::UnaryExpr(rhs)) => {
+            lhs.op == rhs.op && !lhs.op.is_assignment() && same_expr(&lhs.elem, &rhs.elem)
+        }
+        (Expr::BinaryExpr(lhs), Expr::BinaryExpr(rhs)) => {
+            lhs.op == rhs.op && same_expr(&lhs.lhs, &rhs.lhs) && same_expr(&lhs.rhs, &rhs.rhs)
+        }
+        _ => false,
+    }
+}
+
+/// Check if a value of type `typ` can hold a float, e.g., a tuple of floats.
+/// The fields of structs and the payloads of enums are not known here, so
+/// their values are taken to hold one.
+fn may_hold_float(typ: &Type) -> bool {
+    match *typ {
+        Type::FloatType | Type::AnyType | Type::NamedType(..) => true,
+        Type::ListType(ref elem_typ) | Type::OptionType(ref elem_typ) => may_hold_float(elem_typ),
+        Type::TupleType(ref elem_typs) => elem_typs.iter().any(may_hold_float),
+        _ => false,
+    }
+}
+
+impl VisitorMut for Linter<'_> {
+    type Result = ();
+
+    fn visit_assign_expr(&mut self, ass_expr: &mut AssignExpr) -> Self::Result {
+        if let Expr::VnameExpr(ref vname) = *ass_expr.target {
+            // compound assignments read the variable before the rhs.
+            if ass_expr.op != BinaryOperator::Assign {
+                self.read_var(&vname.id.spelling);
+            } else if let Expr::VnameExpr(ref rhs) = *ass_expr.expr {
+                if rhs.id.spelling == vname.id.spelling {
+                    self.report(
+                        Lint::SelfAssignment,
+                        ExprError::with_span(
+                            ErrorCode::SelfAssignment,
+                            format!("variable {:?} is assigned to itself", vname.id.spelling),
+                            ass_expr.span.clone(),
+                        ),
+                    );
+                }
+            }
+        }
+
+        self.visit_expr(&mut ass_expr.expr);
+
+        self.assign_target(&mut ass_expr.target);
+    }
+
+    fn visit_ast(&mut self, ast: &mut Ast) -> Self::Result {
+        for expr in &mut ast.exprs {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_binary_expr(&mut self, bin_expr: &mut BinaryExpr) -> Self::Result {
+        self.visit_expr(&mut bin_expr.lhs);
+        self.visit_expr(&mut bin_expr.rhs);
+
+        let always = match bin_expr.op {
+            BinaryOperator::Equal
+            | BinaryOperator::GreaterThanOrEqual
+            | BinaryOperator::LessThanOrEqual => true,
+            BinaryOperator::NotEqual | BinaryOperator::GreaterThan | BinaryOperator::LessThan => {
+                false
+            }
+            _ => return,
+        };
+
+        // x == x is false for a float x that is NaN, and so for any value
+        // that holds one.
+        if bin_expr.lhs.typ().is_some_and(|typ| may_hold_float(&typ)) {
+            return;
+        }
+
+        if same_expr(&bin_expr.lhs, &bin_expr.rhs) {
+            self.report(
+                Lint::ConstantConditions,
+                ExprError::with_span(
+                    ErrorCode::ConstantCondition,
+                    format!("comparison is always {}", always),
+                    bin_expr.span.clone(),
+                )
+                .with_help(format!(
+                    "both sides of {} are the same expression",
+                    bin_expr.op
+                )),
+            );
+        }
+    }
+
+    fn visit_block_expr(&mut self, block_expr: &mut BlockExpr) -> Self::Result {
+        self.scopes.push(HashMap::new());
+        for expr in &mut block_expr.exprs {
+            self.visit_expr(expr);
+        }
+        self.scopes.pop();
+    }
+
+    fn visit_bool_expr(&mut self, _bool_expr: &mut BoolExpr) -> Self::Result {}
+
+    fn visit_break_expr(&mut self, _break_expr: &mut BreakExpr) -> Self::Result {
+        self.leave_loop();
+    }
+
+    fn visit_call_expr(&mut self, call_expr: &mut CallExpr) -> Self::Result {
+        self.visit_expr(&mut call_expr.callee);
+        for arg in &mut call_expr.args {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_cast_expr(&mut self, cast_expr: &mut CastExpr) -> Self::Result {
+        self.visit_expr(&mut cast_expr.expr);
+    }
+
+    fn visit_continue_expr(&mut self, _continue_expr: &mut ContinueExpr) -> Self::Result {
+        self.leave_loop();
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) -> Self::Result {
+        match expr {
+            Expr::AssignExpr(ref mut ass_expr) => self.visit_assign_expr(ass_expr),
+            Expr::BinaryExpr(ref mut bin_expr) => self.visit_binary_expr(bin_expr),
+            Expr::BlockExpr(ref mut block_expr) => self.visit_block_expr(block_expr),
+            Expr::BoolExpr(ref mut bool_expr) => self.visit_bool_expr(bool_expr),
+            Expr::BreakExpr(ref mut break_expr) => self.visit_break_expr(break_expr),
+            Expr::CallExpr(ref mut call_expr) => self.visit_call_expr(call_expr),
+            Expr::CastExpr(ref mut cast_expr) => self.visit_cast_expr(cast_expr),
+            Expr::ContinueExpr(ref mut continue_expr) => self.visit_continue_expr(continue_expr),
+            Expr::FieldExpr(ref mut field_expr) => self.visit_field_expr(field_expr),
+            Expr::FloatExpr(ref mut float_expr) => self.visit_float_expr(float_expr),
+            Expr::FnExpr(ref mut fn_expr) => self.visit_fn_expr(fn_expr),
+            Expr::LambdaExpr(ref mut lambda_expr) => self.visit_lambda_expr(lambda_expr),
+            Expr::ForExpr(ref mut for_expr) => self.visit_for_expr(for_expr),
+            Expr::IfExpr(ref mut if_expr) => self.visit_if_expr(if_expr),
+            Expr::IndexExpr(ref mut index_expr) => self.visit_index_expr(index_expr),
+            Expr::IntegerExpr(ref mut int_expr) => self.visit_integer_expr(int_expr),
+            Expr::LetExpr(ref mut let_expr) => self.visit_let_expr(let_expr),
+            Expr::ListExpr(ref mut list_expr) => self.visit_list_expr(list_expr),
+            Expr::PrintExpr(ref mut print_expr) => self.visit_print_expr(print_expr),
+            Expr::RecordExpr(ref mut record_expr) => self.visit_record_expr(record_expr),
+            Expr::EnumExpr(ref mut enum_expr) => self.visit_enum_expr(enum_expr),
+            Expr::MatchExpr(ref mut match_expr) => self.visit_match_expr(match_expr),
+            Expr::NoneExpr(ref mut none_expr) => self.visit_none_expr(none_expr),
+            Expr::StringExpr(ref mut str_expr) => self.visit_string_expr(str_expr),
+            Expr::StructExpr(ref mut struct_expr) => self.visit_struct_expr(struct_expr),
+            Expr::TernaryExpr(ref mut ternary_expr) => self.visit_ternary_expr(ternary_expr),
+            Expr::TupleExpr(ref mut tuple_expr) => self.visit_tuple_expr(tuple_expr),
+            Expr::UnaryExpr(ref mut un_expr) => self.visit_unary_expr(un_expr),
+            Expr::VnameExpr(ref mut vname_expr) => self.visit_vname_expr(vname_expr),
+            Expr::WhileExpr(ref mut while_expr) => self.visit_while_expr(while_expr),
+        }
+    }
+
+    fn visit_enum_expr(&mut self, _enum_expr: &mut EnumExpr) -> Self::Result {}
+
+    fn visit_field_expr(&mut self, field_expr: &mut FieldExpr) -> Self::Result {
+        self.visit_expr(&mut field_expr.expr);
+    }
+
+    fn visit_float_expr(&mut self, _float_expr: &mut FloatExpr) -> Self::Result {}
+
+    fn visit_fn_expr(&mut self, fn_expr: &mut FnExpr) -> Self::Result {
+        // the body runs when the function is called, outside of any loop.
+        let loops = mem::take(&mut self.loops);
+        self.scopes.push(HashMap::new());
+        for param in &fn_expr.params {
+            self.declare_var(&param.vname);
+            // parameters are part of the signature, even if they are unused.
+            self.read_var(&param.vname.id.spelling);
+        }
+        self.visit_branch(&mut fn_expr.body);
+        self.scopes.pop();
+        self.loops = loops;
+    }
+
+    fn visit_for_expr(&mut self, for_expr: &mut ForExpr) -> Self::Result {
+        self.visit_expr(&mut for_expr.start);
+        self.visit_expr(&mut for_expr.end);
+
+        self.scopes.push(HashMap::new());
+        self.declare_var(&for_expr.vname);
+        // the loop variable counts the iterations, even if it is not read.
+        self.read_var(&for_expr.vname.id.spelling);
+        self.visit_loop(None, &mut for_expr.body);
+        self.scopes.pop();
+    }
+
+    fn visit_identifier(&mut self, _id: &mut Identifier) -> Self::Result {}
+
+    fn visit_if_expr(&mut self, if_expr: &mut IfExpr) -> Self::Result {
+        self.visit_expr(&mut if_expr.cond);
+        self.visit_branch(&mut if_expr.then_branch);
+        if let Some(ref mut else_branch) = if_expr.else_branch {
+            self.visit_branch(else_branch);
+        }
+    }
+
+    fn visit_index_expr(&mut self, index_expr: &mut IndexExpr) -> Self::Result {
+        self.visit_expr(&mut index_expr.list);
+        self.visit_expr(&mut index_expr.index);
+    }
+
+    fn visit_integer_expr(&mut self, _int_expr: &mut IntegerExpr) -> Self::Result {}
+
+    fn visit_lambda_expr(&mut self, lambda_expr: &mut LambdaExpr) -> Self::Result {
+        // the captured variables are read when the closure is created, and
+        // the body runs when it is called, outside of any loop.
+        let loops = mem::take(&mut self.loops);
+        self.scopes.push(HashMap::new());
+        for param in &lambda_expr.params {
+            self.declare_var(&param.vname);
+            self.read_var(&param.vname.id.spelling);
+        }
+        self.visit_branch(&mut lambda_expr.body);
+        self.scopes.pop();
+        self.loops = loops;
+    }
+
+    fn visit_let_expr(&mut self, let_expr: &mut LetExpr) -> Self::Result {
+        self.visit_expr(&mut let_expr.expr);
+        self.declare_var(&let_expr.vname);
+    }
+
+    fn visit_list_expr(&mut self, list_expr: &mut ListExpr) -> Self::Result {
+        for elem in &mut list_expr.elems {
+            self.visit_expr(elem);
+        }
+    }
+
+    fn visit_match_expr(&mut self, match_expr: &mut MatchExpr) -> Self::Result {
+        self.visit_expr(&mut match_expr.expr);
+        for arm in &mut match_expr.arms {
+            self.scopes.push(HashMap::new());
+            self.declare_pattern(&arm.pattern);
+            self.visit_branch(&mut arm.body);
+            self.scopes.pop();
+        }
+    }
+
+    fn visit_none_expr(&mut self, _none_expr: &mut NoneExpr) -> Self::Result {}
+
+    fn visit_print_expr(&mut self, print_expr: &mut PrintExpr) -> Self::Result {
+        self.visit_expr(&mut print_expr.expr);
+    }
+
+    fn visit_record_expr(&mut self, record_expr: &mut RecordExpr) -> Self::Result {
+        for (_, field_expr) in &mut record_expr.fields {
+            self.visit_expr(field_expr);
+        }
+    }
+
+    fn visit_string_expr(&mut self, _str_expr: &mut StringExpr) -> Self::Result {}
+
+    fn visit_struct_expr(&mut self, _struct_expr: &mut StructExpr) -> Self::Result {}
+
+    fn visit_ternary_expr(&mut self, ternary_expr: &mut TernaryExpr) -> Self::Result {
+        self.visit_expr(&mut ternary_expr.cond);
+        self.visit_branch(&mut ternary_expr.then_expr);
+        self.visit_branch(&mut ternary_expr.else_expr);
+    }
+
+    fn visit_tuple_expr(&mut self, tuple_expr: &mut TupleExpr) -> Self::Result {
+        for elem in &mut tuple_expr.elems {
+            self.visit_expr(elem);
+        }
+    }
+
+    fn visit_unary_expr(&mut self, un_expr: &mut UnaryExpr) -> Self::Result {
+        if un_expr.op == UnaryOperator::UnaryPlus {
+            self.report(
+                Lint::UnaryPlus,
+                ExprError::with_span(
+                    ErrorCode::UnaryPlus,
+                    "unary plus has no effect".to_owned(),
+                    un_expr.span.clone(),
+                )
+                .with_help("remove the +".to_owned()),
+            );
+        }
+
+        self.visit_expr(&mut un_expr.elem);
+
+        // ++ and -- read the variable and then assign to it.
+        if let (true, Expr::VnameExpr(ref vname)) = (un_expr.op.is_assignment(), &*un_expr.elem) {
+            self.assign_var(vname);
+        }
+    }
+
+    fn visit_vname_expr(&mut self, vname_expr: &mut VnameExpr) -> Self::Result {
+        self.read_var(&vname_expr.id.spelling);
+    }
+
+    fn visit_while_expr(&mut self, while_expr: &mut WhileExpr) -> Self::Result {
+        self.visit_loop(Some(&mut while_expr.cond), &mut while_expr.body);
+    }
+}
@@ -1,4 +1,7 @@
 pub mod checker;
+pub mod exhaustiveness;
 pub mod id_table;
+pub mod lints;
+pub mod printer;
 pub mod stdenv;
-pub mod visitor;
\ No newline at end of file
+pub mod visitor;
@@ -0,0 +1,397 @@
+use super::visitor::VisitorMut;
+use crate::front::ast::*;
+use crate::front::source_file::Span;
+use std::fmt::Write;
+
+/// Pretty-prints an ast as an indented tree, one node per line, along with
+/// the type of each node (if it has been type-checked) and its span.
+pub struct AstPrinter {
+    indent: usize,
+    out: String,
+}
+
+impl Default for AstPrinter {
+    fn default() -> Self {
+        AstPrinter::new()
+    }
+}
+
+impl AstPrinter {
+    pub fn new() -> Self {
+        AstPrinter {
+            indent: 0,
+            out: String::new(),
+        }
+    }
+
+    pub fn print(ast: &mut Ast) -> String {
+        let mut printer = AstPrinter::new();
+        printer.visit_ast(ast);
+        printer.out
+    }
+
+    fn line(&mut self, node: &str, typ: &Option<Type>, span: &Span) {
+        let _ = write!(self.out, "{}{}", "  ".repeat(self.indent), node);
+        if let Some(ref typ) = typ {
+            let _ = write!(self.out, " : {}", typ);
+        }
+        let _ = writeln!(
+            self.out,
+            " @ {}:{}-{}:{}",
+            span.start.line, span.start.col, span.end.line, span.end.col
+        );
+    }
+
+    fn nested(&mut self, expr: &mut Expr) {
+        self.indent += 1;
+        self.visit_expr(expr);
+        self.indent -= 1;
+    }
+}
+
+impl VisitorMut for AstPrinter {
+    type Result = ();
+
+    fn visit_assign_expr(&mut self, ass_expr: &mut AssignExpr) -> Self::Result {
+        self.line(
+            &format!("AssignExpr {}", ass_expr.op),
+            &ass_expr.typ,
+            &ass_expr.span,
+        );
+        self.nested(&mut ass_expr.target);
+        self.nested(&mut ass_expr.expr);
+    }
+
+    fn visit_ast(&mut self, ast: &mut Ast) -> Self::Result {
+        for expr in &mut ast.exprs {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_binary_expr(&mut self, bin_expr: &mut BinaryExpr) -> Self::Result {
+        self.line(
+            &format!("BinaryExpr {}", bin_expr.op),
+            &bin_expr.typ,
+            &bin_expr.span,
+        );
+        self.nested(&mut bin_expr.lhs);
+        self.nested(&mut bin_expr.rhs);
+    }
+
+    fn visit_block_expr(&mut self, block_expr: &mut BlockExpr) -> Self::Result {
+        self.line("BlockExpr", &block_expr.typ, &block_expr.span);
+        for expr in &mut block_expr.exprs {
+            self.nested(expr);
+        }
+    }
+
+    fn visit_bool_expr(&mut self, bool_expr: &mut BoolExpr) -> Self::Result {
+        self.line(
+            &format!("BoolExpr {}", bool_expr.value),
+            &None,
+            &bool_expr.span,
+        );
+    }
+
+    fn visit_break_expr(&mut self, break_expr: &mut BreakExpr) -> Self::Result {
+        self.line("BreakExpr", &None, &break_expr.span);
+    }
+
+    fn visit_call_expr(&mut self, call_expr: &mut CallExpr) -> Self::Result {
+        self.line("CallExpr", &call_expr.typ, &call_expr.span);
+        self.nested(&mut call_expr.callee);
+        for arg in &mut call_expr.args {
+            self.nested(arg);
+        }
+    }
+
+    fn visit_cast_expr(&mut self, cast_expr: &mut CastExpr) -> Self::Result {
+        self.line(
+            &format!("CastExpr as {}", cast_expr.target),
+            &cast_expr.typ,
+            &cast_expr.span,
+        );
+        self.nested(&mut cast_expr.expr);
+    }
+
+    fn visit_continue_expr(&mut self, continue_expr: &mut ContinueExpr) -> Self::Result {
+        self.line("ContinueExpr", &None, &continue_expr.span);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) -> Self::Result {
+        match expr {
+            Expr::AssignExpr(ref mut ass_expr) => self.visit_assign_expr(ass_expr),
+            Expr::BinaryExpr(ref mut bin_expr) => self.visit_binary_expr(bin_expr),
+            Expr::BlockExpr(ref mut block_expr) => self.visit_block_expr(block_expr),
+            Expr::BoolExpr(ref mut bool_expr) => self.visit_bool_expr(bool_expr),
+            Expr::BreakExpr(ref mut break_expr) => self.visit_break_expr(break_expr),
+            Expr::CallExpr(ref mut call_expr) => self.visit_call_expr(call_expr),
+            Expr::CastExpr(ref mut cast_expr) => self.visit_cast_expr(cast_expr),
+            Expr::ContinueExpr(ref mut continue_expr) => self.visit_continue_expr(continue_expr),
+            Expr::FieldExpr(ref mut field_expr) => self.visit_field_expr(field_expr),
+            Expr::FloatExpr(ref mut float_expr) => self.visit_float_expr(float_expr),
+            Expr::FnExpr(ref mut fn_expr) => self.visit_fn_expr(fn_expr),
+            Expr::LambdaExpr(ref mut lambda_expr) => self.visit_lambda_expr(lambda_expr),
+            Expr::ForExpr(ref mut for_expr) => self.visit_for_expr(for_expr),
+            Expr::IfExpr(ref mut if_expr) => self.visit_if_expr(if_expr),
+            Expr::IndexExpr(ref mut index_expr) => self.visit_index_expr(index_expr),
+            Expr::IntegerExpr(ref mut int_expr) => self.visit_integer_expr(int_expr),
+            Expr::LetExpr(ref mut let_expr) => self.visit_let_expr(let_expr),
+            Expr::ListExpr(ref mut list_expr) => self.visit_list_expr(list_expr),
+            Expr::PrintExpr(ref mut print_expr) => self.visit_print_expr(print_expr),
+            Expr::RecordExpr(ref mut record_expr) => self.visit_record_expr(record_expr),
+            Expr::EnumExpr(ref mut enum_expr) => self.visit_enum_expr(enum_expr),
+            Expr::MatchExpr(ref mut match_expr) => self.visit_match_expr(match_expr),
+            Expr::NoneExpr(ref mut none_expr) => self.visit_none_expr(none_expr),
+            Expr::StringExpr(ref mut str_expr) => self.visit_string_expr(str_expr),
+            Expr::StructExpr(ref mut struct_expr) => self.visit_struct_expr(struct_expr),
+            Expr::TernaryExpr(ref mut ternary_expr) => self.visit_ternary_expr(ternary_expr),
+            Expr::TupleExpr(ref mut tuple_expr) => self.visit_tuple_expr(tuple_expr),
+            Expr::UnaryExpr(ref mut un_expr) => self.visit_unary_expr(un_expr),
+            Expr::VnameExpr(ref mut vname_expr) => self.visit_vname_expr(vname_expr),
+            Expr::WhileExpr(ref mut while_expr) => self.visit_while_expr(while_expr),
+        }
+    }
+
+    fn visit_enum_expr(&mut self, enum_expr: &mut EnumExpr) -> Self::Result {
+        let variants = enum_expr
+            .variants
+            .iter()
+            .map(|variant| {
+                let payload = variant
+                    .payload
+                    .iter()
+                    .map(|(typ, _)| typ.to_string())
+                    .collect::<Vec<_>>();
+                if payload.is_empty() {
+                    variant.name.spelling.clone()
+                } else {
+                    format!("{}({})", variant.name.spelling, payload.join(", "))
+                }
+            })
+            .collect::<Vec<_>>();
+        self.line(
+            &format!(
+                "EnumExpr {} {{ {} }}",
+                enum_expr.name.spelling,
+                variants.join(", ")
+            ),
+            &None,
+            &enum_expr.span,
+        );
+    }
+
+    fn visit_field_expr(&mut self, field_expr: &mut FieldExpr) -> Self::Result {
+        self.line(
+            &format!("FieldExpr .{}", field_expr.field),
+            &field_expr.typ,
+            &field_expr.span,
+        );
+        self.nested(&mut field_expr.expr);
+    }
+
+    fn visit_float_expr(&mut self, float_expr: &mut FloatExpr) -> Self::Result {
+        self.line(
+            &format!("FloatExpr {:?}", float_expr.value),
+            &None,
+            &float_expr.span,
+        );
+    }
+
+    fn visit_fn_expr(&mut self, fn_expr: &mut FnExpr) -> Self::Result {
+        let params = fn_expr
+            .params
+            .iter()
+            .map(|param| format!("{}: {}", param.vname.id.spelling, param.typ))
+            .collect::<Vec<String>>()
+            .join(", ");
+        self.line(
+            &format!(
+                "FnExpr {}({}) -> {}",
+                fn_expr.vname.id.spelling, params, fn_expr.ret_typ
+            ),
+            &None,
+            &fn_expr.span,
+        );
+        self.nested(&mut fn_expr.body);
+    }
+
+    fn visit_for_expr(&mut self, for_expr: &mut ForExpr) -> Self::Result {
+        self.line(
+            &format!("ForExpr {}", for_expr.vname.id.spelling),
+            &None,
+            &for_expr.span,
+        );
+        self.nested(&mut for_expr.start);
+        self.nested(&mut for_expr.end);
+        self.nested(&mut for_expr.body);
+    }
+
+    fn visit_identifier(&mut self, _id: &mut Identifier) -> Self::Result {}
+
+    fn visit_if_expr(&mut self, if_expr: &mut IfExpr) -> Self::Result {
+        self.line("IfExpr", &if_expr.typ, &if_expr.span);
+        self.nested(&mut if_expr.cond);
+        self.nested(&mut if_expr.then_branch);
+        if let Some(ref mut else_branch) = if_expr.else_branch {
+            self.nested(else_branch);
+        }
+    }
+
+    fn visit_index_expr(&mut self, index_expr: &mut IndexExpr) -> Self::Result {
+        self.line("IndexExpr", &index_expr.typ, &index_expr.span);
+        self.nested(&mut index_expr.list);
+        self.nested(&mut index_expr.index);
+    }
+
+    fn visit_integer_expr(&mut self, int_expr: &mut IntegerExpr) -> Self::Result {
+        self.line(
+            &format!("IntegerExpr {}", int_expr.value),
+            &None,
+            &int_expr.span,
+        );
+    }
+
+    fn visit_lambda_expr(&mut self, lambda_expr: &mut LambdaExpr) -> Self::Result {
+        let params = lambda_expr
+            .params
+            .iter()
+            .map(|param| match param.vname.typ {
+                Some(ref typ) => format!("{}: {}", param.vname.id.spelling, typ),
+                None => param.vname.id.spelling.clone(),
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        self.line(
+            &format!("LambdaExpr |{}|", params),
+            &lambda_expr.typ,
+            &lambda_expr.span,
+        );
+        self.nested(&mut lambda_expr.body);
+    }
+
+    fn visit_let_expr(&mut self, let_expr: &mut LetExpr) -> Self::Result {
+        let node = match let_expr.annotation {
+            Some((ref annotation, _)) => format!("LetExpr annotated {}", annotation),
+            None => "LetExpr".to_owned(),
+        };
+        self.line(&node, &let_expr.typ, &let_expr.span);
+        self.indent += 1;
+        self.visit_vname_expr(&mut let_expr.vname);
+        self.indent -= 1;
+        self.nested(&mut let_expr.expr);
+    }
+
+    fn visit_list_expr(&mut self, list_expr: &mut ListExpr) -> Self::Result {
+        self.line("ListExpr", &list_expr.typ, &list_expr.span);
+        for elem in &mut list_expr.elems {
+            self.nested(elem);
+        }
+    }
+
+    fn visit_match_expr(&mut self, match_expr: &mut MatchExpr) -> Self::Result {
+        self.line("MatchExpr", &match_expr.typ, &match_expr.span);
+        self.nested(&mut match_expr.expr);
+        self.indent += 1;
+        for arm in &mut match_expr.arms {
+            self.line(&format!("MatchArm {}", arm.pattern), &None, &arm.span);
+            self.nested(&mut arm.body);
+        }
+        self.indent -= 1;
+    }
+
+    fn visit_none_expr(&mut self, none_expr: &mut NoneExpr) -> Self::Result {
+        self.line("NoneExpr", &none_expr.typ, &none_expr.span);
+    }
+
+    fn visit_print_expr(&mut self, print_expr: &mut PrintExpr) -> Self::Result {
+        self.line("PrintExpr", &None, &print_expr.span);
+        self.nested(&mut print_expr.expr);
+    }
+
+    fn visit_record_expr(&mut self, record_expr: &mut RecordExpr) -> Self::Result {
+        let fields = record_expr
+            .fields
+            .iter()
+            .map(|(field_id, _)| field_id.spelling.clone())
+            .collect::<Vec<_>>();
+        self.line(
+            &format!(
+                "RecordExpr {} {{ {} }}",
+                record_expr.name.spelling,
+                fields.join(", ")
+            ),
+            &record_expr.typ,
+            &record_expr.span,
+        );
+        for (_, field_expr) in &mut record_expr.fields {
+            self.nested(field_expr);
+        }
+    }
+
+    fn visit_string_expr(&mut self, str_expr: &mut StringExpr) -> Self::Result {
+        self.line(
+            &format!("StringExpr {:?}", str_expr.value),
+            &None,
+            &str_expr.span,
+        );
+    }
+
+    fn visit_struct_expr(&mut self, struct_expr: &mut StructExpr) -> Self::Result {
+        let fields = struct_expr
+            .fields
+            .iter()
+            .map(|field| format!("{}: {}", field.name.spelling, field.typ))
+            .collect::<Vec<_>>();
+        self.line(
+            &format!(
+                "StructExpr {} {{ {} }}",
+                struct_expr.name.spelling,
+                fields.join(", ")
+            ),
+            &None,
+            &struct_expr.span,
+        );
+    }
+
+    fn visit_ternary_expr(&mut self, ternary_expr: &mut TernaryExpr) -> Self::Result {
+        self.line("TernaryExpr", &ternary_expr.typ, &ternary_expr.span);
+        self.nested(&mut ternary_expr.cond);
+        self.nested(&mut ternary_expr.then_expr);
+        self.nested(&mut ternary_expr.else_expr);
+    }
+
+    fn visit_tuple_expr(&mut self, tuple_expr: &mut TupleExpr) -> Self::Result {
+        self.line("TupleExpr", &tuple_expr.typ, &tuple_expr.span);
+        for elem in &mut tuple_expr.elems {
+            self.nested(elem);
+        }
+    }
+
+    fn visit_unary_expr(&mut self, un_expr: &mut UnaryExpr) -> Self::Result {
+        let fixity = match un_expr.op {
+            UnaryOperator::PostfixDecrement | UnaryOperator::PostfixIncrement => "postfix ",
+            _ => "",
+        };
+        self.line(
+            &format!("UnaryExpr {}{}", fixity, un_expr.op),
+            &un_expr.typ,
+            &un_expr.span,
+        );
+        self.nested(&mut un_expr.elem);
+    }
+
+    fn visit_vname_expr(&mut self, vname_expr: &mut VnameExpr) -> Self::Result {
+        self.line(
+            &format!("VnameExpr {}", vname_expr.id.spelling),
+            &vname_expr.typ,
+            &vname_expr.span,
+        );
+    }
+
+    fn visit_while_expr(&mut self, while_expr: &mut WhileExpr) -> Self::Result {
+        self.line("WhileExpr", &None, &while_expr.span);
+        self.nested(&mut while_expr.cond);
+        self.nested(&mut while_expr.body);
+    }
+}
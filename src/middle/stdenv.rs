@@ -40,6 +40,17 @@ lazy_static! {
             ))),
         );
 
+        // ++ and --, in both their prefix and postfix forms.
+        for name in ["increment", "decrement"] {
+            m.insert(
+                name,
+                Decl::OperatorDecl(OperatorDecl::UnaryOperatorDecl(UnaryOperatorDecl::new(
+                    Type::IntType,
+                    Type::IntType,
+                ))),
+            );
+        }
+
         m.insert(
             "add",
             Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(BinaryOperatorDecl::new(
@@ -140,7 +151,7 @@ lazy_static! {
             Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(BinaryOperatorDecl::new(
                 Type::AnyType,
                 Type::AnyType,
-                Type::AnyType,
+                Type::BoolType,
             ))),
         );
         m.insert(
@@ -148,7 +159,7 @@ lazy_static! {
             Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(BinaryOperatorDecl::new(
                 Type::IntType,
                 Type::IntType,
-                Type::IntType,
+                Type::BoolType,
             ))),
         );
         m.insert(
@@ -156,7 +167,7 @@ lazy_static! {
             Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(BinaryOperatorDecl::new(
                 Type::IntType,
                 Type::IntType,
-                Type::IntType,
+                Type::BoolType,
             ))),
         );
         m.insert(
@@ -180,7 +191,7 @@ lazy_static! {
             Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(BinaryOperatorDecl::new(
                 Type::IntType,
                 Type::IntType,
-                Type::IntType,
+                Type::BoolType,
             ))),
         );
         m.insert(
@@ -188,7 +199,7 @@ lazy_static! {
             Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(BinaryOperatorDecl::new(
                 Type::IntType,
                 Type::IntType,
-                Type::IntType,
+                Type::BoolType,
             ))),
         );
         m.insert(
@@ -260,7 +271,7 @@ lazy_static! {
             Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(BinaryOperatorDecl::new(
                 Type::AnyType,
                 Type::AnyType,
-                Type::AnyType,
+                Type::BoolType,
             ))),
         );
         m.insert(
@@ -296,141 +307,273 @@ lazy_static! {
             ))),
         );
 
+        // float overloads - the checker prefers these for float operands.
+        for name in ["unary_plus_float", "unary_minus_float"] {
+            m.insert(
+                name,
+                Decl::OperatorDecl(OperatorDecl::UnaryOperatorDecl(UnaryOperatorDecl::new(
+                    Type::FloatType,
+                    Type::FloatType,
+                ))),
+            );
+        }
+        for name in [
+            "add_float",
+            "add_assign_float",
+            "sub_float",
+            "sub_assign_float",
+            "mul_float",
+            "mul_assign_float",
+            "div_float",
+            "div_assign_float",
+            "mod_float",
+            "mod_assign_float",
+        ] {
+            m.insert(
+                name,
+                Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(BinaryOperatorDecl::new(
+                    Type::FloatType,
+                    Type::FloatType,
+                    Type::FloatType,
+                ))),
+            );
+        }
+        for name in [
+            "greater_than_float",
+            "greater_than_or_equal_float",
+            "less_than_float",
+            "less_than_or_equal_float",
+        ] {
+            m.insert(
+                name,
+                Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(BinaryOperatorDecl::new(
+                    Type::FloatType,
+                    Type::FloatType,
+                    Type::BoolType,
+                ))),
+            );
+        }
+
+        // string overloads.
+        for name in ["add_string", "add_assign_string"] {
+            m.insert(
+                name,
+                Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(BinaryOperatorDecl::new(
+                    Type::StringType,
+                    Type::StringType,
+                    Type::StringType,
+                ))),
+            );
+        }
+        for name in [
+            "greater_than_string",
+            "greater_than_or_equal_string",
+            "less_than_string",
+            "less_than_or_equal_string",
+        ] {
+            m.insert(
+                name,
+                Decl::OperatorDecl(OperatorDecl::BinaryOperatorDecl(BinaryOperatorDecl::new(
+                    Type::StringType,
+                    Type::StringType,
+                    Type::BoolType,
+                ))),
+            );
+        }
+
+        // builtin functions.
+        m.insert(
+            "len",
+            Decl::FunctionDecl(FunctionDecl::new(vec![Type::StringType], Type::IntType)),
+        );
+
+        // builtin functions on lists, where any stands for the same element
+        // type throughout a signature. len is overloaded for lists with the
+        // _list suffix, like the operators are for floats and strings.
+        let any_list = || Type::ListType(Box::new(Type::AnyType));
+        m.insert(
+            "len_list",
+            Decl::FunctionDecl(FunctionDecl::new(vec![any_list()], Type::IntType)),
+        );
+        m.insert(
+            "push",
+            Decl::FunctionDecl(FunctionDecl::new(
+                vec![any_list(), Type::AnyType],
+                Type::UnitType,
+            )),
+        );
+        m.insert(
+            "pop",
+            Decl::FunctionDecl(FunctionDecl::new(vec![any_list()], Type::AnyType)),
+        );
+
+        // builtin functions on options, where any stands for the type of the
+        // value of the option.
+        let any_option = || Type::OptionType(Box::new(Type::AnyType));
+        m.insert(
+            "some",
+            Decl::FunctionDecl(FunctionDecl::new(vec![Type::AnyType], any_option())),
+        );
+        m.insert(
+            "is_some",
+            Decl::FunctionDecl(FunctionDecl::new(vec![any_option()], Type::BoolType)),
+        );
+
         m
     };
 }
 
-/// Loads the standard environment at level 0.
-pub fn load_stdenv(id_table: &mut IdentificationTable) {
-    id_table.save_attr(
+/// Loads the standard environment at level 0: the builtin functions into
+/// `id_table`, and the operators into `op_table`, where the declarations of
+/// the program cannot shadow them.
+pub fn load_stdenv(id_table: &mut IdentificationTable, op_table: &mut IdentificationTable) {
+    op_table.save_attr(
         "bitwise_not",
         DeclOrId::Decl(STDENV.get("bitwise_not").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "logical_not",
         DeclOrId::Decl(STDENV.get("logical_not").unwrap().clone()),
     );
 
-    id_table.save_attr(
+    op_table.save_attr(
         "unary_plus",
         DeclOrId::Decl(STDENV.get("unary_plus").unwrap().clone()),
     );
 
-    id_table.save_attr(
+    op_table.save_attr(
         "unary_minus",
         DeclOrId::Decl(STDENV.get("unary_minus").unwrap().clone()),
     );
 
-    id_table.save_attr(
+    op_table.save_attr(
+        "increment",
+        DeclOrId::Decl(STDENV.get("increment").unwrap().clone()),
+    );
+
+    op_table.save_attr(
+        "decrement",
+        DeclOrId::Decl(STDENV.get("decrement").unwrap().clone()),
+    );
+
+    op_table.save_attr(
         "assign",
         DeclOrId::Decl(STDENV.get("assign").unwrap().clone()),
     );
 
-    id_table.save_attr("add", DeclOrId::Decl(STDENV.get("add").unwrap().clone()));
-    id_table.save_attr(
+    op_table.save_attr("add", DeclOrId::Decl(STDENV.get("add").unwrap().clone()));
+    op_table.save_attr(
         "add_assign",
         DeclOrId::Decl(STDENV.get("add_assign").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "bitwise_and",
         DeclOrId::Decl(STDENV.get("bitwise_and").unwrap().clone()),
     );
 
-    id_table.save_attr(
+    op_table.save_attr(
         "bitwise_and_assign",
         DeclOrId::Decl(STDENV.get("bitwise_and_assign").unwrap().clone()),
     );
 
-    id_table.save_attr(
+    op_table.save_attr(
         "bitwise_or",
         DeclOrId::Decl(STDENV.get("bitwise_or").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "bitwise_or_assign",
         DeclOrId::Decl(STDENV.get("bitwise_or_assign").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "bitwise_xor",
         DeclOrId::Decl(STDENV.get("bitwise_xor").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "bitwise_xor_assign",
         DeclOrId::Decl(STDENV.get("bitwise_xor_assign").unwrap().clone()),
     );
-    id_table.save_attr("div", DeclOrId::Decl(STDENV.get("div").unwrap().clone()));
-    id_table.save_attr(
+    op_table.save_attr("div", DeclOrId::Decl(STDENV.get("div").unwrap().clone()));
+    op_table.save_attr(
         "div_assign",
         DeclOrId::Decl(STDENV.get("div_assign").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "equal",
         DeclOrId::Decl(STDENV.get("equal").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "greater_than",
         DeclOrId::Decl(STDENV.get("greater_than").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "greater_than_or_equal",
         DeclOrId::Decl(STDENV.get("greater_than_or_equal").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "left_shift",
         DeclOrId::Decl(STDENV.get("left_shift").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "left_shift_assign",
         DeclOrId::Decl(STDENV.get("left_shift_assign").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "less_than",
         DeclOrId::Decl(STDENV.get("less_than").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "less_than_or_equal",
         DeclOrId::Decl(STDENV.get("less_than_or_equal").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "logical_and",
         DeclOrId::Decl(STDENV.get("logical_and").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "logical_and_assign",
         DeclOrId::Decl(STDENV.get("logical_and_assign").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "logical_or",
         DeclOrId::Decl(STDENV.get("logical_or").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "logical_or_assign",
         DeclOrId::Decl(STDENV.get("logical_or_assign").unwrap().clone()),
     );
-    id_table.save_attr("mod", DeclOrId::Decl(STDENV.get("mod").unwrap().clone()));
-    id_table.save_attr(
+    op_table.save_attr("mod", DeclOrId::Decl(STDENV.get("mod").unwrap().clone()));
+    op_table.save_attr(
         "mod_assign",
         DeclOrId::Decl(STDENV.get("mod_assign").unwrap().clone()),
     );
-    id_table.save_attr("mul", DeclOrId::Decl(STDENV.get("mul").unwrap().clone()));
-    id_table.save_attr(
+    op_table.save_attr("mul", DeclOrId::Decl(STDENV.get("mul").unwrap().clone()));
+    op_table.save_attr(
         "mul_assign",
         DeclOrId::Decl(STDENV.get("mul_assign").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "not_equal",
         DeclOrId::Decl(STDENV.get("not_equal").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "right_shift",
         DeclOrId::Decl(STDENV.get("right_shift").unwrap().clone()),
     );
-    id_table.save_attr(
+    op_table.save_attr(
         "right_shift_assign",
         DeclOrId::Decl(STDENV.get("right_shift_assign").unwrap().clone()),
     );
-    id_table.save_attr("sub", DeclOrId::Decl(STDENV.get("sub").unwrap().clone()));
-    id_table.save_attr(
+    op_table.save_attr("sub", DeclOrId::Decl(STDENV.get("sub").unwrap().clone()));
+    op_table.save_attr(
         "sub_assign",
-        DeclOrId::Decl(STDENV.get("sub_assign").unwrap().clone().clone()),
+        DeclOrId::Decl(STDENV.get("sub_assign").unwrap().clone()),
     );
+
+    for (name, decl) in STDENV.iter() {
+        if name.ends_with("_float") || name.ends_with("_string") {
+            op_table.save_attr(name, DeclOrId::Decl(decl.clone()));
+        } else if matches!(decl, Decl::FunctionDecl(_)) && !name.ends_with("_list") {
+            id_table.save_attr(name, DeclOrId::Decl(decl.clone()));
+        }
+    }
 }
@@ -1,16 +1,79 @@
 use crate::front::ast::*;
 
+/// A visitor that changes the ast as it goes, e.g., to annotate it with types.
 pub trait VisitorMut {
     type Result;
 
     fn visit_assign_expr(&mut self, ass_expr: &mut AssignExpr) -> Self::Result;
     fn visit_ast(&mut self, ast: &mut Ast) -> Self::Result;
     fn visit_binary_expr(&mut self, bin_expr: &mut BinaryExpr) -> Self::Result;
-    fn visit_bool_expr(&mut self, expr: &mut bool) -> Self::Result;
+    fn visit_block_expr(&mut self, block_expr: &mut BlockExpr) -> Self::Result;
+    fn visit_bool_expr(&mut self, bool_expr: &mut BoolExpr) -> Self::Result;
+    fn visit_break_expr(&mut self, break_expr: &mut BreakExpr) -> Self::Result;
+    fn visit_call_expr(&mut self, call_expr: &mut CallExpr) -> Self::Result;
+    fn visit_cast_expr(&mut self, cast_expr: &mut CastExpr) -> Self::Result;
+    fn visit_continue_expr(&mut self, continue_expr: &mut ContinueExpr) -> Self::Result;
+    fn visit_enum_expr(&mut self, enum_expr: &mut EnumExpr) -> Self::Result;
     fn visit_expr(&mut self, expr: &mut Expr) -> Self::Result;
+    fn visit_field_expr(&mut self, field_expr: &mut FieldExpr) -> Self::Result;
+    fn visit_float_expr(&mut self, float_expr: &mut FloatExpr) -> Self::Result;
+    fn visit_fn_expr(&mut self, fn_expr: &mut FnExpr) -> Self::Result;
+    fn visit_for_expr(&mut self, for_expr: &mut ForExpr) -> Self::Result;
     fn visit_identifier(&mut self, id: &mut Identifier) -> Self::Result;
-    fn visit_integer_expr(&mut self, expr: &mut i32) -> Self::Result;
-    fn visit_print_expr(&mut self, expr: &mut Expr) -> Self::Result;
+    fn visit_if_expr(&mut self, if_expr: &mut IfExpr) -> Self::Result;
+    fn visit_index_expr(&mut self, index_expr: &mut IndexExpr) -> Self::Result;
+    fn visit_integer_expr(&mut self, int_expr: &mut IntegerExpr) -> Self::Result;
+    fn visit_lambda_expr(&mut self, lambda_expr: &mut LambdaExpr) -> Self::Result;
+    fn visit_let_expr(&mut self, let_expr: &mut LetExpr) -> Self::Result;
+    fn visit_list_expr(&mut self, list_expr: &mut ListExpr) -> Self::Result;
+    fn visit_match_expr(&mut self, match_expr: &mut MatchExpr) -> Self::Result;
+    fn visit_none_expr(&mut self, none_expr: &mut NoneExpr) -> Self::Result;
+    fn visit_print_expr(&mut self, print_expr: &mut PrintExpr) -> Self::Result;
+    fn visit_record_expr(&mut self, record_expr: &mut RecordExpr) -> Self::Result;
+    fn visit_string_expr(&mut self, str_expr: &mut StringExpr) -> Self::Result;
+    fn visit_struct_expr(&mut self, struct_expr: &mut StructExpr) -> Self::Result;
+    fn visit_ternary_expr(&mut self, ternary_expr: &mut TernaryExpr) -> Self::Result;
+    fn visit_tuple_expr(&mut self, tuple_expr: &mut TupleExpr) -> Self::Result;
     fn visit_unary_expr(&mut self, un_expr: &mut UnaryExpr) -> Self::Result;
     fn visit_vname_expr(&mut self, expr: &mut VnameExpr) -> Self::Result;
+    fn visit_while_expr(&mut self, while_expr: &mut WhileExpr) -> Self::Result;
+}
+
+/// A visitor that only reads the ast.
+pub trait Visitor {
+    type Result;
+
+    fn visit_assign_expr(&mut self, ass_expr: &AssignExpr) -> Self::Result;
+    fn visit_ast(&mut self, ast: &Ast) -> Self::Result;
+    fn visit_binary_expr(&mut self, bin_expr: &BinaryExpr) -> Self::Result;
+    fn visit_block_expr(&mut self, block_expr: &BlockExpr) -> Self::Result;
+    fn visit_bool_expr(&mut self, bool_expr: &BoolExpr) -> Self::Result;
+    fn visit_break_expr(&mut self, break_expr: &BreakExpr) -> Self::Result;
+    fn visit_call_expr(&mut self, call_expr: &CallExpr) -> Self::Result;
+    fn visit_cast_expr(&mut self, cast_expr: &CastExpr) -> Self::Result;
+    fn visit_continue_expr(&mut self, continue_expr: &ContinueExpr) -> Self::Result;
+    fn visit_enum_expr(&mut self, enum_expr: &EnumExpr) -> Self::Result;
+    fn visit_expr(&mut self, expr: &Expr) -> Self::Result;
+    fn visit_field_expr(&mut self, field_expr: &FieldExpr) -> Self::Result;
+    fn visit_float_expr(&mut self, float_expr: &FloatExpr) -> Self::Result;
+    fn visit_fn_expr(&mut self, fn_expr: &FnExpr) -> Self::Result;
+    fn visit_for_expr(&mut self, for_expr: &ForExpr) -> Self::Result;
+    fn visit_identifier(&mut self, id: &Identifier) -> Self::Result;
+    fn visit_if_expr(&mut self, if_expr: &IfExpr) -> Self::Result;
+    fn visit_index_expr(&mut self, index_expr: &IndexExpr) -> Self::Result;
+    fn visit_integer_expr(&mut self, int_expr: &IntegerExpr) -> Self::Result;
+    fn visit_lambda_expr(&mut self, lambda_expr: &LambdaExpr) -> Self::Result;
+    fn visit_let_expr(&mut self, let_expr: &LetExpr) -> Self::Result;
+    fn visit_list_expr(&mut self, list_expr: &ListExpr) -> Self::Result;
+    fn visit_match_expr(&mut self, match_expr: &MatchExpr) -> Self::Result;
+    fn visit_none_expr(&mut self, none_expr: &NoneExpr) -> Self::Result;
+    fn visit_print_expr(&mut self, print_expr: &PrintExpr) -> Self::Result;
+    fn visit_record_expr(&mut self, record_expr: &RecordExpr) -> Self::Result;
+    fn visit_string_expr(&mut self, str_expr: &StringExpr) -> Self::Result;
+    fn visit_struct_expr(&mut self, struct_expr: &StructExpr) -> Self::Result;
+    fn visit_ternary_expr(&mut self, ternary_expr: &TernaryExpr) -> Self::Result;
+    fn visit_tuple_expr(&mut self, tuple_expr: &TupleExpr) -> Self::Result;
+    fn visit_unary_expr(&mut self, un_expr: &UnaryExpr) -> Self::Result;
+    fn visit_vname_expr(&mut self, expr: &VnameExpr) -> Self::Result;
+    fn visit_while_expr(&mut self, while_expr: &WhileExpr) -> Self::Result;
 }
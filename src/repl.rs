@@ -0,0 +1,347 @@
+use crate::backend::interpreter::Interpreter;
+use crate::backend::runtime::ExprValue;
+use crate::diagnostics::{render::Renderer, Diagnostics};
+use crate::front::ast::{Ast, Decl, Type};
+use crate::front::token::TokenKind;
+use crate::front::{parser::Parser, scanner::Scanner, source_file::SourceFile};
+use crate::middle::checker::Checker;
+use crate::middle::id_table::DeclOrId;
+use crate::middle::printer::AstPrinter;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::mem;
+use std::path::PathBuf;
+
+/// An interactive session. The checker and the interpreter are kept alive
+/// across entries, so that variables defined by one entry can be used by the
+/// following ones.
+///
+/// Lines starting with a colon are meta-commands for inspecting the session
+/// (see `:help`).
+pub struct Repl {
+    checker: Checker,
+    interpreter: Interpreter,
+    entries: usize,
+    colour: bool,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Repl::new()
+    }
+}
+
+impl Repl {
+    const PROMPT: &'static str = "> ";
+    const CONTINUATION_PROMPT: &'static str = ". ";
+    const HISTORY_FILE: &'static str = ".expr_history";
+    const HELP: &'static str = "\
+:type <expr>    show the inferred type of <expr> without evaluating it
+:ast <expr>     show the ast of <expr>
+:tokens <expr>  show the tokens of <expr>
+:env            list the variables and the functions in the session, with their types and values
+:load <file>    load and run <file> in the session
+:reset          discard all the variables in the session
+:help           show this message
+:quit           end the session";
+
+    pub fn new() -> Self {
+        Repl {
+            checker: Checker::new(),
+            interpreter: Interpreter::new(),
+            entries: 0,
+            colour: io::stderr().is_terminal(),
+        }
+    }
+
+    /// Scan, parse, check and evaluate a single entry, returning its value
+    /// along with its inferred type.
+    ///
+    /// An entry is evaluated atomically - if it fails in any phase, the
+    /// session is restored to its state before the entry, including the
+    /// contents of the lists and the records that it changed.
+    pub fn eval(
+        &mut self,
+        source_file: &SourceFile,
+    ) -> Result<(ExprValue, Option<Type>), Diagnostics> {
+        let checker = self.checker.clone();
+        let interpreter = self.interpreter.clone();
+        let contents = self.interpreter.runtime().contents();
+        let mut diagnostics = Diagnostics::new();
+
+        let mut ast = Repl::parse(source_file, &mut diagnostics);
+        let typ = self.checker.check(&mut ast, &mut diagnostics);
+
+        if !diagnostics.has_errors() {
+            match self.interpreter.evaluate(&ast) {
+                Ok(value) => return Ok((value, typ)),
+                Err(err) => diagnostics.push(err),
+            }
+        }
+
+        self.checker = checker;
+        self.interpreter = interpreter;
+        contents.restore();
+        Err(diagnostics)
+    }
+
+    /// Infer the type of an entry without evaluating it. The entry is checked
+    /// against a copy of the session, so that it has no effect on it.
+    pub fn type_of(&self, source_file: &SourceFile) -> Result<Option<Type>, Diagnostics> {
+        let mut diagnostics = Diagnostics::new();
+        let mut ast = Repl::parse(source_file, &mut diagnostics);
+        let typ = self.checker.clone().check(&mut ast, &mut diagnostics);
+
+        if diagnostics.has_errors() {
+            Err(diagnostics)
+        } else {
+            Ok(typ)
+        }
+    }
+
+    /// Discard all the variables in the session.
+    pub fn reset(&mut self) {
+        self.checker = Checker::new();
+        self.interpreter = Interpreter::new();
+    }
+
+    fn parse(source_file: &SourceFile, diagnostics: &mut Diagnostics) -> Ast {
+        let mut scanner = Scanner::new(source_file.chars.clone());
+        scanner.scan_all(diagnostics);
+
+        let mut parser = Parser::new(scanner.tokens);
+        parser.parse(diagnostics)
+    }
+
+    /// Check if the input is incomplete, i.e., if it has more opening than
+    /// closing parentheses, brackets or braces, in which case more lines
+    /// should be read.
+    pub fn is_incomplete(input: &str) -> bool {
+        let mut scanner = Scanner::new(SourceFile::from_contents("<repl>", input).chars);
+        scanner.scan_all(&mut Diagnostics::new());
+
+        let depth = scanner
+            .tokens
+            .iter()
+            .fold(0isize, |depth, token| match token.kind {
+                TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::LeftBrace => depth + 1,
+                TokenKind::RightParen | TokenKind::RightBracket | TokenKind::RightBrace => {
+                    depth - 1
+                }
+                _ => depth,
+            });
+
+        depth > 0
+    }
+
+    /// Every entry is named differently, so that locations in earlier entries
+    /// can be told apart from the ones in the current entry.
+    fn next_source_file(&mut self, input: &str) -> SourceFile {
+        self.entries += 1;
+        SourceFile::from_contents(format!("<repl:{}>", self.entries), input)
+    }
+
+    fn report(&self, source_file: &SourceFile, diagnostics: &Diagnostics) {
+        let renderer = Renderer::new(source_file, self.colour);
+        for err in diagnostics.sorted() {
+            eprintln!("{}", renderer.render(err));
+        }
+    }
+
+    fn eval_and_print(&mut self, source_file: &SourceFile) {
+        match self.eval(source_file) {
+            Ok((ExprValue::Unit, _)) => {}
+            Ok((value, Some(typ))) => println!("{} : {}", value, typ),
+            Ok((value, None)) => println!("{}", value),
+            Err(diagnostics) => self.report(source_file, &diagnostics),
+        }
+    }
+
+    fn show_type(&mut self, input: &str) {
+        let source_file = self.next_source_file(input);
+        match self.type_of(&source_file) {
+            Ok(Some(typ)) => println!("{}", typ),
+            Ok(None) => println!("<unknown>"),
+            Err(diagnostics) => self.report(&source_file, &diagnostics),
+        }
+    }
+
+    /// :ast <expr> - the ast is type-checked against a copy of the session, so
+    /// that the types are shown wherever they can be inferred.
+    fn show_ast(&mut self, input: &str) {
+        let source_file = self.next_source_file(input);
+        let mut diagnostics = Diagnostics::new();
+        let mut ast = Repl::parse(&source_file, &mut diagnostics);
+
+        if !diagnostics.has_errors() {
+            self.checker.clone().check(&mut ast, &mut diagnostics);
+            print!("{}", AstPrinter::print(&mut ast));
+        }
+        self.report(&source_file, &diagnostics);
+    }
+
+    fn show_tokens(&mut self, input: &str) {
+        let source_file = self.next_source_file(input);
+        let mut diagnostics = Diagnostics::new();
+        let mut scanner = Scanner::new(source_file.chars.clone());
+        scanner.scan_all(&mut diagnostics);
+
+        for token in &scanner.tokens {
+            println!("{}", token);
+        }
+        self.report(&source_file, &diagnostics);
+    }
+
+    fn show_env(&self) {
+        for line in self.env() {
+            println!("{}", line);
+        }
+    }
+
+    /// The variables and the functions of the session, sorted by name, one
+    /// line each (see `:env`).
+    pub fn env(&self) -> Vec<String> {
+        let bindings = self.interpreter.runtime().bindings();
+        let mut lines: Vec<(&str, String)> = Vec::new();
+
+        for id in self.checker.id_table().identifiers() {
+            // a name that has no value yet is shown without one.
+            let value = bindings
+                .iter()
+                .find(|(name, _)| *name == id.spelling)
+                .map_or(String::new(), |(_, value)| format!(" = {}", value));
+
+            let line = match id.typ {
+                Some(ref typ) => format!("{} : {}{}", id.spelling, typ, value),
+                None => format!("{}{}", id.spelling, value),
+            };
+            lines.push((&id.spelling, line));
+        }
+
+        // functions are declarations rather than variables in the id table,
+        // so they are found through their values instead.
+        for (name, value) in bindings {
+            if !matches!(*value, ExprValue::Function(_))
+                || lines.iter().any(|(seen, _)| *seen == name)
+            {
+                continue;
+            }
+
+            let line = match self.checker.id_table().get_attr(name) {
+                Some(DeclOrId::Decl(Decl::FunctionDecl(ref func_decl))) => format!(
+                    "{} : {}",
+                    name,
+                    Type::FnType(
+                        func_decl.param_typs.clone(),
+                        Box::new(func_decl.ret_typ.clone())
+                    )
+                ),
+                _ => name.to_owned(),
+            };
+            lines.push((name, line));
+        }
+
+        lines.sort_by(|lhs, rhs| lhs.0.cmp(rhs.0));
+        lines.into_iter().map(|(_, line)| line).collect()
+    }
+
+    fn load(&mut self, path: &str) {
+        match SourceFile::new(path) {
+            Ok(source_file) => self.eval_and_print(&source_file),
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+
+    /// Run a meta-command, returning false if the session should end.
+    fn run_command(&mut self, line: &str) -> bool {
+        let (command, arg) = match line.trim().split_once(char::is_whitespace) {
+            Some((command, arg)) => (command, arg.trim()),
+            None => (line.trim(), ""),
+        };
+
+        match command {
+            ":type" | ":t" | ":ast" | ":tokens" | ":load" | ":l" if arg.is_empty() => {
+                let usage = match command {
+                    ":load" | ":l" => "<file>",
+                    _ => "<expr>",
+                };
+                eprintln!("usage: {} {}", command, usage)
+            }
+            ":type" | ":t" => self.show_type(arg),
+            ":ast" => self.show_ast(arg),
+            ":tokens" => self.show_tokens(arg),
+            ":env" => self.show_env(),
+            ":load" | ":l" => self.load(arg),
+            ":reset" => self.reset(),
+            ":help" | ":h" => println!("{}", Repl::HELP),
+            ":quit" | ":q" => return false,
+            _ => eprintln!("unknown command {}, see :help", command),
+        }
+
+        true
+    }
+
+    fn history_path() -> Option<PathBuf> {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join(Repl::HISTORY_FILE))
+    }
+
+    /// Run the read-eval-print loop until end of input (Ctrl-D). Ctrl-C
+    /// discards the entry being typed.
+    pub fn run(&mut self) -> rustyline::Result<()> {
+        let mut editor = DefaultEditor::new()?;
+        let history_path = Repl::history_path();
+        if let Some(ref history_path) = history_path {
+            let _ = editor.load_history(history_path);
+        }
+
+        let mut input = String::new();
+
+        loop {
+            let prompt = if input.is_empty() {
+                Repl::PROMPT
+            } else {
+                Repl::CONTINUATION_PROMPT
+            };
+
+            match editor.readline(prompt) {
+                Ok(line) => {
+                    if input.is_empty() && line.trim_start().starts_with(':') {
+                        let _ = editor.add_history_entry(line.trim());
+                        if self.run_command(&line) {
+                            continue;
+                        }
+                        break;
+                    }
+
+                    input.push_str(&line);
+                    input.push('\n');
+
+                    if Repl::is_incomplete(&input) {
+                        continue;
+                    }
+
+                    let entry = mem::take(&mut input);
+                    if entry.trim().is_empty() {
+                        continue;
+                    }
+
+                    let _ = editor.add_history_entry(entry.trim_end());
+                    let source_file = self.next_source_file(&entry);
+                    self.eval_and_print(&source_file);
+                }
+
+                Err(ReadlineError::Interrupted) => input.clear(),
+                Err(ReadlineError::Eof) => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        if let Some(ref history_path) = history_path {
+            let _ = editor.save_history(history_path);
+        }
+
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ mod common;
 use std::io;
 use std::path::PathBuf;
 
+use expr_lang::diagnostics::Diagnostics;
 use expr_lang::front::{parser::Parser, scanner::Scanner, source_file::SourceFile};
 use expr_lang::middle::checker::Checker;
 
@@ -19,11 +20,13 @@ fn all_checker_tests() -> io::Result<()> {
 }
 
 fn run_test(test_file: PathBuf) {
-    let mut scanner = Scanner::new(SourceFile::new(test_file).chars);
-    scanner.scan_all();
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::new(test_file).unwrap().chars);
+    scanner.scan_all(&mut diagnostics);
     let mut parser = Parser::new(scanner.tokens);
-    let mut ast = parser.parse();
+    let mut ast = parser.parse(&mut diagnostics);
     let mut checker = Checker::new();
-    checker.check(&mut ast);
+    checker.check(&mut ast, &mut diagnostics);
     println!("{:#?}", ast);
+    assert!(!diagnostics.has_errors());
 }
@@ -0,0 +1,147 @@
+use std::process::{Command, Output};
+
+fn expr_lang(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_expr-lang"))
+        .args(args)
+        .output()
+        .expect("failed to run expr-lang")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn run_prints_only_the_program_output() {
+    for args in [
+        &["run", "examples/single_add.expr"][..],
+        &["examples/single_add.expr"][..],
+    ] {
+        let output = expr_lang(args);
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(stdout(&output), "3\n");
+    }
+}
+
+#[test]
+fn check_does_not_run() {
+    let output = expr_lang(&["check", "examples/single_add.expr"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn eval_with_emitted_stages() {
+    let output = expr_lang(&["eval", "-e", "1 + 2", "--emit=tokens,typed-ast"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "1:1-1:1 Integer \"1\"\n\
+         1:3-1:3 Plus \"+\"\n\
+         1:5-1:5 Integer \"2\"\n\
+         1:6-1:6 Eof \"\"\n\
+         BinaryExpr + : int @ 1:1-1:5\n\
+         \x20 IntegerExpr 1 @ 1:1-1:1\n\
+         \x20 IntegerExpr 2 @ 1:5-1:5\n\
+         3\n"
+    );
+}
+
+#[test]
+fn options_may_precede_the_command() {
+    let output = expr_lang(&["--max-call-depth", "5", "eval", "-e", "1 + 2"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "3\n");
+    let output = expr_lang(&["-A", "warnings", "check", "examples/single_add.expr"]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn ast_is_untyped() {
+    let output = expr_lang(&["ast", "examples/single_add.expr"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("LetExpr @ 1:1-1:13\n"));
+}
+
+#[test]
+fn exit_codes_depend_on_the_error_kind() {
+    let cases = [("@", 3), ("1 +", 4), ("1 + true", 5), ("1 / 0", 6)];
+    for (expr, code) in cases {
+        let output = expr_lang(&["eval", "-e", expr]);
+        assert_eq!(output.status.code(), Some(code), "{}", expr);
+    }
+    assert_eq!(expr_lang(&["run", "missing.expr"]).status.code(), Some(3));
+}
+
+#[test]
+fn invalid_arguments() {
+    for args in [
+        &["run"][..],
+        &["eval"][..],
+        &["eval", "examples/single_add.expr"][..],
+        &["run", "examples/single_add.expr", "--emit=bytecode"][..],
+        &["check", "--frobnicate", "examples/single_add.expr"][..],
+        &["run", "examples/single_add.expr", "--max-call-depth"][..],
+        &["run", "examples/single_add.expr", "--max-call-depth=0"][..],
+        &["eval", "--max-call-depth", "100000000000", "-e", "1"][..],
+        &["-e", "1", "run", "examples/single_add.expr"][..],
+        &["examples/single_add.expr", "check"][..],
+    ] {
+        assert_eq!(expr_lang(args).status.code(), Some(2), "{:?}", args);
+    }
+}
+
+#[test]
+fn json_message_format() {
+    let output = expr_lang(&["eval", "-e", "1 +\n2 @", "--message-format=json"]);
+    assert_eq!(output.status.code(), Some(3));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines = stderr.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0]
+        .starts_with("{\"code\":\"E0002\",\"kind\":\"ScannerError\",\"severity\":\"error\","));
+    assert!(lines[0].ends_with('}'));
+}
+
+#[test]
+fn explain_error_codes() {
+    let output = expr_lang(&["explain", "E0007"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("E0007: unexpected end of input\n"));
+    assert!(stdout(&output).contains("\n    let a = 1 +\n"));
+
+    assert_eq!(expr_lang(&["explain", "E9999"]).status.code(), Some(2));
+}
+
+#[test]
+fn warnings_do_not_fail_unless_denied() {
+    let output = expr_lang(&["eval", "-e", "let a = +1"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "1\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("warning[W0005]"));
+
+    let output = expr_lang(&["eval", "-e", "let a = +1", "-A", "warnings"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stderr.is_empty());
+
+    let output = expr_lang(&["eval", "-e", "let a = +1", "-D", "unary_plus"]);
+    assert_eq!(output.status.code(), Some(5));
+    assert_eq!(stdout(&output), "");
+
+    assert_eq!(
+        expr_lang(&["eval", "-e", "1", "-D", "nope"]).status.code(),
+        Some(2)
+    );
+}
+
+#[test]
+fn max_call_depth() {
+    let program =
+        "fn down(n: int) -> int { if n == 0 { 0 } else { down(n - 1) } }\nprint(down(50))";
+    let output = expr_lang(&["eval", "-e", program]);
+    assert_eq!(stdout(&output), "0\n");
+
+    let output = expr_lang(&["eval", "-e", program, "--max-call-depth=10"]);
+    assert_eq!(output.status.code(), Some(6));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("E0039"));
+}
@@ -0,0 +1,658 @@
+use expr_lang::backend::interpreter::Interpreter;
+use expr_lang::diagnostics::{codes::ErrorCode, Diagnostics};
+use expr_lang::error::{ExprError, ExprErrorKind};
+use expr_lang::front::{parser::Parser, scanner::Scanner, source_file::SourceFile};
+use expr_lang::middle::checker::Checker;
+use expr_lang::middle::lints::{LintLevels, Linter};
+
+fn run_source(contents: &str) -> Vec<ExprError> {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::from_contents("test.expr", contents).chars);
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let mut ast = parser.parse(&mut diagnostics);
+    let mut checker = Checker::new();
+    checker.check(&mut ast, &mut diagnostics);
+
+    if !diagnostics.has_errors() {
+        Linter::new(&LintLevels::new()).lint(&mut ast, &mut diagnostics);
+    }
+
+    if !diagnostics.has_errors() {
+        let mut interpreter = Interpreter::new();
+        if let Err(err) = interpreter.interpret(&ast) {
+            diagnostics.push(err);
+        }
+    }
+
+    diagnostics.sorted().into_iter().cloned().collect()
+}
+
+fn kinds(errors: &[ExprError]) -> Vec<ExprErrorKind> {
+    errors.iter().map(|err| err.kind()).collect()
+}
+
+#[test]
+fn missing_source_file() {
+    let err = SourceFile::new("does/not/exist.expr").unwrap_err();
+    assert_eq!(err.kind(), ExprErrorKind::ScannerError);
+}
+
+#[test]
+fn scanner_error() {
+    let errors = run_source("let a = 1 $ 2");
+    assert_eq!(errors[0].kind(), ExprErrorKind::ScannerError);
+    let loc = errors[0].location().unwrap();
+    assert_eq!((loc.line, loc.col), (1, 11));
+}
+
+#[test]
+fn parser_error() {
+    let errors = run_source("print((1 + 2)");
+    assert_eq!(kinds(&errors), vec![ExprErrorKind::ParserError]);
+
+    let errors = run_source("1 +");
+    assert_eq!(kinds(&errors), vec![ExprErrorKind::ParserError]);
+}
+
+#[test]
+fn nesting_errors() {
+    let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+
+    assert!(run_source(&nested(Parser::MAX_NESTING_DEPTH)).is_empty());
+
+    let errors = run_source(&nested(Parser::MAX_NESTING_DEPTH + 1));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code(), ErrorCode::NestingTooDeep);
+    assert_eq!(
+        errors[0].location().unwrap().col,
+        Parser::MAX_NESTING_DEPTH + 2
+    );
+
+    let depth = Parser::MAX_NESTING_DEPTH + 1;
+    let errors = run_source(&format!(
+        "let xs: {}int{} = []",
+        "List<".repeat(depth),
+        ">".repeat(depth)
+    ));
+    assert_eq!(errors[0].code(), ErrorCode::NestingTooDeep);
+
+    let chain = |terms: usize| vec!["1"; terms].join(" + ");
+    assert!(run_source(&chain(Parser::MAX_NESTING_DEPTH)).is_empty());
+    let errors = run_source(&chain(2000));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code(), ErrorCode::NestingTooDeep);
+}
+
+#[test]
+fn checker_error() {
+    let errors = run_source("let a = 1\na = true");
+    assert_eq!(kinds(&errors), vec![ExprErrorKind::CheckerError]);
+
+    let errors = run_source("print(1 + true)");
+    assert_eq!(kinds(&errors), vec![ExprErrorKind::CheckerError]);
+}
+
+#[test]
+fn interpreter_error() {
+    let errors = run_source("let a = 0\nprint(10 / a)");
+    assert_eq!(kinds(&errors), vec![ExprErrorKind::InterpreterError]);
+    assert_eq!(errors[0].message(), "division by zero");
+
+    let errors = run_source("print(2147483647 + 1)");
+    assert_eq!(kinds(&errors), vec![ExprErrorKind::InterpreterError]);
+}
+
+#[test]
+fn float_errors() {
+    let errors = run_source("print(1 + 1.5)");
+    assert_eq!(errors[0].code(), ErrorCode::OperandTypeMismatch);
+
+    let errors = run_source("let a = 1\na += 1.5");
+    assert_eq!(kinds(&errors), vec![ExprErrorKind::CheckerError]);
+
+    let errors = run_source("print(true as float)");
+    assert_eq!(errors[0].code(), ErrorCode::InvalidCast);
+
+    let errors = run_source("print(-1e10 as int)");
+    assert_eq!(errors[0].code(), ErrorCode::FloatToIntOverflow);
+
+    let errors = run_source("print(1.0 / 0.0)");
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn call_errors() {
+    let errors = run_source("print(len(1))");
+    assert_eq!(errors[0].code(), ErrorCode::ArgumentTypeMismatch);
+
+    let errors = run_source("print(len())");
+    assert_eq!(errors[0].code(), ErrorCode::ArgumentCountMismatch);
+
+    let errors = run_source("let a = \"a\"\nprint(a(1))");
+    assert_eq!(errors[0].code(), ErrorCode::NotCallable);
+
+    let errors = run_source("print(foo(1))");
+    assert_eq!(errors[0].code(), ErrorCode::UndeclaredVariable);
+
+    // functions are not hoisted, so they cannot call the ones declared later.
+    let errors = run_source("fn a() -> int { b() }\nfn b() -> int { 1 }");
+    assert_eq!(errors[0].code(), ErrorCode::UndeclaredVariable);
+    assert_eq!(errors[0].location().unwrap().col, 17);
+
+    let errors = run_source("fn f(x: int, x: int) -> int { x }");
+    assert_eq!(errors[0].code(), ErrorCode::DuplicateParameter);
+    assert_eq!(errors[0].location().unwrap().col, 14);
+
+    let errors = run_source("let g = |x: int, x: int| x\nprint(g(1, 2))");
+    assert_eq!(errors[0].code(), ErrorCode::DuplicateParameter);
+
+    let errors = run_source("print(\"a\" - \"b\")");
+    assert_eq!(errors[0].code(), ErrorCode::OperandTypeMismatch);
+}
+
+#[test]
+fn let_errors() {
+    let errors = run_source("a = 1");
+    assert_eq!(errors[0].code(), ErrorCode::UndeclaredVariable);
+
+    let errors = run_source("let a = 1\nprint(a + b)");
+    assert_eq!(errors[0].code(), ErrorCode::UndeclaredVariable);
+    assert_eq!(errors[0].location().unwrap().col, 11);
+
+    let errors = run_source("let a: float = 1\nprint(a)");
+    assert_eq!(errors[0].code(), ErrorCode::AnnotationMismatch);
+    assert_eq!(errors[0].labels()[0].span.start.col, 8);
+
+    let errors = run_source("let a: int 1");
+    assert_eq!(errors[0].code(), ErrorCode::ExpectedToken);
+
+    let errors = run_source("let a: int = 1\nlet b: string = \"b\"\nprint(b + \"\" + b)\nprint(a)");
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn block_scopes() {
+    let errors = run_source("{\nlet a = 1\nprint(a)\n}\nprint(a)");
+    assert_eq!(errors[0].code(), ErrorCode::UndeclaredVariable);
+    assert_eq!(errors[0].location().unwrap().line, 5);
+
+    let errors = run_source("let a = 1\n{\nlet a = true\nprint(a)\n}\na += 2\nprint(a)");
+    assert!(errors.is_empty());
+
+    let errors = run_source("{\nlet a = 1\n");
+    assert_eq!(errors[0].code(), ErrorCode::ExpectedToken);
+}
+
+#[test]
+fn if_errors() {
+    let errors = run_source("let a = 1\nif a { print(a) }");
+    assert_eq!(errors[0].code(), ErrorCode::NonBoolCondition);
+
+    let errors = run_source("let a = if true { 1 } else { 1.0 }");
+    assert_eq!(errors[0].code(), ErrorCode::BranchTypeMismatch);
+    assert_eq!(errors[0].labels()[0].span.start.col, 17);
+
+    let errors = run_source("let a = if true { 1 } else if false { 2 } else { \"3\" }");
+    assert_eq!(errors[0].code(), ErrorCode::BranchTypeMismatch);
+
+    let errors = run_source("if true { 1 } else 2");
+    assert_eq!(errors[0].code(), ErrorCode::ExpectedToken);
+
+    let errors = run_source("let a = 0\nprint(if a != 0 { 1 / a } else { 0 })");
+    assert!(errors.is_empty());
+
+    let errors = run_source("let a = 0\nif a == 0 { print(1) } else { print(1 / a) }");
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn loop_errors() {
+    let errors = run_source("let a = 1\nif a > 0 { break }");
+    assert_eq!(errors[0].code(), ErrorCode::BreakOutsideLoop);
+
+    let errors = run_source("continue");
+    assert_eq!(errors[0].code(), ErrorCode::BreakOutsideLoop);
+
+    let errors = run_source("let a = 1\nwhile a { a -= 1 }");
+    assert_eq!(errors[0].code(), ErrorCode::NonBoolCondition);
+
+    let errors = run_source("for i in 0..\"3\" { print(i) }");
+    assert_eq!(errors[0].code(), ErrorCode::NonIntRangeBound);
+    assert_eq!(errors[0].location().unwrap().col, 13);
+
+    let errors = run_source("for i in 0..3 {\nprint(i)\n}\nprint(i)");
+    assert_eq!(errors[0].code(), ErrorCode::UndeclaredVariable);
+    assert_eq!(errors[0].location().unwrap().line, 4);
+
+    let errors = run_source("for i in 0..3 { print(i / (i - 2)) }");
+    assert_eq!(errors[0].code(), ErrorCode::DivisionByZero);
+
+    let errors = run_source("for i in 0..3 {\nwhile true { break }\nprint(i)\n}\nprint(1)");
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn function_errors() {
+    let errors = run_source("fn half(x: int) -> float {\nx / 2\n}");
+    assert_eq!(errors[0].code(), ErrorCode::ReturnTypeMismatch);
+    assert_eq!(errors[0].location().unwrap().line, 2);
+
+    let errors = run_source("fn f(x: int) { x }");
+    assert_eq!(errors[0].code(), ErrorCode::ReturnTypeMismatch);
+
+    let errors = run_source("let k = 2\nfn scale(x: int) -> int { x * k }");
+    assert_eq!(errors[0].code(), ErrorCode::CannotCaptureVariable);
+
+    let errors = run_source("fn f(x: int) {}\nf(true)");
+    assert_eq!(errors[0].code(), ErrorCode::ArgumentTypeMismatch);
+
+    let errors = run_source("while true { fn f() { break } }");
+    assert_eq!(errors[0].code(), ErrorCode::BreakOutsideLoop);
+
+    let errors = run_source("fn down(n: int) -> int { down(n - 1) }\nprint(down(3))");
+    assert_eq!(errors[0].code(), ErrorCode::CallDepthExceeded);
+
+    let errors = run_source(
+        "fn fact(n: int) -> int { if n < 2 { 1 } else { n * fact(n - 1) } }\nprint(fact(12))",
+    );
+    assert!(errors.is_empty());
+
+    let errors = run_source("fn f() -> int { 1 }\nfn f() -> int { 2 }\nprint(f())");
+    assert_eq!(errors[0].code(), ErrorCode::DuplicateFunction);
+    assert_eq!(errors[0].location().unwrap().line, 2);
+
+    // a function in a block shadows the outer one, and a function of the
+    // program shadows a builtin one.
+    let errors = run_source(
+        "fn f() -> int { 1 }\n{\n  fn f() -> int { 2 }\n  print(f())\n}\nprint(f())\n\
+         fn len(s: string) -> int { 0 }\nprint(len(\"ab\"))",
+    );
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn closure_errors() {
+    let errors = run_source("let inc = |x| x + 1");
+    assert_eq!(errors[0].code(), ErrorCode::CannotInferParamType);
+    assert_eq!(errors[0].location().unwrap().col, 12);
+
+    let errors = run_source("let total = 0\nlet add = |x: int| total += x");
+    assert_eq!(errors[0].code(), ErrorCode::AssignToCapturedVariable);
+
+    let errors = run_source("fn square(x: int) -> int { x * x }\nlet f = square");
+    assert_eq!(errors[0].code(), ErrorCode::FunctionUsedAsValue);
+
+    let errors = run_source("let a = 1\na(2)");
+    assert_eq!(errors[0].code(), ErrorCode::NotCallable);
+
+    let errors = run_source("let f: fn(int) -> string = |x| x + 1");
+    assert_eq!(errors[0].code(), ErrorCode::AnnotationMismatch);
+
+    let errors =
+        run_source("fn apply(f: fn(int) -> int) -> int { f(1) }\napply(|s: string| len(s))");
+    assert_eq!(errors[0].code(), ErrorCode::ArgumentTypeMismatch);
+
+    let errors = run_source(
+        "let k = 2\nfn adder(n: int) -> fn(int) -> int { |x| x + n + k }\nprint(adder(1)(2))",
+    );
+    assert_eq!(errors[0].code(), ErrorCode::CannotCaptureVariable);
+
+    let errors = run_source(
+        "let k = 2\nlet f = |x: int| x * k\nk = 3\nfn apply(g: fn(int) -> int) -> int { g(5) }\nprint(apply(f) + apply(|x| x + k))",
+    );
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn increment_errors() {
+    let errors = run_source("let f = 1.5\nf++");
+    assert_eq!(errors[0].code(), ErrorCode::UnaryOperandMismatch);
+
+    let errors = run_source("let s = \"a\"\n--s");
+    assert_eq!(errors[0].code(), ErrorCode::UnaryOperandMismatch);
+
+    let errors = run_source("let a = 1\n(a + 1)++");
+    assert_eq!(errors[0].code(), ErrorCode::InvalidAssignmentTarget);
+
+    let errors = run_source("let a = 2147483647\na++");
+    assert_eq!(errors[0].code(), ErrorCode::ArithmeticOverflow);
+
+    let errors = run_source("let a = 1\nlet b = a++ + ++a\nprint(a * b)");
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn ternary_errors() {
+    let errors = run_source("let a = 1\nprint(a ? 1 : 2)");
+    assert_eq!(errors[0].code(), ErrorCode::NonBoolCondition);
+
+    let errors = run_source("let a = 1\nprint(a > 0 ? 1 : \"negative\")");
+    assert_eq!(errors[0].code(), ErrorCode::BranchTypeMismatch);
+    assert_eq!(errors[0].location().unwrap().col, 19);
+
+    let errors = run_source("let a = 1\nprint(a > 0 ? 1 a)");
+    assert_eq!(errors[0].code(), ErrorCode::ExpectedToken);
+
+    // only the selected arm is evaluated.
+    let errors = run_source("let a = 0\nprint(a == 0 ? 0 : 1 / a)");
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn list_errors() {
+    let errors = run_source("let xs = []");
+    assert_eq!(errors[0].code(), ErrorCode::CannotInferListType);
+
+    let errors = run_source("let xs = [1, \"two\"]");
+    assert_eq!(errors[0].code(), ErrorCode::ListElementTypeMismatch);
+    assert_eq!(errors[0].location().unwrap().col, 14);
+
+    let errors = run_source("let xs = [1]\nxs[0] = 1.5");
+    assert_eq!(errors[0].code(), ErrorCode::ListElementTypeMismatch);
+
+    let errors = run_source("let s = \"abc\"\nprint(s[0])");
+    assert_eq!(errors[0].code(), ErrorCode::NotIndexable);
+
+    let errors = run_source("let xs = [1]\nprint(xs[\"0\"])");
+    assert_eq!(errors[0].code(), ErrorCode::NonIntIndex);
+
+    let errors = run_source("let xs = [1]\npush(xs, true)");
+    assert_eq!(errors[0].code(), ErrorCode::ArgumentTypeMismatch);
+
+    let errors = run_source("let xs = [1, 2]\nxs[-1] += 1");
+    assert_eq!(errors[0].code(), ErrorCode::IndexOutOfBounds);
+    assert_eq!(errors[0].location().unwrap().line, 2);
+
+    let errors = run_source("let xs: List<int> = []\npop(xs)");
+    assert_eq!(errors[0].code(), ErrorCode::IndexOutOfBounds);
+
+    let errors = run_source(
+        "let xs: List<List<int>> = [[]]\npush(xs[0], 1)\nxs[0][0] *= 2\nprint(len(xs[0]) + pop(xs[0]))",
+    );
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn tuple_errors() {
+    let errors = run_source("let p = (1, 2)\nprint(p.2)");
+    assert_eq!(errors[0].code(), ErrorCode::UnknownField);
+    assert_eq!(errors[0].location().unwrap().col, 9);
+
+    let errors = run_source("let n = 1\nprint(n.0)");
+    assert_eq!(errors[0].code(), ErrorCode::UnknownField);
+
+    let errors = run_source("let a = 1\nlet b = 2\n(a, b) = (1, 2, 3)");
+    assert_eq!(errors[0].code(), ErrorCode::DestructuringMismatch);
+
+    let errors = run_source("let a = 1\nlet b = 2\n(a, b) = (1, 2.0)");
+    assert_eq!(errors[0].code(), ErrorCode::DestructuringMismatch);
+    assert_eq!(errors[0].location().unwrap().col, 5);
+
+    let errors = run_source("let a = 1\n(a, 2) = (1, 2)");
+    assert_eq!(errors[0].code(), ErrorCode::InvalidAssignmentTarget);
+
+    let errors = run_source("let a = 1\nlet b = 2\n(a, b) += (1, 2)");
+    assert_eq!(errors[0].code(), ErrorCode::InvalidAssignmentTarget);
+
+    let errors = run_source("let a = 1\nlet f = |b: int| (a, b) = (b, 0)");
+    assert_eq!(errors[0].code(), ErrorCode::AssignToCapturedVariable);
+
+    let errors = run_source(
+        "let a = 1\nlet b = 2\nlet xs = [0]\n(a, (b, xs[0])) = (b, (a, 5))\nprint((a, b, xs).2[0])",
+    );
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn struct_errors() {
+    let decl = "struct Order { amount: int, urgent: bool }\n";
+    let run = |src: &str| run_source(&format!("{}{}", decl, src));
+
+    let errors = run("let o = Item { amount: 1 }");
+    assert_eq!(errors[0].code(), ErrorCode::UnknownType);
+    assert_eq!(errors[0].location().unwrap().col, 9);
+
+    let errors = run("let o: Item = Order { amount: 1, urgent: true }");
+    assert_eq!(errors[0].code(), ErrorCode::UnknownType);
+
+    let errors = run("let o = Order { amount: 1, urgent: true, late: false }");
+    assert_eq!(errors[0].code(), ErrorCode::UnknownField);
+    assert_eq!(errors[0].location().unwrap().col, 42);
+
+    let errors = run("let o = Order { amount: true, urgent: true }");
+    assert_eq!(errors[0].code(), ErrorCode::FieldTypeMismatch);
+
+    let errors = run("let o = Order { amount: 1 }");
+    assert_eq!(errors[0].code(), ErrorCode::MissingField);
+
+    let errors = run("let o = Order { amount: 1, amount: 2, urgent: true }");
+    assert_eq!(errors[0].code(), ErrorCode::DuplicateField);
+
+    let errors = run_source("struct Pair { a: int, a: int }");
+    assert_eq!(errors[0].code(), ErrorCode::DuplicateField);
+
+    let errors = run("let o = Order { amount: 1, urgent: true }\nprint(o.late)");
+    assert_eq!(errors[0].code(), ErrorCode::UnknownField);
+
+    let errors = run("let o = Order { amount: 1, urgent: true }\no.amount += 1.5");
+    assert_eq!(errors[0].code(), ErrorCode::FieldTypeMismatch);
+
+    let errors = run("let p = (1, 2)\np.0 = 3");
+    assert_eq!(errors[0].code(), ErrorCode::InvalidAssignmentTarget);
+
+    let errors = run(
+        "let o = Order { amount: 1, urgent: true }\nstruct Order { late: bool }\nprint(o.late)",
+    );
+    assert_eq!(errors[0].code(), ErrorCode::DuplicateType);
+    assert_eq!(errors[0].location().unwrap().col, 8);
+
+    // a struct declared in a block is another type than the one it shadows,
+    // even once its records have left the block.
+    let errors =
+        run("let o = { struct Order { late: bool }\nOrder { late: true } }\nlet p: Order = o");
+    assert_eq!(errors[0].code(), ErrorCode::AnnotationMismatch);
+    // both are named Order, so the declarations of the two are pointed at.
+    let decls: Vec<_> = errors[0].labels()[1..]
+        .iter()
+        .map(|label| (label.span.start.line, label.span.start.col))
+        .collect();
+    assert_eq!(decls, vec![(1, 8), (2, 18)]);
+
+    let errors = run(
+        "let o = { struct Order { late: bool }\nOrder { late: true } }\nfn f(p: Order) {}\nf(o)",
+    );
+    assert_eq!(errors[0].code(), ErrorCode::ArgumentTypeMismatch);
+    assert_eq!(errors[0].labels().len(), 2);
+
+    let errors =
+        run("let o = { struct Order { late: bool }\nOrder { late: true } }\nprint(o.amount)");
+    assert_eq!(errors[0].code(), ErrorCode::UnknownField);
+
+    let errors = run(
+        "let o = Order { urgent: false, amount: 1 }\nlet p = o\np.amount *= 10\nprint(o.amount)",
+    );
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn multiple_errors_are_reported_in_order() {
+    let errors =
+        run_source("let a = (1 + 2\nlet b = 1 $ 2\nlet c = 3 * * 2\nlet d = ) 4\nprint(4)");
+    assert_eq!(
+        kinds(&errors),
+        vec![
+            ExprErrorKind::ParserError,
+            ExprErrorKind::ScannerError,
+            ExprErrorKind::ParserError,
+            ExprErrorKind::ParserError,
+        ]
+    );
+
+    let lines = errors
+        .iter()
+        .map(|err| err.location().unwrap().line)
+        .collect::<Vec<usize>>();
+    assert_eq!(lines, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn checker_errors_are_located() {
+    let errors = run_source("let a = 1\nlet b = a + true");
+    let span = errors[0].span().unwrap();
+    assert_eq!((span.start.line, span.start.col), (2, 13));
+    assert_eq!((span.end.line, span.end.col), (2, 16));
+
+    let errors = run_source("let a = 1\na = false");
+    let span = errors[0].span().unwrap();
+    assert_eq!((span.start.line, span.start.col), (2, 5));
+    assert_eq!((span.end.line, span.end.col), (2, 9));
+}
+
+#[test]
+fn error_codes_are_unique() {
+    let mut codes = ErrorCode::ALL
+        .iter()
+        .map(|code| code.as_str())
+        .collect::<Vec<_>>();
+    codes.sort_unstable();
+    codes.dedup();
+    assert_eq!(codes.len(), ErrorCode::ALL.len());
+
+    for code in ErrorCode::ALL {
+        assert_eq!(ErrorCode::from_code(code.as_str()), Some(code));
+    }
+}
+
+#[test]
+fn explanation_examples() {
+    for code in ErrorCode::ALL {
+        let explanation = code.explanation();
+        if let Some(failing) = explanation.failing {
+            let errors = run_source(failing);
+            assert!(errors.iter().any(|err| err.code() == code), "{}", failing);
+        }
+        if let Some(fixed) = explanation.fixed {
+            let errors = run_source(fixed);
+            assert!(
+                errors
+                    .iter()
+                    .all(|err| !err.is_error() && err.code() != code),
+                "{}",
+                fixed
+            );
+        }
+    }
+}
+
+#[test]
+fn enum_errors() {
+    let decl = "enum Status { Ok, Err(int) }\n";
+    let run = |src: &str| run_source(&format!("{}{}", decl, src));
+
+    let errors = run("print(match Ok { Ok => 1 })");
+    assert_eq!(errors[0].code(), ErrorCode::NonExhaustiveMatch);
+    assert_eq!(errors[0].location().unwrap().col, 13);
+
+    let errors = run("print(match Err(1) { Ok => 1, Err(0) => 2 })");
+    assert_eq!(errors[0].code(), ErrorCode::NonExhaustiveMatch);
+
+    let errors = run("print(match 3 { 1 => 1, -1 => 2 })");
+    assert_eq!(errors[0].code(), ErrorCode::NonExhaustiveMatch);
+
+    let errors = run("print(match Ok { _ => 1, Ok => 2 })");
+    assert_eq!(errors[0].code(), ErrorCode::UnreachablePattern);
+    assert_eq!(errors[0].location().unwrap().col, 26);
+
+    let errors = run("print(match true { true => 1, false => 2, b => 3 })");
+    assert_eq!(errors[0].code(), ErrorCode::UnreachablePattern);
+
+    let errors = run("print(match Ok { Ok => 1, Err(\"no\") => 2 })");
+    assert_eq!(errors[0].code(), ErrorCode::PatternTypeMismatch);
+
+    let errors = run("enum Other { Gone }\nprint(match Ok { Gone => 1, _ => 2 })");
+    assert_eq!(errors[0].code(), ErrorCode::PatternTypeMismatch);
+
+    let errors = run("print(match Ok { Ok => 1, Err(code) => \"no\" })");
+    assert_eq!(errors[0].code(), ErrorCode::BranchTypeMismatch);
+
+    let errors = run_source("enum Light { Red, Green, Red }");
+    assert_eq!(errors[0].code(), ErrorCode::DuplicateVariant);
+
+    let errors = run("let make = Err");
+    assert_eq!(errors[0].code(), ErrorCode::FunctionUsedAsValue);
+
+    let errors = run("Ok = Err(1)");
+    assert_eq!(errors[0].code(), ErrorCode::InvalidAssignmentTarget);
+
+    let errors = run("let s = Ok\nenum Status { Fine }\nprint(match s { Fine => 1 })");
+    assert_eq!(errors[0].code(), ErrorCode::DuplicateType);
+
+    // an enum that shadows another one is another type, so its variants do
+    // not match the values of the outer one.
+    let errors = run("let s = Ok\n{\n  enum Status { Fine }\n  print(match s { Fine => 1 })\n}");
+    assert_eq!(errors[0].code(), ErrorCode::PatternTypeMismatch);
+
+    let errors = run(
+        "let s = { enum Status { Fine }\nFine }\nprint(match s { Ok => 0, Err(code) => code })",
+    );
+    assert_eq!(errors[0].code(), ErrorCode::PatternTypeMismatch);
+
+    let errors = run(
+        "let s: Status = Err(404)\nprint(match s { Ok => 0, Err(200) => 1, Err(code) => code })",
+    );
+    assert!(errors.is_empty());
+
+    let errors = run("enum Pair { P(int, int), Q }\nprint(match P(1, 2) { P(x, x) => x, Q => 2 })");
+    assert_eq!(errors[0].code(), ErrorCode::DuplicateBinding);
+    assert_eq!(errors[0].location().unwrap().col, 28);
+}
+
+#[test]
+fn option_errors() {
+    let decl = "let found = some(3)\nlet missing: Option<int> = none\n";
+    let run = |src: &str| run_source(&format!("{}{}", decl, src));
+
+    let errors = run("let x = none");
+    assert_eq!(errors[0].code(), ErrorCode::CannotInferOptionType);
+    assert_eq!(errors[0].location().unwrap().col, 9);
+
+    let errors = run("print(3 ?? 1)");
+    assert_eq!(errors[0].code(), ErrorCode::OperandTypeMismatch);
+    assert_eq!(errors[0].location().unwrap().col, 7);
+
+    let errors = run("print(found ?? \"none\")");
+    assert_eq!(errors[0].code(), ErrorCode::OperandTypeMismatch);
+    assert_eq!(errors[0].location().unwrap().col, 16);
+
+    let errors = run("print(is_some(3))");
+    assert_eq!(errors[0].code(), ErrorCode::ArgumentTypeMismatch);
+
+    let errors = run("let x: Option<Order> = none");
+    assert_eq!(errors[0].code(), ErrorCode::UnknownType);
+
+    let errors = run("let total: int = found");
+    assert_eq!(errors[0].code(), ErrorCode::AnnotationMismatch);
+
+    let errors = run(
+        "fn half(x: int) -> Option<int> {\n  x % 2 == 0 ? some(x / 2) : none\n}\nlet opts = [found, none, half(3)]\nprint(opts[1] ?? 2)\nprint(missing == none && is_some(found) ? found ?? 0 : missing ?? 1)",
+    );
+    assert!(errors.is_empty());
+
+    // the type of none comes from the type that is expected, or else from the
+    // other branches.
+    for src in [
+        "fn f(x: int) -> Option<int> {\n  if x < 0 { none } else { some(x) }\n}\nprint(f(1))",
+        "print(missing == none ? none : some(1))",
+        "print([none, some(1)])",
+        "let nested: Option<Option<int>> = some(none)\nprint(nested)",
+        "let y: Option<int> = missing == none ? none : none\nprint(y)",
+        "print(match 3 { 1 => none, _ => some(\"x\") })",
+    ] {
+        let src = format!("{}\nprint(found == missing)", src);
+        assert!(run(&src).is_empty(), "{}", src);
+    }
+
+    let errors = run("print(missing == none ? none : none)");
+    assert_eq!(errors[0].code(), ErrorCode::CannotInferOptionType);
+}
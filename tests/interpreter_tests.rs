@@ -0,0 +1,253 @@
+mod common;
+
+use std::io;
+use std::path::PathBuf;
+
+use expr_lang::backend::interpreter::Interpreter;
+use expr_lang::backend::runtime::ExprValue;
+use expr_lang::diagnostics::Diagnostics;
+use expr_lang::front::{parser::Parser, scanner::Scanner, source_file::SourceFile};
+use expr_lang::middle::checker::Checker;
+
+#[test]
+fn all_interpreter_tests() -> io::Result<()> {
+    let mut test_files = Vec::new();
+    common::get_all_test_files(&"examples", &mut test_files)?;
+
+    for file in test_files {
+        run_test(file);
+    }
+
+    Ok(())
+}
+
+fn run_test(test_file: PathBuf) {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::new(test_file).unwrap().chars);
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let mut ast = parser.parse(&mut diagnostics);
+    let mut checker = Checker::new();
+    checker.check(&mut ast, &mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    let mut interpreter = Interpreter::new();
+    interpreter.interpret(&ast).unwrap();
+}
+
+fn evaluate(contents: &str) -> ExprValue {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::from_contents("test.expr", contents).chars);
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let mut ast = parser.parse(&mut diagnostics);
+    let mut checker = Checker::new();
+    checker.check(&mut ast, &mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    Interpreter::new().evaluate(&ast).unwrap()
+}
+
+#[test]
+fn logical_operators_short_circuit() {
+    // xs[i] would be out of bounds.
+    let decl = "let xs = [1]\nlet i = 5\n";
+    assert_eq!(
+        evaluate(&format!("{}i < len(xs) && xs[i] == 1", decl)),
+        ExprValue::Bool(false)
+    );
+    assert_eq!(
+        evaluate(&format!("{}i >= len(xs) || xs[i] == 1", decl)),
+        ExprValue::Bool(true)
+    );
+
+    assert_eq!(
+        evaluate("let x = 0\nlet a = false && (x += 1) == 1\nlet b = true || (x += 1) == 1\nx"),
+        ExprValue::Int(0)
+    );
+    assert_eq!(
+        evaluate("let x = 0\nlet a = true && (x += 1) == 1\nlet b = false || (x += 1) == 2\nx"),
+        ExprValue::Int(2)
+    );
+}
+
+#[test]
+fn values_that_contain_themselves_are_shown() {
+    let decl = "struct Node { kids: List<Node> }\nlet n = Node { kids: [] }\n";
+    assert_eq!(
+        evaluate(&format!("{}push(n.kids, n)\nn", decl)).to_string(),
+        "Node { kids: [...] }"
+    );
+    // a value that is shared, rather than nested in itself, is shown in full.
+    assert_eq!(
+        evaluate(&format!("{}Node {{ kids: [n, n] }}", decl)).to_string(),
+        "Node { kids: [Node { kids: [] }, Node { kids: [] }] }"
+    );
+}
+
+#[test]
+fn values_that_contain_themselves_are_compared() {
+    let decl = "struct Node { kids: List<Node> }\n\
+                let a = Node { kids: [] }\npush(a.kids, a)\n\
+                let b = Node { kids: [] }\npush(b.kids, b)\n";
+    assert_eq!(evaluate(&format!("{}a == b", decl)), ExprValue::Bool(true));
+    assert_eq!(
+        evaluate(&format!("{}push(b.kids, a)\na == b", decl)),
+        ExprValue::Bool(false)
+    );
+    assert_eq!(
+        evaluate(&format!(
+            "{}a == Node {{ kids: [Node {{ kids: [] }}] }}",
+            decl
+        )),
+        ExprValue::Bool(false)
+    );
+}
+
+#[test]
+fn long_chains_of_values() {
+    let decl = "enum L { Nil, Cons(int, L) }\nlet a = Nil\nlet b = Nil\n\
+                for i in 0..20000 { a = Cons(i, a) b = Cons(i, b) }\n";
+    assert_eq!(evaluate(&format!("{}a == b", decl)), ExprValue::Bool(true));
+    assert!(evaluate(&format!("{}a", decl))
+        .to_string()
+        .starts_with("Cons(19999, Cons(19998, "));
+
+    let decl = "struct N { v: int, next: Option<N> }\nlet head: Option<N> = none\n\
+                for i in 0..20000 { head = some(N { v: i, next: head }) }\n";
+    assert_eq!(
+        evaluate(&format!("{}is_some(head)", decl)),
+        ExprValue::Bool(true)
+    );
+}
+
+#[test]
+fn names_of_operators_can_be_declared() {
+    assert_eq!(evaluate("let sub = 5\nsub - 1"), ExprValue::Int(4));
+    assert_eq!(
+        evaluate("fn add(a: int, b: int) -> int { a * b }\nadd(2, 3) + 1"),
+        ExprValue::Int(7)
+    );
+    assert_eq!(
+        evaluate("let less_than_float = 1.5\nless_than_float < 2.0"),
+        ExprValue::Bool(true)
+    );
+}
+
+#[test]
+fn float_arithmetic_and_casts() {
+    assert_eq!(evaluate("1.5 + 2e1"), ExprValue::Float(21.5));
+    assert_eq!(evaluate("-1.5 * 2.0"), ExprValue::Float(-3.0));
+    assert_eq!(evaluate("-1.5 + 2.0"), ExprValue::Float(0.5));
+    assert_eq!(evaluate("-1 + 2"), ExprValue::Int(1));
+    assert_eq!(evaluate("7.5 % 2.0"), ExprValue::Float(1.5));
+    assert_eq!(
+        evaluate("let c = 0.5\nc *= 1.5e-1\nc"),
+        ExprValue::Float(0.075)
+    );
+    assert_eq!(evaluate("1.0 / 4.0 <= 0.25"), ExprValue::Bool(true));
+
+    assert_eq!(evaluate("7 as float / 2.0"), ExprValue::Float(3.5));
+    // casts to int truncate towards zero.
+    assert_eq!(evaluate("3.9 as int"), ExprValue::Int(3));
+    assert_eq!(evaluate("-3.9 as int"), ExprValue::Int(-3));
+}
+
+#[test]
+fn blocks_have_the_value_of_their_last_expr() {
+    assert_eq!(
+        evaluate("let a = 1\nlet b = { let a = 10\na + 1 }\na + b"),
+        ExprValue::Int(12)
+    );
+    assert_eq!(evaluate("{ }"), ExprValue::Unit);
+    // an assignment in a block changes the outer variable.
+    assert_eq!(evaluate("let a = 1\n{ a = 5 }\na"), ExprValue::Int(5));
+}
+
+#[test]
+fn if_takes_the_first_branch_whose_condition_holds() {
+    let chain = |a: i32| {
+        evaluate(&format!(
+            "let a = {}\nif a > 0 {{ \"pos\" }} else if a < 0 {{ \"neg\" }} else {{ \"zero\" }}",
+            a
+        ))
+    };
+    assert_eq!(chain(3), ExprValue::Str("pos".to_owned()));
+    assert_eq!(chain(-3), ExprValue::Str("neg".to_owned()));
+    assert_eq!(chain(0), ExprValue::Str("zero".to_owned()));
+
+    // only the branch taken is evaluated.
+    assert_eq!(
+        evaluate("let x = 0\nif true { x += 1 } else { x += 10 }\nx"),
+        ExprValue::Int(1)
+    );
+}
+
+#[test]
+fn loops_sum_and_stop() {
+    assert_eq!(
+        evaluate("let t = 0\nfor i in 0..10 { t += i }\nt"),
+        ExprValue::Int(45)
+    );
+    assert_eq!(
+        evaluate("let k = 0\nwhile true { k += 1\nif k == 7 { break } }\nk"),
+        ExprValue::Int(7)
+    );
+    // break and continue apply to the innermost loop.
+    assert_eq!(
+        evaluate(
+            "let s = 0\nfor i in 0..5 { for j in 0..5 {\n\
+             if j > i { break }\nif j % 2 == 1 { continue }\ns += j } }\ns"
+        ),
+        ExprValue::Int(10)
+    );
+    // a range whose end is before its start is empty.
+    assert_eq!(
+        evaluate("let n = 0\nfor i in 5..0 { n += i }\nn"),
+        ExprValue::Int(0)
+    );
+}
+
+#[test]
+fn recursive_functions() {
+    assert_eq!(
+        evaluate("fn fact(n: int) -> int { if n < 2 { 1 } else { n * fact(n - 1) } }\nfact(10)"),
+        ExprValue::Int(3628800)
+    );
+    assert_eq!(
+        evaluate(
+            "fn fib(n: int) -> int { if n < 2 { n } else { fib(n - 1) + fib(n - 2) } }\nfib(15)"
+        ),
+        ExprValue::Int(610)
+    );
+}
+
+#[test]
+fn closures_capture_by_value() {
+    // the closure keeps the value k had when it was created.
+    assert_eq!(
+        evaluate("let k = 2\nlet scale = |x: int| x * k\nk = 100\nscale(5)"),
+        ExprValue::Int(10)
+    );
+    assert_eq!(
+        evaluate("fn adder(n: int) -> fn(int) -> int { |x| x + n }\nlet add = adder(3)\nadd(4)"),
+        ExprValue::Int(7)
+    );
+    // a captured list is still shared with the variable it was captured from.
+    assert_eq!(
+        evaluate("let xs = [1]\nlet grow = || push(xs, 2)\ngrow()\nlen(xs)"),
+        ExprValue::Int(2)
+    );
+}
+
+#[test]
+fn ternaries_take_one_arm() {
+    let nested = |a: i32| evaluate(&format!("let a = {}\na > 5 ? 1 : a > 0 ? 2 : 3", a));
+    assert_eq!(nested(9), ExprValue::Int(1));
+    assert_eq!(nested(4), ExprValue::Int(2));
+    assert_eq!(nested(-4), ExprValue::Int(3));
+
+    // only the arm taken is evaluated.
+    assert_eq!(
+        evaluate("let x = 0\nlet y = false ? (x += 1) : (x += 10)\nx"),
+        ExprValue::Int(10)
+    );
+}
@@ -0,0 +1,195 @@
+use expr_lang::diagnostics::{codes::ErrorCode, Diagnostics};
+use expr_lang::error::Severity;
+use expr_lang::front::{parser::Parser, scanner::Scanner, source_file::SourceFile};
+use expr_lang::middle::checker::Checker;
+use expr_lang::middle::lints::{Lint, LintLevel, LintLevels, Linter};
+
+fn lint_source(contents: &str, levels: &LintLevels) -> Vec<(ErrorCode, Severity, usize)> {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::from_contents("test.expr", contents).chars);
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let mut ast = parser.parse(&mut diagnostics);
+    let mut checker = Checker::new();
+    checker.check(&mut ast, &mut diagnostics);
+    assert!(!diagnostics.has_errors());
+
+    Linter::new(levels).lint(&mut ast, &mut diagnostics);
+    diagnostics
+        .sorted()
+        .into_iter()
+        .map(|err| (err.code(), err.severity(), err.location().unwrap().line))
+        .collect()
+}
+
+fn warnings(contents: &str) -> Vec<(ErrorCode, usize)> {
+    lint_source(contents, &LintLevels::new())
+        .into_iter()
+        .map(|(code, severity, line)| {
+            assert_eq!(severity, Severity::Warning);
+            (code, line)
+        })
+        .collect()
+}
+
+#[test]
+fn unused_variables() {
+    assert_eq!(
+        warnings("let a = 1\nlet b = 2\nprint(b)"),
+        vec![(ErrorCode::UnusedVariable, 1)]
+    );
+    assert_eq!(warnings("let a = 1\na += 1"), vec![]);
+}
+
+#[test]
+fn dead_assignments() {
+    assert_eq!(
+        warnings("let a = 1\na = 2\nprint(a)"),
+        vec![(ErrorCode::DeadAssignment, 1)]
+    );
+    assert_eq!(warnings("let a = 1\na = a + 1\nprint(a)"), vec![]);
+    assert_eq!(warnings("let a = 1\nprint(a)\na = 2\nprint(a)"), vec![]);
+}
+
+#[test]
+fn shadowed_variables_are_distinct() {
+    assert_eq!(
+        warnings("let a = 1\n{\nlet a = 2\nprint(a)\n}\nprint(a)"),
+        vec![]
+    );
+    assert_eq!(
+        warnings("let a = 1\n{\nlet a = 2\n}\nprint(a)"),
+        vec![(ErrorCode::UnusedVariable, 3)]
+    );
+    assert_eq!(
+        warnings("let a = 1\n{\na = 2\n}\nprint(a)"),
+        vec![(ErrorCode::DeadAssignment, 1)]
+    );
+}
+
+#[test]
+fn assignments_in_branches() {
+    assert_eq!(warnings("let a = 1\nif true { a = 2 }\nprint(a)"), vec![]);
+    assert_eq!(
+        warnings("let a = 1\nif true { a = 2 } else { a = 3 }\nprint(a)"),
+        vec![]
+    );
+    assert_eq!(
+        warnings("let a = 1\nif true { a = 2 }\na = 3\nprint(a)"),
+        vec![(ErrorCode::DeadAssignment, 2)]
+    );
+}
+
+#[test]
+fn assignments_in_loops() {
+    assert_eq!(
+        warnings("let a = 0\nlet b = 0\nwhile a < 3 {\nprint(b)\nb = a\na += 1\n}"),
+        vec![]
+    );
+    assert_eq!(
+        warnings("let a = 0\nfor i in 0..3 {\na = 1\nif i > 1 { break }\na = i\n}\nprint(a)"),
+        vec![]
+    );
+    assert_eq!(
+        warnings("let a = 0\nfor i in 0..3 {\na = 1\na = i\n}\nprint(a)"),
+        vec![(ErrorCode::DeadAssignment, 3)]
+    );
+    assert_eq!(warnings("for i in 0..3 { print(1) }"), vec![]);
+}
+
+#[test]
+fn function_bodies() {
+    assert_eq!(
+        warnings("fn f(x: int, y: int) -> int { x }\nprint(f(1, 2))"),
+        vec![]
+    );
+    assert_eq!(
+        warnings("fn f(x: int) -> int {\nlet y = x\ny = 2\ny\n}\nprint(f(1))"),
+        vec![(ErrorCode::DeadAssignment, 2)]
+    );
+}
+
+#[test]
+fn closures_read_their_captures() {
+    assert_eq!(
+        warnings("let k = 1\nlet f = |x: int, y: int| x + k\nprint(f(1, 2))"),
+        vec![]
+    );
+    assert_eq!(
+        warnings("let k = 1\nk = 2\nlet f = |x: int| x + k\nprint(f(1))"),
+        vec![(ErrorCode::DeadAssignment, 1)]
+    );
+}
+
+#[test]
+fn assignments_in_ternaries() {
+    assert_eq!(
+        warnings("let a = 1\nlet b = 0\nb = a > 0 ? a++ : a--\nprint(a + b)"),
+        vec![(ErrorCode::DeadAssignment, 2)]
+    );
+}
+
+#[test]
+fn self_assignment() {
+    assert_eq!(
+        warnings("let a = 1\na = a\nprint(a)"),
+        vec![(ErrorCode::SelfAssignment, 2)]
+    );
+    assert_eq!(warnings("let a = 1\na -= a\nprint(a)"), vec![]);
+}
+
+#[test]
+fn constant_conditions() {
+    assert_eq!(
+        warnings("let a = 1\nprint(a == a)\nprint((-a) < (-a))\nprint(a + 1 >= a + 1)"),
+        vec![
+            (ErrorCode::ConstantCondition, 2),
+            (ErrorCode::ConstantCondition, 3),
+            (ErrorCode::ConstantCondition, 4),
+        ]
+    );
+    assert_eq!(
+        warnings("let a = 1\nlet b = 2\nprint(a == b)\nprint(a + 1 >= 1 + a)"),
+        vec![]
+    );
+    assert_eq!(
+        warnings("let i = 0\nprint(i++ == i++)\nprint(--i < --i)"),
+        vec![]
+    );
+    // a NaN is not equal to itself, nor is a value that holds one.
+    assert_eq!(
+        warnings("let a = 0.0 / 0.0\nlet t = (a, 1)\nprint(a == a)\nprint(t == t)\nprint(some(a) == some(a))"),
+        vec![]
+    );
+    assert_eq!(
+        warnings("let t = (1, \"a\")\nprint(t == t)"),
+        vec![(ErrorCode::ConstantCondition, 2)]
+    );
+}
+
+#[test]
+fn unary_plus() {
+    assert_eq!(
+        warnings("let a = +1\nprint(a)"),
+        vec![(ErrorCode::UnaryPlus, 1)]
+    );
+}
+
+#[test]
+fn lints_can_be_allowed_and_denied() {
+    let source = "let a = +1\na = 2";
+
+    let mut levels = LintLevels::new();
+    levels.set(Lint::UnaryPlus, LintLevel::Allow);
+    levels.set(Lint::DeadAssignments, LintLevel::Deny);
+    assert_eq!(
+        lint_source(source, &levels),
+        vec![
+            (ErrorCode::DeadAssignment, Severity::Error, 1),
+            (ErrorCode::UnusedVariable, Severity::Warning, 1),
+        ]
+    );
+
+    levels.set_all(LintLevel::Allow);
+    assert_eq!(lint_source(source, &levels), vec![]);
+}
@@ -3,6 +3,8 @@ mod common;
 use std::io;
 use std::path::PathBuf;
 
+use expr_lang::diagnostics::Diagnostics;
+use expr_lang::front::ast::{BinaryOperator, Expr, Pattern, Type, UnaryOperator};
 use expr_lang::front::{parser::Parser, scanner::Scanner, source_file::SourceFile};
 
 #[test]
@@ -18,9 +20,390 @@ fn all_parser_tests() -> io::Result<()> {
 }
 
 fn run_test(test_file: PathBuf) {
-    let mut scanner = Scanner::new(SourceFile::new(test_file).chars);
-    scanner.scan_all();
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::new(test_file).unwrap().chars);
+    scanner.scan_all(&mut diagnostics);
     let mut parser = Parser::new(scanner.tokens);
-    let ast = parser.parse();
+    let ast = parser.parse(&mut diagnostics);
     println!("{:#?}", ast);
+    assert!(!diagnostics.has_errors());
+}
+
+#[test]
+fn expr_spans() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::from_contents("test.expr", "a = 1 +\n  22").chars);
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+
+    let span = ast.exprs[0].span();
+    assert_eq!((span.start.line, span.start.col), (1, 1));
+    assert_eq!((span.end.line, span.end.col), (2, 4));
+
+    if let Expr::AssignExpr(ref ass_expr) = ast.exprs[0] {
+        assert_eq!(
+            (ass_expr.op_span.start.col, ass_expr.op_span.end.col),
+            (3, 3)
+        );
+        if let Expr::BinaryExpr(ref bin_expr) = *ass_expr.expr {
+            assert_eq!(
+                (bin_expr.op_span.start.line, bin_expr.op_span.start.col),
+                (1, 7)
+            );
+            assert_eq!(bin_expr.rhs.span().start.col, 3);
+        } else {
+            panic!("expected a binary expression");
+        }
+    } else {
+        panic!("expected an assignment expression");
+    }
+}
+
+#[test]
+fn lambda_bodies_extend_to_the_right() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner =
+        Scanner::new(SourceFile::from_contents("test.expr", "f(|x, y: int| x + y, || 1)").chars);
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+
+    if let Expr::CallExpr(ref call_expr) = ast.exprs[0] {
+        assert_eq!(call_expr.args.len(), 2);
+        match (&call_expr.args[0], &call_expr.args[1]) {
+            (Expr::LambdaExpr(ref lhs), Expr::LambdaExpr(ref rhs)) => {
+                assert_eq!(lhs.params.len(), 2);
+                assert!(lhs.params[0].annotation.is_none());
+                assert!(matches!(*lhs.body, Expr::BinaryExpr(_)));
+                assert!(rhs.params.is_empty());
+            }
+            _ => panic!("expected lambda expressions"),
+        }
+    } else {
+        panic!("expected a call expression");
+    }
+}
+
+#[test]
+fn postfix_operators_continue_only_on_the_same_line() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::from_contents("test.expr", "a++\nb\n--c").chars);
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+
+    let ops = ast
+        .exprs
+        .iter()
+        .map(|expr| match *expr {
+            Expr::UnaryExpr(ref un_expr) => Some(un_expr.op),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ops,
+        vec![
+            Some(UnaryOperator::PostfixIncrement),
+            None,
+            Some(UnaryOperator::PrefixDecrement),
+        ]
+    );
+}
+
+#[test]
+fn ternaries_are_right_associative_and_bind_below_or() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner =
+        Scanner::new(SourceFile::from_contents("test.expr", "x = a || b ? 1 : c ? 2 : 3").chars);
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+
+    let ternary_expr = match ast.exprs[0] {
+        Expr::AssignExpr(ref ass_expr) => match *ass_expr.expr {
+            Expr::TernaryExpr(ref ternary_expr) => ternary_expr,
+            _ => panic!("expected a ternary expression"),
+        },
+        _ => panic!("expected an assignment expression"),
+    };
+    assert!(matches!(*ternary_expr.cond, Expr::BinaryExpr(_)));
+    assert!(matches!(*ternary_expr.then_expr, Expr::IntegerExpr(_)));
+    assert!(matches!(*ternary_expr.else_expr, Expr::TernaryExpr(_)));
+}
+
+#[test]
+fn prefix_operators_bind_tighter_than_binary_ones() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::from_contents("test.expr", "-1 + 2 * -a").chars);
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+
+    // (-1) + (2 * (-a)), rather than -(1 + 2 * -a).
+    let bin_expr = match ast.exprs[0] {
+        Expr::BinaryExpr(ref bin_expr) => bin_expr,
+        _ => panic!("expected a binary expression"),
+    };
+    assert_eq!(bin_expr.op, BinaryOperator::Add);
+    assert!(matches!(
+        *bin_expr.lhs,
+        Expr::UnaryExpr(ref un_expr) if un_expr.op == UnaryOperator::UnaryMinus
+    ));
+    match *bin_expr.rhs {
+        Expr::BinaryExpr(ref rhs) => {
+            assert_eq!(rhs.op, BinaryOperator::Mul);
+            assert!(matches!(*rhs.rhs, Expr::UnaryExpr(_)));
+        }
+        _ => panic!("expected a binary expression"),
+    }
+}
+
+#[test]
+fn nested_list_types_and_indices() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(
+        SourceFile::from_contents(
+            "test.expr",
+            "let xs: List<List<int>> = [[1]]\nxs[0][0]\n[2]",
+        )
+        .chars,
+    );
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(ast.exprs.len(), 3);
+
+    if let Expr::LetExpr(ref let_expr) = ast.exprs[0] {
+        let (ref typ, ref span) = *let_expr.annotation.as_ref().unwrap();
+        assert_eq!(typ.to_string(), "List<List<int>>");
+        assert_eq!((span.start.col, span.end.col), (9, 23));
+    } else {
+        panic!("expected a let expression");
+    }
+
+    if let Expr::IndexExpr(ref index_expr) = ast.exprs[1] {
+        assert!(matches!(*index_expr.list, Expr::IndexExpr(_)));
+        assert_eq!(index_expr.span.end.col, 8);
+    } else {
+        panic!("expected an index expression");
+    }
+
+    assert!(matches!(ast.exprs[2], Expr::ListExpr(_)));
+}
+
+#[test]
+fn tuples_and_parenthesised_exprs() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(
+        SourceFile::from_contents(
+            "test.expr",
+            "(a)\n(a, (b, c)).1.0\nlet p: (int, (bool)) = (1, true)",
+        )
+        .chars,
+    );
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(ast.exprs.len(), 3);
+
+    assert!(matches!(ast.exprs[0], Expr::VnameExpr(_)));
+
+    if let Expr::FieldExpr(ref field_expr) = ast.exprs[1] {
+        assert_eq!(field_expr.field, "0");
+        assert_eq!(field_expr.span.end.col, 15);
+        match *field_expr.expr {
+            Expr::FieldExpr(ref inner) => {
+                assert_eq!(inner.field, "1");
+                assert!(
+                    matches!(*inner.expr, Expr::TupleExpr(ref tuple) if tuple.elems.len() == 2)
+                );
+            }
+            _ => panic!("expected a field expression"),
+        }
+    } else {
+        panic!("expected a field expression");
+    }
+
+    if let Expr::LetExpr(ref let_expr) = ast.exprs[2] {
+        let (ref typ, _) = *let_expr.annotation.as_ref().unwrap();
+        assert_eq!(typ.to_string(), "(int, bool)");
+    } else {
+        panic!("expected a let expression");
+    }
+}
+
+#[test]
+fn records_and_blocks() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(
+        SourceFile::from_contents(
+            "test.expr",
+            "struct Order { amount: int, urgent: bool, }\nOrder { urgent: true, amount: 1, }.amount\nif x { y }\nwhile x {\n  y\n}",
+        )
+        .chars,
+    );
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(ast.exprs.len(), 4);
+
+    if let Expr::StructExpr(ref struct_expr) = ast.exprs[0] {
+        assert_eq!(struct_expr.name.spelling, "Order");
+        assert_eq!(struct_expr.fields.len(), 2);
+        assert_eq!(struct_expr.fields[1].typ, Type::BoolType);
+    } else {
+        panic!("expected a struct expression");
+    }
+
+    if let Expr::FieldExpr(ref field_expr) = ast.exprs[1] {
+        assert_eq!(field_expr.field, "amount");
+        match *field_expr.expr {
+            Expr::RecordExpr(ref record_expr) => {
+                assert_eq!(record_expr.fields[0].0.spelling, "urgent");
+                assert_eq!(record_expr.span.end.col, 34);
+            }
+            _ => panic!("expected a record expression"),
+        }
+    } else {
+        panic!("expected a field expression");
+    }
+
+    assert!(matches!(ast.exprs[2], Expr::IfExpr(_)));
+    assert!(matches!(ast.exprs[3], Expr::WhileExpr(_)));
+}
+
+#[test]
+fn calls_continue_only_on_the_same_line() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner =
+        Scanner::new(SourceFile::from_contents("test.expr", "len(\"ab\")\na\n(1 + 2)").chars);
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(ast.exprs.len(), 3);
+
+    if let Expr::CallExpr(ref call_expr) = ast.exprs[0] {
+        assert_eq!(call_expr.args.len(), 1);
+        assert_eq!(call_expr.span.end.col, 9);
+    } else {
+        panic!("expected a call expression");
+    }
+}
+
+#[test]
+fn closures_start_on_a_new_line() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(
+        SourceFile::from_contents("test.expr", "a | b\nk * 2\n|x: int| x\nk\n|| 1").chars,
+    );
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(ast.exprs.len(), 5);
+
+    assert!(
+        matches!(ast.exprs[0], Expr::BinaryExpr(ref bin_expr) if bin_expr.op == BinaryOperator::BitwiseOr)
+    );
+    assert!(matches!(ast.exprs[2], Expr::LambdaExpr(_)));
+    assert!(matches!(ast.exprs[4], Expr::LambdaExpr(_)));
+}
+
+#[test]
+fn enums_and_matches() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(
+        SourceFile::from_contents(
+            "test.expr",
+            "enum Status { Ok, Err(int), }\nmatch s {\n  Err(-1) => 0\n  Err(code) => code,\n  _ => 1\n}",
+        )
+        .chars,
+    );
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(ast.exprs.len(), 2);
+
+    if let Expr::EnumExpr(ref enum_expr) = ast.exprs[0] {
+        assert_eq!(enum_expr.name.spelling, "Status");
+        assert_eq!(enum_expr.variants.len(), 2);
+        assert!(enum_expr.variants[0].payload.is_empty());
+        assert_eq!(enum_expr.variants[1].payload[0].0, Type::IntType);
+    } else {
+        panic!("expected an enum expression");
+    }
+
+    if let Expr::MatchExpr(ref match_expr) = ast.exprs[1] {
+        assert_eq!(match_expr.arms.len(), 3);
+        match match_expr.arms[0].pattern {
+            Pattern::Variant(ref variant) => {
+                assert_eq!(variant.name.spelling, "Err");
+                assert!(
+                    matches!(variant.args[0], Pattern::Int(ref int_expr) if int_expr.value == -1)
+                );
+            }
+            _ => panic!("expected a variant pattern"),
+        }
+        assert!(matches!(match_expr.arms[2].pattern, Pattern::Wildcard(_)));
+        assert_eq!(match_expr.span.end.line, 6);
+    } else {
+        panic!("expected a match expression");
+    }
+}
+
+#[test]
+fn options_and_coalescing() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(
+        SourceFile::from_contents(
+            "test.expr",
+            "let x: Option<List<int>> = none\na ?? b ?? c || d",
+        )
+        .chars,
+    );
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let ast = parser.parse(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(ast.exprs.len(), 2);
+
+    if let Expr::LetExpr(ref let_expr) = ast.exprs[0] {
+        assert_eq!(
+            let_expr.annotation.as_ref().map(|(typ, _)| typ),
+            Some(&Type::OptionType(Box::new(Type::ListType(Box::new(
+                Type::IntType
+            )))))
+        );
+        assert!(matches!(*let_expr.expr, Expr::NoneExpr(_)));
+    } else {
+        panic!("expected a let expression");
+    }
+
+    // ?? is right-associative, and binds more loosely than ||.
+    if let Expr::BinaryExpr(ref bin_expr) = ast.exprs[1] {
+        assert_eq!(bin_expr.op, BinaryOperator::Coalesce);
+        assert!(matches!(*bin_expr.lhs, Expr::VnameExpr(_)));
+        match *bin_expr.rhs {
+            Expr::BinaryExpr(ref rhs) => {
+                assert_eq!(rhs.op, BinaryOperator::Coalesce);
+                assert!(
+                    matches!(*rhs.rhs, Expr::BinaryExpr(ref or_expr) if or_expr.op == BinaryOperator::LogicalOr)
+                );
+            }
+            _ => panic!("expected a binary expression"),
+        }
+    } else {
+        panic!("expected a binary expression");
+    }
 }
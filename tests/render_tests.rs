@@ -0,0 +1,125 @@
+use expr_lang::diagnostics::{json, render::Renderer, Diagnostics};
+use expr_lang::front::{parser::Parser, scanner::Scanner, source_file::SourceFile};
+use expr_lang::middle::checker::Checker;
+
+fn check_source(source_file: &SourceFile) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(source_file.chars.clone());
+    scanner.scan_all(&mut diagnostics);
+    let mut parser = Parser::new(scanner.tokens);
+    let mut ast = parser.parse(&mut diagnostics);
+    let mut checker = Checker::new();
+    checker.check(&mut ast, &mut diagnostics);
+    diagnostics
+}
+
+fn render_source(contents: &str) -> String {
+    let source_file = SourceFile::from_contents("test.expr", contents);
+    let diagnostics = check_source(&source_file);
+
+    let renderer = Renderer::new(&source_file, false);
+    diagnostics
+        .sorted()
+        .into_iter()
+        .map(|err| renderer.render(err))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[test]
+fn render_primary_span() {
+    assert_eq!(
+        render_source("let a = 1 $ 2"),
+        "error[E0002]: unexpected character '$'\n \
+         --> test.expr:1:11\n  \
+         |\n\
+         1 | let a = 1 $ 2\n  \
+         |           ^\n"
+    );
+}
+
+#[test]
+fn render_secondary_labels_and_notes() {
+    assert_eq!(
+        render_source("let a = 1\nlet b = 2\na = false\nprint(b)"),
+        "error[E0012]: variable \"a\" was declared as int, but the value has type bool\n \
+         --> test.expr:3:5\n  \
+         |\n\
+         1 | let a = 1\n  \
+         |     - declared as int here\n\
+         ...\n\
+         3 | a = false\n  \
+         |     ^^^^^\n  \
+         |\n  \
+         = note: the type of a variable cannot change once declared\n"
+    );
+}
+
+#[test]
+fn render_operand_types() {
+    assert_eq!(
+        render_source("[1] < [2]"),
+        "error[E0015]: the left operand of < has type List<int>, but the operator expects int\n \
+         --> test.expr:1:1\n  \
+         |\n\
+         1 | [1] < [2]\n  \
+         | ^^^\n  \
+         |     - expected int due to this operator\n"
+    );
+    assert_eq!(
+        render_source("let a = [1]\na += 1"),
+        "error[E0015]: the variable has type List<int>, but the operator += expects int\n \
+         --> test.expr:2:1\n  \
+         |\n\
+         2 | a += 1\n  \
+         | ^\n  \
+         |   -- expected int due to this operator\n"
+    );
+}
+
+fn render_json(contents: &str) -> Vec<String> {
+    let source_file = SourceFile::from_contents("test.expr", contents);
+    let diagnostics = check_source(&source_file);
+    diagnostics
+        .sorted()
+        .into_iter()
+        .map(json::to_json)
+        .collect()
+}
+
+#[test]
+fn render_json_one_object_per_error() {
+    assert_eq!(
+        render_json("let a = 1\na = false\nlet b = 1 $ 2"),
+        vec![
+            "{\"code\":\"E0012\",\"kind\":\"CheckerError\",\"severity\":\"error\",\
+             \"message\":\"variable \\\"a\\\" was declared as int, but the value has type bool\",\
+             \"file\":\"test.expr\",\
+             \"span\":{\"start\":{\"line\":2,\"col\":5},\"end\":{\"line\":2,\"col\":9}},\
+             \"labels\":[{\"file\":\"test.expr\",\
+             \"span\":{\"start\":{\"line\":1,\"col\":5},\"end\":{\"line\":1,\"col\":5}},\
+             \"message\":\"declared as int here\"}],\
+             \"notes\":[\"the type of a variable cannot change once declared\"],\
+             \"help\":null}",
+            "{\"code\":\"E0002\",\"kind\":\"ScannerError\",\"severity\":\"error\",\
+             \"message\":\"unexpected character '$'\",\
+             \"file\":\"test.expr\",\
+             \"span\":{\"start\":{\"line\":3,\"col\":11},\"end\":{\"line\":3,\"col\":11}},\
+             \"labels\":[],\"notes\":[],\"help\":null}",
+        ]
+    );
+}
+
+#[test]
+fn render_json_escapes_strings() {
+    assert_eq!(
+        render_json("let a = 1 \\ 2"),
+        vec![
+            "{\"code\":\"E0002\",\"kind\":\"ScannerError\",\"severity\":\"error\",\
+             \"message\":\"unexpected character '\\\\\\\\'\",\
+             \"file\":\"test.expr\",\
+             \"span\":{\"start\":{\"line\":1,\"col\":11},\"end\":{\"line\":1,\"col\":11}},\
+             \"labels\":[],\"notes\":[],\"help\":null}",
+        ]
+    );
+}
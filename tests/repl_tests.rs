@@ -0,0 +1,211 @@
+use expr_lang::backend::runtime::{ExprValue, List};
+use expr_lang::front::{ast::Type, source_file::SourceFile};
+use expr_lang::repl::Repl;
+use std::sync::Arc;
+
+fn eval(repl: &mut Repl, input: &str) -> Option<(ExprValue, Option<Type>)> {
+    repl.eval(&SourceFile::from_contents("<repl>", input)).ok()
+}
+
+#[test]
+fn state_persists_across_entries() {
+    let mut repl = Repl::new();
+    assert_eq!(
+        eval(&mut repl, "let a = 1 + 2"),
+        Some((ExprValue::Int(3), Some(Type::IntType)))
+    );
+    assert_eq!(
+        eval(&mut repl, "a * 2 > 5"),
+        Some((ExprValue::Bool(true), Some(Type::BoolType)))
+    );
+}
+
+#[test]
+fn failed_entries_do_not_change_state() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "let a = 1");
+    assert!(eval(&mut repl, "a = 2 let b = a / 0").is_none());
+    assert!(eval(&mut repl, "let b = true let c = 1 + false").is_none());
+    assert_eq!(
+        eval(&mut repl, "a"),
+        Some((ExprValue::Int(1), Some(Type::IntType)))
+    );
+    assert!(eval(&mut repl, "b").is_none());
+}
+
+#[test]
+fn failed_entries_do_not_change_shared_values() {
+    let mut repl = Repl::new();
+    eval(
+        &mut repl,
+        "struct P { x: int } let xs = [1] let p = P { x: 1 }",
+    );
+    assert!(eval(&mut repl, "push(xs, 2) p.x = 2 1 / 0").is_none());
+    assert_eq!(
+        eval(&mut repl, "xs").map(|(val, _)| val.to_string()),
+        Some("[1]".to_owned())
+    );
+    assert_eq!(
+        eval(&mut repl, "p.x"),
+        Some((ExprValue::Int(1), Some(Type::IntType)))
+    );
+}
+
+#[test]
+fn unbalanced_parentheses_are_incomplete() {
+    assert!(Repl::is_incomplete("print((1 + 2)\n"));
+    assert!(!Repl::is_incomplete("print((1 + 2)\n* 3)\n"));
+    assert!(!Repl::is_incomplete("let a = 1 // (\n"));
+    assert!(Repl::is_incomplete("let a = {\n"));
+    assert!(!Repl::is_incomplete("let a = {\n1\n}\n"));
+    assert!(Repl::is_incomplete("let xs = [1,\n"));
+    assert!(!Repl::is_incomplete("let xs = [1,\n2]\n"));
+}
+
+#[test]
+fn type_of_does_not_evaluate() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "let a = 1");
+    let type_of = |repl: &Repl, input| repl.type_of(&SourceFile::from_contents("<repl>", input));
+    assert_eq!(type_of(&repl, "a * 2 > 1").ok(), Some(Some(Type::BoolType)));
+    assert_eq!(
+        type_of(&repl, "let b = a + 1").ok(),
+        Some(Some(Type::IntType))
+    );
+    assert!(type_of(&repl, "a + true").is_err());
+    assert!(eval(&mut repl, "b").is_none());
+}
+
+#[test]
+fn env_lists_variables_and_functions() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "let b = 2");
+    eval(&mut repl, "fn square(x: int) -> int { x * x }");
+    eval(&mut repl, "let a = [1]");
+    assert_eq!(
+        repl.env(),
+        vec![
+            "a : List<int> = [1]",
+            "b : int = 2",
+            "square : fn(int) -> int"
+        ]
+    );
+}
+
+#[test]
+fn reset_discards_bindings() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "let a = 1");
+    repl.reset();
+    assert!(eval(&mut repl, "a").is_none());
+    assert_eq!(
+        eval(&mut repl, "let a = true"),
+        Some((ExprValue::Bool(true), Some(Type::BoolType)))
+    );
+}
+
+#[test]
+fn increments_have_the_old_or_the_new_value() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "let a = 1");
+    let int = |ival| Some((ExprValue::Int(ival), Some(Type::IntType)));
+    assert_eq!(eval(&mut repl, "a++"), int(1));
+    assert_eq!(eval(&mut repl, "++a"), int(3));
+    assert_eq!(eval(&mut repl, "a--"), int(3));
+    assert_eq!(eval(&mut repl, "--a"), int(1));
+    assert_eq!(eval(&mut repl, "a"), int(1));
+}
+
+#[test]
+fn lists_are_shared_between_variables() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "let xs = [1, 2]");
+    eval(&mut repl, "let ys = xs");
+    eval(&mut repl, "push(ys, 3)");
+    eval(&mut repl, "ys[0] += 10");
+    let list = |elems: Vec<i32>| {
+        ExprValue::List(Arc::new(List::new(
+            elems.into_iter().map(ExprValue::Int).collect(),
+        )))
+    };
+    assert_eq!(
+        eval(&mut repl, "xs"),
+        Some((
+            list(vec![11, 2, 3]),
+            Some(Type::ListType(Box::new(Type::IntType)))
+        ))
+    );
+    assert_eq!(
+        eval(&mut repl, "pop(xs)"),
+        Some((ExprValue::Int(3), Some(Type::IntType)))
+    );
+    assert_eq!(eval(&mut repl, "len(ys)").unwrap().0, ExprValue::Int(2));
+}
+
+#[test]
+fn tuple_assignments_swap() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "let a = 1");
+    eval(&mut repl, "let b = \"two\"");
+    eval(&mut repl, "let p = (b, a)");
+    eval(&mut repl, "(b, a) = (\"one\", 2)");
+    assert_eq!(
+        eval(&mut repl, "p"),
+        Some((
+            ExprValue::Tuple(vec![ExprValue::Str("two".to_owned()), ExprValue::Int(1)]),
+            Some(Type::TupleType(vec![Type::StringType, Type::IntType]))
+        ))
+    );
+    assert_eq!(
+        eval(&mut repl, "p.0 + b"),
+        Some((ExprValue::Str("twoone".to_owned()), Some(Type::StringType)))
+    );
+}
+
+#[test]
+fn records_are_shared() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "struct Order { amount: int, urgent: bool }");
+    eval(&mut repl, "let a = Order { urgent: false, amount: 1 }");
+    eval(&mut repl, "let b = a");
+    eval(&mut repl, "b.amount += 9");
+    assert_eq!(
+        eval(&mut repl, "a.amount"),
+        Some((ExprValue::Int(10), Some(Type::IntType)))
+    );
+    let (val, typ) = eval(&mut repl, "a").unwrap();
+    assert_eq!(val.to_string(), "Order { amount: 10, urgent: false }");
+    assert_eq!(typ, Some(Type::NamedType("Order".to_owned(), Some(0))));
+}
+
+#[test]
+fn variants_across_entries() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "enum Status { Ok, Err(int) }");
+    eval(&mut repl, "let s = Err(7)");
+    let (val, typ) = eval(&mut repl, "s").unwrap();
+    assert_eq!(val.to_string(), "Err(7)");
+    assert_eq!(typ, Some(Type::NamedType("Status".to_owned(), Some(0))));
+    assert_eq!(
+        eval(&mut repl, "match s { Ok => 0, Err(code) => code }"),
+        Some((ExprValue::Int(7), Some(Type::IntType)))
+    );
+}
+
+#[test]
+fn options_across_entries() {
+    let mut repl = Repl::new();
+    eval(&mut repl, "let missing: Option<string> = none");
+    let (val, typ) = eval(&mut repl, "missing").unwrap();
+    assert_eq!(val.to_string(), "none");
+    assert_eq!(typ, Some(Type::OptionType(Box::new(Type::StringType))));
+    eval(&mut repl, "missing = some(\"here\")");
+    assert_eq!(
+        eval(&mut repl, "missing ?? \"gone\""),
+        Some((ExprValue::Str("here".to_owned()), Some(Type::StringType)))
+    );
+    assert_eq!(
+        eval(&mut repl, "is_some(missing)"),
+        Some((ExprValue::Bool(true), Some(Type::BoolType)))
+    );
+}
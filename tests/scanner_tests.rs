@@ -3,7 +3,8 @@ mod common;
 use std::io;
 use std::path::PathBuf;
 
-use expr_lang::front::{scanner::Scanner, source_file::SourceFile};
+use expr_lang::diagnostics::{codes::ErrorCode, Diagnostics};
+use expr_lang::front::{scanner::Scanner, source_file::SourceFile, token::TokenKind};
 
 #[test]
 fn all_scanner_tests() -> io::Result<()> {
@@ -18,9 +19,205 @@ fn all_scanner_tests() -> io::Result<()> {
 }
 
 fn run_test(test_file: PathBuf) {
-    let mut scanner = Scanner::new(SourceFile::new(test_file).chars);
-    scanner.scan_all();
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::new(test_file).unwrap().chars);
+    scanner.scan_all(&mut diagnostics);
     for token in scanner.tokens {
         println!("{:?}", token);
     }
+    assert!(!diagnostics.has_errors());
+}
+
+#[test]
+fn equal_is_a_single_token() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::from_contents("test.expr", "a == b = c").chars);
+    scanner.scan_all(&mut diagnostics);
+    assert_eq!(
+        scanner
+            .tokens
+            .iter()
+            .map(|token| token.kind)
+            .collect::<Vec<_>>(),
+        vec![
+            TokenKind::Identifier,
+            TokenKind::Equal,
+            TokenKind::Identifier,
+            TokenKind::Assign,
+            TokenKind::Identifier,
+            TokenKind::Eof,
+        ]
+    );
+}
+
+#[test]
+fn increments_are_single_tokens() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::from_contents("test.expr", "a++ + --b - -c").chars);
+    scanner.scan_all(&mut diagnostics);
+    assert_eq!(
+        scanner
+            .tokens
+            .iter()
+            .map(|token| token.kind)
+            .collect::<Vec<_>>(),
+        vec![
+            TokenKind::Identifier,
+            TokenKind::Increment,
+            TokenKind::Plus,
+            TokenKind::Decrement,
+            TokenKind::Identifier,
+            TokenKind::Minus,
+            TokenKind::Minus,
+            TokenKind::Identifier,
+            TokenKind::Eof,
+        ]
+    );
+}
+
+#[test]
+fn float_literals() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner =
+        Scanner::new(SourceFile::from_contents("test.expr", "1 1.5 2e10 2.5E-3 2e").chars);
+    scanner.scan_all(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(
+        scanner
+            .tokens
+            .iter()
+            .map(|token| (token.kind, token.spelling.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (TokenKind::Integer, "1"),
+            (TokenKind::Float, "1.5"),
+            (TokenKind::Float, "2e10"),
+            (TokenKind::Float, "2.5E-3"),
+            (TokenKind::Integer, "2"),
+            (TokenKind::Identifier, "e"),
+            (TokenKind::Eof, ""),
+        ]
+    );
+}
+
+#[test]
+fn ranges_are_not_floats() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::from_contents("test.expr", "0..10 1.5..2").chars);
+    scanner.scan_all(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(
+        scanner
+            .tokens
+            .iter()
+            .map(|token| (token.kind, token.spelling.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (TokenKind::Integer, "0"),
+            (TokenKind::DotDot, ".."),
+            (TokenKind::Integer, "10"),
+            (TokenKind::Float, "1.5"),
+            (TokenKind::DotDot, ".."),
+            (TokenKind::Integer, "2"),
+            (TokenKind::Eof, ""),
+        ]
+    );
+}
+
+#[test]
+fn fields_are_not_floats() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(SourceFile::from_contents("test.expr", "t.0.1 0.1").chars);
+    scanner.scan_all(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(
+        scanner
+            .tokens
+            .iter()
+            .map(|token| (token.kind, token.spelling.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (TokenKind::Identifier, "t"),
+            (TokenKind::Dot, "."),
+            (TokenKind::Integer, "0"),
+            (TokenKind::Dot, "."),
+            (TokenKind::Integer, "1"),
+            (TokenKind::Float, "0.1"),
+            (TokenKind::Eof, ""),
+        ]
+    );
+}
+
+#[test]
+fn underscores_in_identifiers() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner =
+        Scanner::new(SourceFile::from_contents("test.expr", "is_some(x) ?? none ? _ : y").chars);
+    scanner.scan_all(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(
+        scanner
+            .tokens
+            .iter()
+            .map(|token| (token.kind, token.spelling.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (TokenKind::Identifier, "is_some"),
+            (TokenKind::LeftParen, "("),
+            (TokenKind::Identifier, "x"),
+            (TokenKind::RightParen, ")"),
+            (TokenKind::QuestionQuestion, "??"),
+            (TokenKind::None, "none"),
+            (TokenKind::Question, "?"),
+            (TokenKind::Underscore, "_"),
+            (TokenKind::Colon, ":"),
+            (TokenKind::Identifier, "y"),
+            (TokenKind::Eof, ""),
+        ]
+    );
+}
+
+#[test]
+fn string_literals() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(
+        SourceFile::from_contents("test.expr", r#""a\tb" "q\"\\" "\u{48}i" "print""#).chars,
+    );
+    scanner.scan_all(&mut diagnostics);
+    assert!(!diagnostics.has_errors());
+    assert_eq!(
+        scanner
+            .tokens
+            .iter()
+            .map(|token| (token.kind, token.spelling.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (TokenKind::String, "a\tb"),
+            (TokenKind::String, "q\"\\"),
+            (TokenKind::String, "Hi"),
+            (TokenKind::String, "print"),
+            (TokenKind::Eof, ""),
+        ]
+    );
+}
+
+#[test]
+fn invalid_string_literals() {
+    let mut diagnostics = Diagnostics::new();
+    let mut scanner = Scanner::new(
+        SourceFile::from_contents("test.expr", "\"a\\qb\" \"\\u{110000}\"\n\"open").chars,
+    );
+    scanner.scan_all(&mut diagnostics);
+    assert_eq!(
+        diagnostics
+            .sorted()
+            .into_iter()
+            .map(|err| err.code())
+            .collect::<Vec<_>>(),
+        vec![
+            ErrorCode::InvalidEscape,
+            ErrorCode::InvalidEscape,
+            ErrorCode::UnterminatedString,
+        ]
+    );
 }